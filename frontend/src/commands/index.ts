@@ -1,6 +1,9 @@
 import { ref } from 'vue'
 import type { Ref } from 'vue'
 
+// 服务器地址，构建时可通过 VITE_API_URL 指定
+const API_URL = (import.meta.env.VITE_API_URL ?? 'http://localhost:8080').replace(/\/$/, '')
+
 // 命令处理状态
 export interface CommandState {
   showHint: Ref<boolean>
@@ -13,11 +16,18 @@ export interface CommandState {
   lastCommandName: Ref<string | null>
 }
 
+// 带样式的输出片段，style 对应 CSS 类 style-<style>
+export interface OutputSpan {
+  text: string
+  style?: 'directory' | 'executable' | 'error' | 'highlight' | 'link'
+}
+
 // 命令处理结果
 export interface CommandResult {
   success: boolean
   message: string
   data?: any
+  spans?: OutputSpan[]
 }
 
 // 创建命令处理状态
@@ -34,7 +44,7 @@ export const createCommandState = (): CommandState => ({
 // 获取验证码
 export const getCaptcha = async (): Promise<{ captcha: string; sessionId: string } | null> => {
   try {
-    const response = await fetch('http://localhost:8080/api/captcha')
+    const response = await fetch(`${API_URL}/api/captcha`)
     const data = await response.json()
     if (data.success) {
       return {
@@ -52,43 +62,98 @@ export const getCaptcha = async (): Promise<{ captcha: string; sessionId: string
 // 命令描述缓存
 export const commandDescriptions: Map<string, string> = new Map()
 
-// 解析help命令返回的消息，提取所有命令描述
-export const parseHelpMessage = (message: string) => {
-  const descriptions = new Map<string, string>()
-  const lines = message.split('\n')
-  
-  for (const line of lines) {
-    if (line.startsWith('- ')) {
-      const match = line.match(/^- (\w+): (.+)$/)
-      if (match) {
-        const [_, command, description] = match
-        descriptions.set(command, description)
-      }
-    }
-  }
-  
-  return descriptions
+// 补全候选项
+export interface CompletionCandidate {
+  value: string
+  kind: 'command' | 'alias' | 'flag' | 'directory' | 'file'
+  description?: string
 }
 
-// 初始化命令描述缓存
-export const initCommandDescriptions = async () => {
+// 补全结果，start 与 end 为需要替换的字符区间
+export interface CompletionResult {
+  start: number
+  end: number
+  candidates: CompletionCandidate[]
+}
+
+// 请求服务端补全
+export const requestCompletion = async (
+  line: string,
+  cursor: number,
+  authToken: string | null = null
+): Promise<CompletionResult | null> => {
   try {
-    const response = await fetch('http://localhost:8080/api/command', {
+    const headers: Record<string, string> = {
+      'Content-Type': 'application/json'
+    }
+    if (authToken) {
+      headers['Authorization'] = `Bearer ${authToken}`
+    }
+
+    const response = await fetch(`${API_URL}/api/complete`, {
       method: 'POST',
-      headers: { 'Content-Type': 'application/json' },
-      body: JSON.stringify({ command: 'help' })
+      headers,
+      body: JSON.stringify({ line, cursor })
     })
-    
     const data = await response.json()
-    if (data.success) {
-      const descriptions = parseHelpMessage(data.message)
-      // 更新缓存
-      descriptions.forEach((value, key) => {
-        commandDescriptions.set(key, value)
-      })
-    }
+    return data.success ? data.data : null
   } catch (error) {
-    console.error('获取命令描述失败:', error)
+    console.error('获取补全失败:', error)
+    return null
+  }
+}
+
+// 初始化命令描述缓存
+export const initCommandDescriptions = async () => {
+  const result = await requestCompletion('', 0)
+  if (!result) return
+
+  for (const candidate of result.candidates) {
+    if (candidate.kind === 'command' && candidate.description) {
+      commandDescriptions.set(candidate.value, candidate.description)
+    }
+  }
+}
+
+// 计算候选项的公共前缀
+const commonPrefix = (values: string[]): string => {
+  if (values.length === 0) return ''
+  let prefix = values[0]
+  for (const value of values.slice(1)) {
+    while (!value.startsWith(prefix)) {
+      prefix = prefix.slice(0, -1)
+    }
+  }
+  return prefix
+}
+
+// 处理 Tab 补全，返回补全后的输入和光标位置
+export const completeInput = async (
+  input: string,
+  cursor: number,
+  state: CommandState,
+  authToken: Ref<string | null>
+): Promise<{ value: string; cursor: number } | null> => {
+  const chars = Array.from(input)
+  const result = await requestCompletion(input, Array.from(input.slice(0, cursor)).length, authToken.value)
+  if (!result || result.candidates.length === 0) return null
+
+  const values = result.candidates.map(c => c.value)
+  let replacement = commonPrefix(values)
+  // 唯一的非目录候选项后追加空格
+  if (values.length === 1 && !replacement.endsWith('/')) {
+    replacement += ' '
+  }
+  if (values.length > 1) {
+    state.showHint.value = true
+    state.commandHint.value = values.join('  ')
+  }
+
+  const before = chars.slice(0, result.start).join('')
+  const after = chars.slice(result.end).join('')
+  return {
+    value: before + replacement + after,
+    cursor: before.length + replacement.length
   }
 }
 
@@ -107,13 +172,12 @@ export const sendCommand = async (
       headers['Authorization'] = `Bearer ${authToken}`
     }
 
-    const response = await fetch('http://localhost:8080/api/command', {
+    const response = await fetch(`${API_URL}/api/command`, {
       method: 'POST',
       headers,
       body: JSON.stringify({
         command,
-        session_id: sessionId,
-        cwd: currentPath.value
+        session_id: sessionId
       })
     })
 
@@ -128,6 +192,54 @@ export const sendCommand = async (
   }
 }
 
+// 服务端推送的事件
+export interface ServerEvent {
+  event: string
+  [key: string]: any
+}
+
+let socket: WebSocket | null = null
+
+// 建立 WebSocket 连接接收服务端推送的事件（如后台任务结束），token 变化时重新连接
+export const connectEvents = (
+  authToken: string | null,
+  onEvent: (event: ServerEvent) => void
+): void => {
+  socket?.close()
+  socket = null
+  if (!authToken) return
+
+  const ws = new WebSocket(`${API_URL.replace(/^http/, 'ws')}/api/ws?token=${encodeURIComponent(authToken)}`)
+  ws.onmessage = (message) => {
+    const data = JSON.parse(message.data)
+    if (data.type === 'event') {
+      onEvent(data)
+    }
+  }
+  ws.onerror = (error) => {
+    console.error('WebSocket 连接失败:', error)
+  }
+  socket = ws
+}
+
+// 将事件格式化为终端输出
+export const formatEvent = (event: ServerEvent): { text: string; isError: boolean } => {
+  switch (event.event) {
+    case 'job_finished':
+      return {
+        text: `[${event.job}] ${event.status === 'done' ? '已完成' : '失败'} ${event.command}`,
+        isError: event.status !== 'done'
+      }
+    case 'message':
+      return {
+        text: `[${new Date(event.sent_at).toLocaleString()} 来自 ${event.from} 的${event.broadcast ? '公告' : '消息'}] ${event.text}`,
+        isError: false
+      }
+    default:
+      return { text: JSON.stringify(event), isError: false }
+  }
+}
+
 // 处理命令输入
 export const handleCommandInput = async (
   input: string,
@@ -201,6 +313,14 @@ export const updatePath = (newPath: string) => {
   localStorage.setItem('cwd', newPath)
 }
 
+// 从服务端会话同步当前路径（工作目录由服务端维护）
+export const syncPath = async (authToken: string | null) => {
+  const response = await sendCommand('pwd', '', authToken)
+  if (response.success && response.data?.path) {
+    updatePath(response.data.path)
+  }
+}
+
 // 获取文件类型emoji
 const getFileEmoji = (name: string, isDirectory: boolean): string => {
   if (isDirectory) return '📂'
@@ -232,7 +352,7 @@ const formatLsOutput = (contents: any[], path: string): string => {
     const emoji = getFileEmoji(name, item.is_directory)
     const permissions = item.permissions
     const date = new Date(item.updated_at).toLocaleString()
-    const size = item.is_directory ? '<DIR>' : '0'
+    const size = item.is_directory ? '<DIR>' : String(item.size ?? 0)
     return `${emoji} ${permissions} ${date} ${size.padStart(8)} ${name}`
   }).join('\n')
 }
@@ -242,7 +362,7 @@ export const executeCommand = async (
   command: string,
   state: CommandState,
   authToken: Ref<string | null>,
-  onOutput: (text: string, isError: boolean, isCaptcha: boolean) => void
+  onOutput: (text: string, isError: boolean, isCaptcha: boolean, spans?: OutputSpan[]) => void
 ): Promise<void> => {
   if (state.isClearing.value) return
 
@@ -257,15 +377,13 @@ export const executeCommand = async (
   const sessionId = state.captchaData.value?.sessionId || ''
   const response = await sendCommand(command, sessionId, authToken.value)
 
-  // 特殊处理ls命令的输出
-  if (commandName === 'ls' && response.success && response.data?.contents) {
+  // 特殊处理不带选项的ls命令的输出，带选项时使用服务端格式化的结果
+  const hasFlags = command.split(/\s+/).slice(1).some(arg => arg.startsWith('-'))
+  if (commandName === 'ls' && !hasFlags && response.success && response.data?.contents) {
     const formattedOutput = formatLsOutput(response.data.contents, response.data.path)
     onOutput(formattedOutput, false, false)
   } else {
-    onOutput(response.message, !response.success, false)
-  }
-  if (command.startsWith('cd') && response.success && response.data?.token) {
-    updatePath(response.data.path)
+    onOutput(response.message, !response.success, false, response.spans)
   }
   if (command.startsWith('login ') && response.success && response.data?.token) {
     const token = response.data.token
@@ -273,14 +391,18 @@ export const executeCommand = async (
     localStorage.setItem('username', username)
     authToken.value = token
     // 更新路径
-    updatePath(`/home/${username}/`)
+    await syncPath(token)
+  } else if (commandName === 'passwd' && response.success && response.data?.token) {
+    // 修改密码后此前的 token 失效，改用服务端签发的新 token
+    localStorage.setItem('token', response.data.token)
+    authToken.value = response.data.token
   } else if (command === 'logout' && response.success) {
     localStorage.removeItem('token')
     localStorage.removeItem('username')
     authToken.value = null
     // 更新路径
     updatePath('/home/guest/')
-  } else if (command.startsWith('cd ') && response.success) {
+  } else if (['cd', 'pushd', 'popd'].includes(commandName) && response.success && response.data?.path) {
     // 更新路径
     updatePath(response.data.path)
   }
@@ -1,7 +1,8 @@
 use crate::auth::types::Claims;
 use chrono::Utc;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use sqlx::PgPool;
 use std::env;
 
 pub fn validate_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
@@ -14,16 +15,59 @@ pub fn validate_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error
     .map(|data| data.claims)
 }
 
+/// 登录用户在数据库中的账号状态
+pub struct LoginStatus {
+    pub locked: bool,
+    /// 管理员重置密码后，用户修改密码之前只能执行少数命令
+    pub must_change_password: bool,
+}
+
+/// 查询 token 对应账号的状态，token 的版本与用户当前的 `token_version` 不一致或用户不存在时返回 None
+pub async fn login_status(
+    db: &PgPool,
+    claims: &Claims,
+) -> Result<Option<LoginStatus>, sqlx::Error> {
+    let user = sqlx::query!(
+        "SELECT token_version, locked, must_change_password FROM users WHERE id = $1",
+        claims.sub
+    )
+    .fetch_optional(db)
+    .await?;
+    match user {
+        Some(user) if user.token_version == claims.ver => Ok(Some(LoginStatus {
+            locked: user.locked,
+            must_change_password: user.must_change_password,
+        })),
+        _ => {
+            warn!("用户 {} 的 token 已失效", claims.username);
+            Ok(None)
+        }
+    }
+}
+
+/// token 是否仍然有效：版本与用户当前的一致且账号未被锁定，查询失败时视为失效
+pub async fn is_token_current(db: &PgPool, claims: &Claims) -> bool {
+    match login_status(db, claims).await {
+        Ok(status) => status.is_some_and(|status| !status.locked),
+        Err(e) => {
+            error!("数据库查询错误: {}", e);
+            false
+        }
+    }
+}
+
 pub fn generate_token(
     user_id: &str,
     username: &str,
     role: &str,
+    token_version: i32,
 ) -> Result<String, jsonwebtoken::errors::Error> {
     debug!("为用户 {} 生成 JWT token", user_id);
     let exp = (Utc::now() + chrono::Duration::hours(24)).timestamp() as usize;
     let claims = Claims {
         sub: user_id.to_string(),
         exp,
+        ver: token_version,
         id: user_id.to_string(),
         username: username.to_string(),
         role: role.to_string(),
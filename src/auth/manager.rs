@@ -95,8 +95,10 @@ impl AuthManager {
         let mut blacklist = self.token_blacklist.lock().unwrap();
         let before_count = blacklist.len();
         blacklist.retain(|_, timestamp| Utc::now() - *timestamp <= chrono::Duration::hours(24));
-        let after_count = blacklist.len();
-        info!("清理了 {} 个过期的 token", before_count - after_count);
+        let removed = before_count - blacklist.len();
+        if removed > 0 {
+            info!("清理了 {} 个过期的 token", removed);
+        }
     }
 
     pub fn hash_password(password: &str) -> Result<String, String> {
@@ -3,7 +3,7 @@ mod manager;
 mod middleware;
 mod types;
 
-pub use jwt::{generate_token, validate_token};
+pub use jwt::{generate_token, is_token_current, login_status, validate_token};
 pub use manager::AuthManager;
 pub use middleware::AuthMiddleware;
 pub use types::{Claims, LoginAttempt};
@@ -8,6 +8,9 @@ pub struct Claims {
     pub id: String,
     pub username: String,
     pub role: String,
+    /// 签发时用户的 token 版本，与 `users.token_version` 不一致时 token 失效
+    #[serde(default)]
+    pub ver: i32,
 }
 
 pub struct LoginAttempt {
@@ -0,0 +1,186 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::blocking::{Client as HttpClient, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::time::Duration;
+
+// 单个请求的超时时间，需覆盖 `wait` 等会阻塞的命令
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 命令产生的副作用，与服务端的 `SideEffect` 对应
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Effect {
+    ChangeDir(String),
+    SetToken(String),
+    ClearToken,
+    ClearScreen,
+    /// 需要输入密码，值为提示文字
+    PromptPassword(String),
+    /// 新版服务端增加的副作用，忽略
+    #[serde(other)]
+    Unknown,
+}
+
+/// `/api/command` 的响应
+#[derive(Debug, Deserialize)]
+pub struct Response {
+    pub success: bool,
+    #[serde(default)]
+    pub message: String,
+    pub data: Option<Value>,
+    /// 旧版服务端不返回退出状态，按是否成功推断
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    /// `message` 按样式拆分的片段，没有样式时为空
+    #[serde(default)]
+    pub spans: Vec<Span>,
+}
+
+/// 带样式的输出片段
+#[derive(Debug, Deserialize)]
+pub struct Span {
+    pub text: String,
+    pub style: Option<String>,
+}
+
+impl Span {
+    // 样式对应的 ANSI 转义，与 SSH 终端一致
+    fn ansi(&self) -> Option<&'static str> {
+        match self.style.as_deref()? {
+            "directory" => Some("\x1b[1;34m"),
+            "executable" => Some("\x1b[1;32m"),
+            "error" => Some("\x1b[31m"),
+            "highlight" => Some("\x1b[1;33m"),
+            "link" => Some("\x1b[4;36m"),
+            _ => None,
+        }
+    }
+}
+
+impl Response {
+    pub fn exit_code(&self) -> i32 {
+        self.exit_code.unwrap_or(if self.success { 0 } else { 1 })
+    }
+
+    /// 输出的文本，`color` 为 true 时以 ANSI 转义显示样式
+    pub fn text(&self, color: bool) -> String {
+        if !color || self.spans.is_empty() {
+            return self.message.clone();
+        }
+        self.spans
+            .iter()
+            .map(|span| match span.ansi() {
+                Some(ansi) => format!("{}{}\x1b[0m", ansi, span.text),
+                None => span.text.clone(),
+            })
+            .collect()
+    }
+}
+
+/// 补全候选项
+#[derive(Debug, Deserialize)]
+pub struct Candidate {
+    pub value: String,
+    pub kind: String,
+}
+
+/// `/api/complete` 的结果，用候选项替换从第 `start` 个字符到光标的内容
+#[derive(Debug, Deserialize)]
+pub struct Completion {
+    pub start: usize,
+    pub candidates: Vec<Candidate>,
+}
+
+/// `/api/captcha` 的结果
+#[derive(Debug, Deserialize)]
+pub struct Captcha {
+    pub session_id: String,
+    pub captcha: String,
+}
+
+/// 从 JWT 的载荷中读取用户名，只用于显示提示符，不校验签名
+pub fn token_username(token: &str) -> Option<String> {
+    let payload = token.split('.').nth(1)?;
+    let payload = URL_SAFE_NO_PAD.decode(payload).ok()?;
+    let claims: Value = serde_json::from_slice(&payload).ok()?;
+    claims["username"].as_str().map(|s| s.to_string())
+}
+
+/// 终端博客的 HTTP 客户端
+pub struct Client {
+    server: String,
+    http: HttpClient,
+    token: RefCell<Option<String>>,
+}
+
+impl Client {
+    pub fn new(server: &str, token: Option<String>) -> Result<Self, String> {
+        let http = HttpClient::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+        Ok(Client {
+            server: server.trim_end_matches('/').to_string(),
+            http,
+            token: RefCell::new(token),
+        })
+    }
+
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    pub fn set_token(&self, token: Option<String>) {
+        *self.token.borrow_mut() = token;
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.token.borrow().as_deref() {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    // 发送请求并解析 JSON 响应；命令失败时服务端也返回 JSON，不按状态码判断
+    fn send<T: for<'de> Deserialize<'de>>(&self, request: RequestBuilder) -> Result<T, String> {
+        let response = request
+            .send()
+            .map_err(|e| format!("无法连接服务器 {}: {}", self.server, e))?;
+        let status = response.status();
+        response
+            .json::<T>()
+            .map_err(|_| format!("服务器返回了无法解析的响应 (HTTP {})", status))
+    }
+
+    /// 执行一行命令
+    pub fn command(&self, command: &str, session_id: &str) -> Result<Response, String> {
+        let request = self
+            .http
+            .post(format!("{}/api/command", self.server))
+            .json(&json!({ "command": command, "session_id": session_id }));
+        self.send(self.authorize(request))
+    }
+
+    /// 补全光标处的单词，`cursor` 按字符计
+    pub fn complete(&self, line: &str, cursor: usize) -> Result<Completion, String> {
+        let request = self
+            .http
+            .post(format!("{}/api/complete", self.server))
+            .json(&json!({ "line": line, "cursor": cursor }));
+        let response: Response = self.send(self.authorize(request))?;
+        let data = response.data.unwrap_or(Value::Null);
+        serde_json::from_value(data).map_err(|e| format!("补全结果格式错误: {}", e))
+    }
+
+    /// 获取验证码，用于 `register` 等命令
+    pub fn captcha(&self) -> Result<Captcha, String> {
+        let request = self.http.get(format!("{}/api/captcha", self.server));
+        let response: Response = self.send(request)?;
+        let data = response.data.unwrap_or(Value::Null);
+        serde_json::from_value(data).map_err(|e| format!("验证码格式错误: {}", e))
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// 保存登录状态的文件
+const CREDENTIALS_FILE: &str = "credentials.json";
+
+// 命令历史文件
+const HISTORY_FILE: &str = "history";
+
+/// 某个服务器上的登录状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credential {
+    pub token: String,
+    pub username: String,
+}
+
+/// 配置目录 `~/.config/tblog`，按服务器地址保存 token
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    credentials: BTreeMap<String, Credential>,
+}
+
+/// 配置目录，优先使用 `TBLOG_CONFIG_DIR`
+pub fn config_dir() -> Option<PathBuf> {
+    match std::env::var_os("TBLOG_CONFIG_DIR") {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => dirs::config_dir().map(|dir| dir.join("tblog")),
+    }
+}
+
+/// 命令历史文件路径
+pub fn history_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(HISTORY_FILE))
+}
+
+impl Config {
+    /// 读取配置，文件不存在或格式错误时返回空配置
+    pub fn load() -> Self {
+        let Some(path) = config_dir().map(|dir| dir.join(CREDENTIALS_FILE)) else {
+            return Config::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("tblog: 忽略格式错误的 {}: {}", path.display(), e);
+                Config::default()
+            }),
+            Err(_) => Config::default(),
+        }
+    }
+
+    /// 保存配置，文件权限为 0600
+    pub fn save(&self) -> io::Result<()> {
+        let dir = config_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "找不到配置目录"))?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(CREDENTIALS_FILE);
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, content)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(())
+    }
+
+    pub fn credential(&self, server: &str) -> Option<&Credential> {
+        self.credentials.get(server)
+    }
+
+    pub fn set_credential(&mut self, server: &str, credential: Credential) {
+        self.credentials.insert(server.to_string(), credential);
+    }
+
+    pub fn remove_credential(&mut self, server: &str) {
+        self.credentials.remove(server);
+    }
+}
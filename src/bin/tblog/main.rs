@@ -0,0 +1,144 @@
+//! tblog：终端博客的命令行客户端
+//!
+//! 不带参数时进入交互模式，`-c` 执行一条命令后退出，退出状态与命令一致，便于在脚本和
+//! CI 中使用。登录后的 token 按服务器地址保存在 `~/.config/tblog`。
+
+mod client;
+mod config;
+mod repl;
+mod shell;
+
+use std::io::{BufRead, IsTerminal};
+use std::process::ExitCode;
+use std::rc::Rc;
+
+use client::Client;
+use config::Config;
+use shell::Shell;
+
+// 默认的服务器地址
+const DEFAULT_SERVER: &str = "http://localhost:8080";
+
+// 无法连接服务器等客户端错误的退出状态，与命令的退出状态区分
+const EXIT_CLIENT_ERROR: u8 = 2;
+
+const USAGE: &str = "用法: tblog [选项]
+
+选项:
+  -c <命令>             执行一条命令后退出，退出状态与命令一致
+  -s, --server <地址>   服务器地址，默认读取 TBLOG_SERVER，否则为 http://localhost:8080
+      --json            以 JSON 输出完整的响应，包括结构化数据
+  -h, --help            显示帮助
+  -V, --version         显示版本
+
+环境变量:
+  TBLOG_SERVER          服务器地址
+  TBLOG_TOKEN           使用指定的 token，不读取保存的登录状态
+  TBLOG_CONFIG_DIR      配置目录，默认为 ~/.config/tblog";
+
+// 命令行参数
+#[derive(Default)]
+struct Args {
+    command: Option<String>,
+    server: Option<String>,
+    json: bool,
+}
+
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut args = Args::default();
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-c" => {
+                args.command = Some(iter.next().ok_or("-c 需要一个命令")?);
+            }
+            "-s" | "--server" => {
+                args.server = Some(iter.next().ok_or(format!("{} 需要服务器地址", arg))?);
+            }
+            "--json" => args.json = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(None);
+            }
+            "-V" | "--version" => {
+                println!("tblog {}", env!("CARGO_PKG_VERSION"));
+                return Ok(None);
+            }
+            _ => return Err(format!("未知参数: {}\n\n{}", arg, USAGE)),
+        }
+    }
+    Ok(Some(args))
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("tblog: {}", e);
+            return ExitCode::from(EXIT_CLIENT_ERROR);
+        }
+    };
+
+    let server = args
+        .server
+        .or_else(|| std::env::var("TBLOG_SERVER").ok())
+        .unwrap_or_else(|| DEFAULT_SERVER.to_string());
+    let config = Config::load();
+    let server = server.trim_end_matches('/').to_string();
+    let (username, token) = match std::env::var("TBLOG_TOKEN") {
+        Ok(token) => (client::token_username(&token), Some(token)),
+        Err(_) => match config.credential(&server) {
+            Some(credential) => (
+                Some(credential.username.clone()),
+                Some(credential.token.clone()),
+            ),
+            None => (None, None),
+        },
+    };
+
+    let client = match Client::new(&server, token) {
+        Ok(client) => Rc::new(client),
+        Err(e) => {
+            eprintln!("tblog: {}", e);
+            return ExitCode::from(EXIT_CLIENT_ERROR);
+        }
+    };
+    let mut shell = Shell::new(client.clone(), config, username, args.json);
+
+    match args.command {
+        Some(command) => {
+            // 非交互模式下从标准输入读取验证码
+            let mut read_captcha = |captcha: &str| {
+                eprintln!("验证码: {}", captcha);
+                let mut line = String::new();
+                std::io::stdin().lock().read_line(&mut line).ok()?;
+                Some(line)
+            };
+            // 在终端上不回显密码，否则从标准输入读取一行
+            let mut read_password = |prompt: &str| {
+                if std::io::stdin().is_terminal() {
+                    return rpassword::prompt_password(prompt).ok();
+                }
+                eprint!("{}", prompt);
+                let mut line = String::new();
+                std::io::stdin().lock().read_line(&mut line).ok()?;
+                Some(line)
+            };
+            match shell.execute(&command, &mut read_captcha, &mut read_password) {
+                Ok(code) => ExitCode::from(code.clamp(0, 255) as u8),
+                Err(e) => {
+                    eprintln!("tblog: {}", e);
+                    ExitCode::from(EXIT_CLIENT_ERROR)
+                }
+            }
+        }
+        None => match repl::run(&mut shell, client) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("tblog: {}", e);
+                ExitCode::from(EXIT_CLIENT_ERROR)
+            }
+        },
+    }
+}
@@ -0,0 +1,136 @@
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+use std::io::IsTerminal;
+use std::rc::Rc;
+
+use crate::client::Client;
+use crate::config;
+use crate::shell::Shell;
+
+// 保存的历史条数
+const HISTORY_SIZE: usize = 1000;
+
+// 行编辑器的补全：请求服务端的 `/api/complete`
+struct BlogHelper {
+    client: Rc<Client>,
+}
+
+impl Completer for BlogHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        // 服务端按字符计算位置，rustyline 按字节
+        let cursor = line[..pos].chars().count();
+        let completion = match self.client.complete(line, cursor) {
+            Ok(completion) => completion,
+            Err(_) => return Ok((pos, Vec::new())),
+        };
+        let start = line
+            .char_indices()
+            .nth(completion.start)
+            .map(|(i, _)| i)
+            .unwrap_or(line.len())
+            .min(pos);
+        let candidates = completion
+            .candidates
+            .into_iter()
+            .map(|c| Pair {
+                replacement: if c.kind == "directory" {
+                    c.value.clone()
+                } else {
+                    format!("{} ", c.value)
+                },
+                display: c.value,
+            })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for BlogHelper {
+    type Hint = String;
+}
+
+impl Highlighter for BlogHelper {}
+
+impl Validator for BlogHelper {}
+
+impl Helper for BlogHelper {}
+
+/// 交互模式：带历史和补全的命令行，`exit` 或 Ctrl-D 退出
+pub fn run(shell: &mut Shell, client: Rc<Client>) -> Result<(), String> {
+    let config = Config::builder()
+        .completion_type(CompletionType::List)
+        .max_history_size(HISTORY_SIZE)
+        .map_err(|e| e.to_string())?
+        .auto_add_history(false)
+        .build();
+    let mut editor: Editor<BlogHelper, FileHistory> =
+        Editor::with_config(config).map_err(|e| format!("初始化终端失败: {}", e))?;
+    println!(
+        "已连接 {}，输入 help 查看可用命令，exit 退出。",
+        client.server()
+    );
+    editor.set_helper(Some(BlogHelper { client }));
+
+    let history = config::history_path();
+    if let Some(path) = &history {
+        // 第一次运行时历史文件还不存在
+        let _ = editor.load_history(path);
+    }
+
+    let color = std::io::stdout().is_terminal();
+    shell.sync_cwd();
+    loop {
+        let line = match editor.readline(&shell.prompt(color)) {
+            Ok(line) => line,
+            // Ctrl-C 放弃当前输入
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(format!("读取输入失败: {}", e)),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+        if let Some(path) = &history {
+            if let Err(e) = save_history(&mut editor, path) {
+                eprintln!("tblog: 保存历史失败: {}", e);
+            }
+        }
+        if line == "exit" || line == "quit" {
+            break;
+        }
+
+        let mut read_captcha = |captcha: &str| {
+            println!("验证码: {}", captcha);
+            editor.readline("请输入验证码: ").ok()
+        };
+        let mut read_password = |prompt: &str| rpassword::prompt_password(prompt).ok();
+        if let Err(e) = shell.execute(line, &mut read_captcha, &mut read_password) {
+            eprintln!("tblog: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn save_history(
+    editor: &mut Editor<BlogHelper, FileHistory>,
+    path: &std::path::Path,
+) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    editor.save_history(path).map_err(|e| e.to_string())
+}
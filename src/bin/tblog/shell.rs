@@ -0,0 +1,165 @@
+use serde_json::json;
+use std::io::IsTerminal;
+use std::rc::Rc;
+
+use crate::client::{Client, Effect, Response};
+use crate::config::{Config, Credential};
+
+// 访客的工作目录，与服务端的访客会话一致
+const GUEST_HOME: &str = "/home/guest";
+
+/// 客户端的 shell 状态：登录用户、工作目录和保存的配置
+pub struct Shell {
+    client: Rc<Client>,
+    config: Config,
+    /// 以 JSON 输出完整的响应
+    json: bool,
+    username: Option<String>,
+    cwd: String,
+}
+
+impl Shell {
+    pub fn new(client: Rc<Client>, config: Config, username: Option<String>, json: bool) -> Self {
+        let cwd = match &username {
+            Some(name) => format!("/home/{}", name),
+            None => GUEST_HOME.to_string(),
+        };
+        Shell {
+            client,
+            config,
+            json,
+            username,
+            cwd,
+        }
+    }
+
+    /// 从服务端同步工作目录，token 失效时按访客处理
+    pub fn sync_cwd(&mut self) {
+        if let Ok(response) = self.client.command("pwd", "") {
+            match response.data.as_ref().and_then(|d| d["path"].as_str()) {
+                Some(path) if response.success => self.cwd = path.to_string(),
+                _ => {}
+            }
+        }
+    }
+
+    /// 提示符，如 `alice@blog:~/Documents$ `
+    pub fn prompt(&self, color: bool) -> String {
+        let username = self.username.as_deref().unwrap_or("guest");
+        let home = format!("/home/{}", username);
+        let cwd = match self.cwd.strip_prefix(&home) {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("~{}", rest),
+            _ => self.cwd.clone(),
+        };
+        if color {
+            format!(
+                "\x1b[1;32m{}@blog\x1b[0m:\x1b[1;34m{}\x1b[0m$ ",
+                username, cwd
+            )
+        } else {
+            format!("{}@blog:{}$ ", username, cwd)
+        }
+    }
+
+    /// 执行一行命令并输出结果，返回命令的退出状态
+    ///
+    /// `register` 没有提供 `--captcha` 时先获取验证码，通过 `read_captcha` 读取用户输入。
+    /// `sudo` 需要密码时通过 `read_password` 读取密码后重新执行。
+    pub fn execute(
+        &mut self,
+        line: &str,
+        read_captcha: &mut dyn FnMut(&str) -> Option<String>,
+        read_password: &mut dyn FnMut(&str) -> Option<String>,
+    ) -> Result<i32, String> {
+        let mut command = line.to_string();
+        let mut session_id = String::new();
+        let first = line.split_whitespace().next().unwrap_or_default();
+        if first == "register" && !line.contains("--captcha") {
+            let captcha = self.client.captcha()?;
+            let code = read_captcha(&captcha.captcha).ok_or("已取消")?;
+            command = format!("{} --captcha {}", line, code.trim());
+            session_id = captcha.session_id;
+        }
+
+        let mut response = self.client.command(&command, &session_id)?;
+        let password_prompt = response.effects.iter().find_map(|effect| match effect {
+            Effect::PromptPassword(prompt) if first == "sudo" => Some(prompt.clone()),
+            _ => None,
+        });
+        if let Some(prompt) = password_prompt {
+            let password = read_password(&prompt).ok_or("已取消")?;
+            let rest = line.trim_start().strip_prefix("sudo").unwrap_or(line);
+            command = format!(
+                "sudo --password '{}'{}",
+                password.trim_end_matches(['\r', '\n']).replace('\'', "'\\''"),
+                rest
+            );
+            response = self.client.command(&command, &session_id)?;
+        }
+        self.apply_effects(&response);
+        self.print(&response);
+        Ok(response.exit_code())
+    }
+
+    fn print(&self, response: &Response) {
+        if self.json {
+            let output = json!({
+                "success": response.success,
+                "exit_code": response.exit_code(),
+                "message": response.message,
+                "data": response.data,
+            });
+            println!("{}", output);
+        } else if !response.message.is_empty() {
+            // 输出到终端时显示样式
+            if response.success {
+                println!("{}", response.text(std::io::stdout().is_terminal()));
+            } else {
+                eprintln!("{}", response.text(std::io::stderr().is_terminal()));
+            }
+        }
+    }
+
+    // 根据副作用更新本地状态，登录和登出时保存或清除 token
+    fn apply_effects(&mut self, response: &Response) {
+        for effect in &response.effects {
+            match effect {
+                Effect::SetToken(token) => {
+                    let username = response
+                        .data
+                        .as_ref()
+                        .and_then(|d| d["user"]["username"].as_str())
+                        .unwrap_or("guest")
+                        .to_string();
+                    self.client.set_token(Some(token.clone()));
+                    self.config.set_credential(
+                        self.client.server(),
+                        Credential {
+                            token: token.clone(),
+                            username: username.clone(),
+                        },
+                    );
+                    self.cwd = format!("/home/{}", username);
+                    self.username = Some(username);
+                    self.save_config();
+                }
+                Effect::ClearToken => {
+                    self.client.set_token(None);
+                    self.config.remove_credential(self.client.server());
+                    self.username = None;
+                    self.cwd = GUEST_HOME.to_string();
+                    self.save_config();
+                }
+                Effect::ChangeDir(path) => self.cwd = path.clone(),
+                Effect::ClearScreen if !self.json => print!("\x1b[H\x1b[2J"),
+                Effect::ClearScreen | Effect::PromptPassword(_) | Effect::Unknown => {}
+            }
+        }
+    }
+
+    fn save_config(&self) {
+        if let Err(e) = self.config.save() {
+            eprintln!("tblog: 保存登录状态失败: {}", e);
+        }
+    }
+}
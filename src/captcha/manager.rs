@@ -89,8 +89,10 @@ impl CaptchaManager {
         let before_count = captchas.len();
         let now = Utc::now();
         captchas.retain(|_, captcha| (now - captcha.created_at).num_seconds() <= 300);
-        let after_count = captchas.len();
-        info!("清理了 {} 个过期的验证码", before_count - after_count);
+        let removed = before_count - captchas.len();
+        if removed > 0 {
+            info!("清理了 {} 个过期的验证码", removed);
+        }
     }
 }
 
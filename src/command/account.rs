@@ -0,0 +1,192 @@
+use super::{CommandContext, CommandResult, ErrorKind};
+use crate::auth::AuthManager;
+use crate::vfs::model::{Role, User as VfsUser};
+use chrono::Utc;
+use log::{debug, error, info, warn};
+use uuid::Uuid;
+
+/// 账号的角色，与 `users.role` 的取值一致
+pub const ROLES: &[&str] = &["admin", "user"];
+
+/// 用户名验证：3-20 个字母、数字或下划线
+pub fn validate_username(username: &str) -> Result<(), String> {
+    if username.len() < 3 {
+        return Err("用户名长度必须大于等于3个字符".to_string());
+    }
+    if username.len() > 20 {
+        return Err("用户名长度不能超过20个字符".to_string());
+    }
+    if !username.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err("用户名只能包含字母、数字和下划线".to_string());
+    }
+    Ok(())
+}
+
+/// 密码验证：6-32 个字符
+pub fn validate_password(password: &str) -> Result<(), String> {
+    if password.len() < 6 {
+        return Err("密码长度必须大于等于6个字符".to_string());
+    }
+    if password.len() > 32 {
+        return Err("密码长度不能超过32个字符".to_string());
+    }
+    Ok(())
+}
+
+/// 家目录的绝对路径
+pub fn home_dir(username: &str) -> String {
+    format!("/home/{}", username)
+}
+
+/// 以系统身份操作 VFS，用于创建、移动家目录等管理操作
+pub fn system_user(id: &str) -> VfsUser {
+    VfsUser {
+        id: id.to_string(),
+        username: "system".to_string(),
+        roles: vec![Role::Admin],
+        session: None,
+    }
+}
+
+/// 按用户名查询用户 ID，用户不存在时返回错误结果
+pub async fn find_user(
+    ctx: &CommandContext<'_>,
+    command: &str,
+    username: &str,
+) -> Result<String, CommandResult> {
+    match sqlx::query_scalar!("SELECT id FROM users WHERE username = $1", username)
+        .fetch_optional(&ctx.state.db)
+        .await
+    {
+        Ok(Some(id)) => Ok(id),
+        Ok(None) => Err(CommandResult::invalid(format!(
+            "{}: 用户 {} 不存在",
+            command, username
+        ))),
+        Err(e) => {
+            error!("数据库查询错误: {}", e);
+            Err(CommandResult::internal())
+        }
+    }
+}
+
+/// 创建账号及其家目录，返回新用户的 ID
+pub async fn create_account(
+    ctx: &CommandContext<'_>,
+    username: &str,
+    password: &str,
+    role: &str,
+) -> Result<String, CommandResult> {
+    let db = &ctx.state.db;
+
+    // 检查用户名是否已存在
+    match sqlx::query!("SELECT id FROM users WHERE username = $1", username)
+        .fetch_optional(db)
+        .await
+    {
+        Ok(Some(_)) => {
+            warn!("用户名已存在: {}", username);
+            return Err(CommandResult::invalid("用户名已存在"));
+        }
+        Ok(None) => debug!("用户名可用: {}", username),
+        Err(e) => {
+            error!("数据库查询错误: {}", e);
+            return Err(CommandResult::internal());
+        }
+    }
+
+    // 加密密码
+    let password_hash = AuthManager::hash_password(password).map_err(|e| {
+        error!("密码加密失败: {}", e);
+        CommandResult::internal()
+    })?;
+
+    // 创建用户
+    let user_id = Uuid::new_v4().to_string();
+    if let Err(e) = sqlx::query!(
+        "INSERT INTO users (id, username, password_hash, role, created_at) VALUES ($1, $2, $3, $4, $5)",
+        user_id,
+        username,
+        password_hash,
+        role,
+        Utc::now()
+    )
+    .execute(db)
+    .await
+    {
+        error!("创建用户失败: {}", e);
+        return Err(CommandResult::internal());
+    }
+    info!("用户创建成功: {}", username);
+
+    // 创建用户目录并把所有者改为新用户
+    let user_dir = home_dir(username);
+    if let Err(e) = ctx
+        .state
+        .vfs_manager
+        .create_dir(&system_user(&user_id), &user_dir, "/")
+        .await
+    {
+        error!("创建用户目录失败: {}", e);
+        return Err(CommandResult::error(
+            ErrorKind::Internal,
+            "创建用户目录失败",
+        ));
+    }
+    if let Err(e) = ctx
+        .state
+        .vfs_manager
+        .update_node_owner(&user_dir, &user_id)
+        .await
+    {
+        error!("更新目录所有者失败: {}", e);
+        return Err(CommandResult::error(
+            ErrorKind::Internal,
+            "更新目录所有者失败",
+        ));
+    }
+
+    Ok(user_id)
+}
+
+/// 清理用户除 `keep` 之外的会话和后台任务
+pub fn end_sessions(ctx: &CommandContext<'_>, user_id: &str, keep: Option<&str>) {
+    for token in ctx.state.session_manager.remove_user(user_id, keep) {
+        ctx.state.job_manager.remove_session(&token);
+    }
+}
+
+/// 使用户的所有登录失效：递增 token 版本，此前签发的 token 不再有效，并清理相应的会话和后台任务
+pub async fn revoke_logins(ctx: &CommandContext<'_>, user_id: &str) -> Result<(), CommandResult> {
+    if let Err(e) = sqlx::query!(
+        "UPDATE users SET token_version = token_version + 1 WHERE id = $1",
+        user_id
+    )
+    .execute(&ctx.state.db)
+    .await
+    {
+        error!("更新 token 版本失败: {}", e);
+        return Err(CommandResult::internal());
+    }
+    end_sessions(ctx, user_id, None);
+    Ok(())
+}
+
+/// 要求当前用户具有管理员权限（通过 `sudo` 提权），返回当前用户
+pub async fn require_admin(
+    ctx: &CommandContext<'_>,
+    command: &str,
+) -> Result<VfsUser, CommandResult> {
+    let user = ctx.user().await?;
+    if !user.roles.contains(&Role::Admin) {
+        warn!(
+            "用户 {} 没有管理员权限，拒绝执行 {}",
+            user.username, command
+        );
+        return Err(CommandResult::forbidden(format!(
+            "{}: 需要管理员权限，请使用 sudo",
+            command
+        )));
+    }
+    Ok(user)
+}
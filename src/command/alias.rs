@@ -0,0 +1,217 @@
+use super::parser::split_command;
+use crate::vfs::model::{User as VfsUser, VfsError};
+use log::{debug, warn};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// 别名文件相对于家目录的位置
+pub const ALIAS_FILE: &str = "Config/aliases";
+
+// 单条命令最多展开的别名层数
+const MAX_ALIAS_DEPTH: usize = 16;
+
+/// 用户别名文件的绝对路径
+pub fn alias_path(username: &str) -> String {
+    format!("/home/{}/{}", username, ALIAS_FILE)
+}
+
+/// 别名名称不能为空，且不能包含空白、引号、`=` 或 `/`
+pub fn is_valid_alias_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '=' | '/' | '\'' | '"' | '$' | '\\'))
+}
+
+/// 按 `name='value'` 的格式输出别名，单引号按 shell 规则转义
+pub fn format_alias(name: &str, value: &str) -> String {
+    format!("{}='{}'", name, value.replace('\'', "'\\''"))
+}
+
+/// 解析别名文件，每行一条 `name='value'`，忽略空行和以 `#` 开头的注释
+pub fn parse_aliases(content: &str) -> BTreeMap<String, String> {
+    let mut aliases = BTreeMap::new();
+    for (lineno, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("alias ").unwrap_or(line);
+        let parsed = split_command(line, None)
+            .ok()
+            .and_then(|words| match words.as_slice() {
+                [word] => word
+                    .split_once('=')
+                    .map(|(name, value)| (name.to_string(), value.to_string())),
+                _ => None,
+            });
+        match parsed {
+            Some((name, value)) if is_valid_alias_name(&name) => {
+                aliases.insert(name, value);
+            }
+            _ => warn!("忽略无效的别名定义（第 {} 行）: {}", lineno + 1, line),
+        }
+    }
+    aliases
+}
+
+/// 从 VFS 读取用户的别名，文件不存在时返回空表
+pub async fn load_aliases(
+    state: &crate::AppState,
+    user: &VfsUser,
+) -> Result<BTreeMap<String, String>, VfsError> {
+    match state
+        .vfs_manager
+        .read_file(user, &alias_path(&user.username), "/")
+        .await
+    {
+        Ok(content) => Ok(parse_aliases(&content)),
+        Err(VfsError::NodeNotFound(_)) => Ok(BTreeMap::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// 将别名写回 VFS，必要时创建 `~/Config` 目录
+pub async fn save_aliases(
+    state: &crate::AppState,
+    user: &VfsUser,
+    aliases: &BTreeMap<String, String>,
+) -> Result<(), VfsError> {
+    let config_dir = format!("/home/{}/Config", user.username);
+    match state.vfs_manager.stat(&config_dir, "/").await {
+        Ok(_) => {}
+        Err(VfsError::NodeNotFound(_)) => {
+            debug!("创建配置目录: {}", config_dir);
+            state.vfs_manager.create_dir(user, &config_dir, "/").await?;
+        }
+        Err(e) => return Err(e),
+    }
+
+    let mut content: String = aliases
+        .iter()
+        .map(|(name, value)| format_alias(name, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+    content.push('\n');
+
+    state
+        .vfs_manager
+        .write_file(user, &alias_path(&user.username), "/", &content)
+        .await?;
+    Ok(())
+}
+
+/// 展开命令别名
+///
+/// 反复替换首个参数，同一别名在一次展开中只使用一次，防止递归定义导致死循环。
+pub fn expand_aliases(
+    mut words: Vec<String>,
+    aliases: &BTreeMap<String, String>,
+    env: &HashMap<String, String>,
+) -> Result<Vec<String>, String> {
+    let mut seen = HashSet::new();
+    while let Some(first) = words.first() {
+        let value = match aliases.get(first) {
+            Some(value) => value,
+            None => break,
+        };
+        if !seen.insert(first.clone()) {
+            break;
+        }
+        if seen.len() > MAX_ALIAS_DEPTH {
+            return Err("别名展开层数过多".to_string());
+        }
+        debug!("展开别名: {} -> {}", first, value);
+
+        let mut expanded = split_command(value, Some(env))?;
+        expanded.extend(words.drain(1..));
+        words = expanded;
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn words(line: &str) -> Vec<String> {
+        split_command(line, None).unwrap()
+    }
+
+    #[test]
+    fn formatted_aliases_parse_back() {
+        let value = "echo 'it''s' \"$HOME\"";
+        let content = format!(
+            "{}\n{}\n",
+            format_alias("ll", "ls -l"),
+            format_alias("q", value)
+        );
+        assert_eq!(content.lines().next(), Some("ll='ls -l'"));
+        assert_eq!(
+            parse_aliases(&content),
+            aliases(&[("ll", "ls -l"), ("q", value)])
+        );
+    }
+
+    #[test]
+    fn parse_aliases_skips_comments_and_invalid_lines() {
+        let content = "# 注释\n\nalias la='ls -a'\nll=ls\nbad line\nx/y='ls'\n=empty\n";
+        assert_eq!(
+            parse_aliases(content),
+            aliases(&[("la", "ls -a"), ("ll", "ls")])
+        );
+    }
+
+    #[test]
+    fn alias_names_exclude_shell_syntax() {
+        assert!(is_valid_alias_name("ll"));
+        assert!(is_valid_alias_name("git-st"));
+        for name in ["", "a b", "a=b", "a/b", "a'b", "a\"b", "$a", "a\\b"] {
+            assert!(!is_valid_alias_name(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn expand_aliases_follows_chains_and_keeps_arguments() {
+        let aliases = aliases(&[("ll", "ls -l"), ("lla", "ll -a"), ("home", "cd $HOME")]);
+        let env = HashMap::from([("HOME".to_string(), "/home/alice".to_string())]);
+        let expand = |line: &str| expand_aliases(words(line), &aliases, &env).unwrap();
+        assert_eq!(expand("lla Documents"), ["ls", "-l", "-a", "Documents"]);
+        assert_eq!(expand("home"), ["cd", "/home/alice"]);
+        // 只展开第一个参数
+        assert_eq!(expand("echo ll"), ["echo", "ll"]);
+    }
+
+    #[test]
+    fn expand_aliases_uses_each_alias_once() {
+        let aliases = aliases(&[("ls", "ls --color"), ("a", "b"), ("b", "a x")]);
+        let env = HashMap::new();
+        let expand = |line: &str| expand_aliases(words(line), &aliases, &env).unwrap();
+        assert_eq!(expand("ls -a"), ["ls", "--color", "-a"]);
+        assert_eq!(expand("a"), ["a", "x"]);
+    }
+
+    #[test]
+    fn expand_aliases_limits_depth() {
+        let chain = |len: usize| -> BTreeMap<String, String> {
+            (0..len)
+                .map(|i| (format!("a{}", i), format!("a{}", i + 1)))
+                .collect()
+        };
+        let env = HashMap::new();
+        assert_eq!(
+            expand_aliases(words("a0"), &chain(MAX_ALIAS_DEPTH), &env).unwrap(),
+            [format!("a{}", MAX_ALIAS_DEPTH)]
+        );
+        assert_eq!(
+            expand_aliases(words("a0"), &chain(MAX_ALIAS_DEPTH + 1), &env).unwrap_err(),
+            "别名展开层数过多"
+        );
+    }
+}
@@ -0,0 +1,794 @@
+use super::CommandResult;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// 历史记录和日志中代替机密参数的文本
+pub const MASK: &str = "***";
+
+/// 参数值的类型，解析时据此校验，补全时据此给出候选
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueKind {
+    Text,
+    /// VFS 路径，补全时列出目录内容
+    Path,
+    /// 非负整数
+    Number,
+    /// `YYYY-MM-DD` 格式的日期
+    Date,
+    /// 只能取给定的值之一
+    Choice(&'static [&'static str]),
+    /// 命令名，补全时列出已注册的命令
+    Command,
+}
+
+impl ValueKind {
+    fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            ValueKind::Text | ValueKind::Path | ValueKind::Command => Ok(()),
+            ValueKind::Number => value
+                .parse::<u64>()
+                .map(|_| ())
+                .map_err(|_| format!("{} 不是有效的数字", value)),
+            ValueKind::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|_| ())
+                .map_err(|_| format!("{} 不是有效的日期，请使用 YYYY-MM-DD 格式", value)),
+            ValueKind::Choice(choices) => {
+                if choices.contains(&value) {
+                    Ok(())
+                } else {
+                    Err(format!("{} 无效，可选值: {}", value, choices.join(", ")))
+                }
+            }
+        }
+    }
+}
+
+// 终端显示宽度，中日韩文字占两列
+pub fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c >= '\u{2e80}' { 2 } else { 1 })
+        .sum()
+}
+
+/// 位置参数
+#[derive(Debug)]
+pub struct Positional {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: ValueKind,
+    pub required: bool,
+    /// 可以出现多次，只能是最后一个位置参数
+    pub repeated: bool,
+    /// 密码等机密，不出现在历史记录和日志中
+    pub secret: bool,
+}
+
+impl Positional {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Positional {
+            name,
+            help,
+            kind: ValueKind::Text,
+            required: false,
+            repeated: false,
+            secret: false,
+        }
+    }
+
+    pub const fn kind(mut self, kind: ValueKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub const fn repeated(mut self) -> Self {
+        self.repeated = true;
+        self
+    }
+
+    pub const fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+
+    fn usage(&self) -> String {
+        let dots = if self.repeated { " ..." } else { "" };
+        if self.required {
+            format!("<{}>{}", self.name, dots)
+        } else {
+            format!("[{}{}]", self.name, dots)
+        }
+    }
+}
+
+/// 选项，`value` 为 `None` 时是不带值的开关；`short` 为可选的短名称，查询结果时统一使用 `name`
+#[derive(Debug)]
+pub struct Opt {
+    pub name: &'static str,
+    pub short: Option<&'static str>,
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+    pub kind: ValueKind,
+    pub required: bool,
+    /// 值是密码等机密，不出现在历史记录和日志中
+    pub secret: bool,
+}
+
+impl Opt {
+    /// 开关选项，如 `-p`、`--show`
+    pub const fn flag(name: &'static str, help: &'static str) -> Self {
+        Opt {
+            name,
+            short: None,
+            value: None,
+            help,
+            kind: ValueKind::Text,
+            required: false,
+            secret: false,
+        }
+    }
+
+    /// 带值的选项，如 `--email <email>`
+    pub const fn value(name: &'static str, value: &'static str, help: &'static str) -> Self {
+        Opt {
+            name,
+            short: None,
+            value: Some(value),
+            help,
+            kind: ValueKind::Text,
+            required: false,
+            secret: false,
+        }
+    }
+
+    pub const fn short(mut self, short: &'static str) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    pub const fn kind(mut self, kind: ValueKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub const fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+
+    fn matches(&self, arg: &str) -> bool {
+        self.name == arg || self.short == Some(arg)
+    }
+
+    fn names(&self) -> String {
+        match self.short {
+            Some(short) => format!("{}, {}", short, self.name),
+            None => self.name.to_string(),
+        }
+    }
+
+    fn usage(&self) -> String {
+        let name = self.short.unwrap_or(self.name);
+        let text = match self.value {
+            Some(value) => format!("{} <{}>", name, value),
+            None => name.to_string(),
+        };
+        if self.required {
+            text
+        } else {
+            format!("[{}]", text)
+        }
+    }
+}
+
+/// 命令的参数规格，驱动参数解析、错误提示、`--help` 和补全
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub positionals: &'static [Positional],
+    pub options: &'static [Opt],
+    /// 第一个位置参数之后的内容原样保留，不再解析选项（如脚本参数、命令文本）
+    pub trailing: bool,
+    /// 手册页中的示例：命令行及说明
+    pub examples: &'static [(&'static str, &'static str)],
+    /// 手册页中的相关命令
+    pub see_also: &'static [&'static str],
+    /// 只能在前台执行：命令会修改会话状态或依赖交互，不能用 `&` 放到后台
+    pub foreground: bool,
+    /// 支持全局选项 `--output`：结果附带记录或结构化数据
+    pub output: bool,
+    /// 支持全局选项 `--color`：输出中带有样式标记
+    pub color: bool,
+}
+
+impl CommandSpec {
+    pub const fn new(name: &'static str, summary: &'static str) -> Self {
+        CommandSpec {
+            name,
+            summary,
+            positionals: &[],
+            options: &[],
+            trailing: false,
+            examples: &[],
+            see_also: &[],
+            foreground: false,
+            output: false,
+            color: false,
+        }
+    }
+
+    pub const fn positionals(mut self, positionals: &'static [Positional]) -> Self {
+        self.positionals = positionals;
+        self
+    }
+
+    pub const fn options(mut self, options: &'static [Opt]) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub const fn trailing(mut self) -> Self {
+        self.trailing = true;
+        self
+    }
+
+    pub const fn examples(mut self, examples: &'static [(&'static str, &'static str)]) -> Self {
+        self.examples = examples;
+        self
+    }
+
+    pub const fn see_also(mut self, see_also: &'static [&'static str]) -> Self {
+        self.see_also = see_also;
+        self
+    }
+
+    pub const fn foreground(mut self) -> Self {
+        self.foreground = true;
+        self
+    }
+
+    pub const fn output(mut self) -> Self {
+        self.output = true;
+        self
+    }
+
+    pub const fn color(mut self) -> Self {
+        self.color = true;
+        self
+    }
+
+    /// 单行用法，如 `mkdir [-p] <目录>`：开关在前，带值选项在位置参数之后
+    pub fn usage(&self) -> String {
+        let mut parts = vec![self.name.to_string()];
+        let (flags, values): (Vec<&Opt>, Vec<&Opt>) =
+            self.options.iter().partition(|o| o.value.is_none());
+        parts.extend(flags.into_iter().map(Opt::usage));
+        parts.extend(self.positionals.iter().map(Positional::usage));
+        parts.extend(values.into_iter().map(Opt::usage));
+        parts.join(" ")
+    }
+
+    /// 参数和选项的说明表，两者共用同一列宽，每行不含缩进
+    pub fn table(&self) -> (Vec<String>, Vec<String>) {
+        let arguments: Vec<(String, &str)> = self
+            .positionals
+            .iter()
+            .map(|p| (p.usage(), p.help))
+            .collect();
+        let mut options: Vec<(String, &str)> = self
+            .options
+            .iter()
+            .map(|o| match o.value {
+                Some(value) => (format!("{} <{}>", o.names(), value), o.help),
+                None => (o.names(), o.help),
+            })
+            .collect();
+        if self.output {
+            options.push((
+                "--output <格式>".to_string(),
+                "以 json、yaml、csv 或 table 格式输出结果",
+            ));
+        }
+        if self.color {
+            options.push((
+                "--color[=<时机>]".to_string(),
+                "是否输出颜色：auto、always 或 never",
+            ));
+        }
+        options.push(("--help".to_string(), "显示帮助"));
+
+        let width = arguments
+            .iter()
+            .chain(&options)
+            .map(|(name, _)| display_width(name))
+            .max()
+            .unwrap_or(0);
+        let row = |(name, help): &(String, &str)| {
+            format!(
+                "{}{}  {}",
+                name,
+                " ".repeat(width - display_width(name)),
+                help
+            )
+        };
+        (
+            arguments.iter().map(row).collect(),
+            options.iter().map(row).collect(),
+        )
+    }
+
+    /// `--help` 输出的完整帮助
+    pub fn help(&self) -> String {
+        let (arguments, options) = self.table();
+        let mut lines = vec![
+            format!("{} - {}", self.name, self.summary),
+            String::new(),
+            format!("用法：{}", self.usage()),
+        ];
+        if !arguments.is_empty() {
+            lines.push(String::new());
+            lines.push("参数：".to_string());
+            lines.extend(arguments.iter().map(|row| format!("  {}", row)));
+        }
+        lines.push(String::new());
+        lines.push("选项：".to_string());
+        lines.extend(options.iter().map(|row| format!("  {}", row)));
+        lines.push(String::new());
+        lines.push(format!("使用 man {} 查看完整手册", self.name));
+        lines.join("\n")
+    }
+
+    /// 统一格式的参数错误：说明原因并附上用法
+    pub fn usage_error(&self, detail: impl std::fmt::Display) -> CommandResult {
+        CommandResult::invalid(format!("{}: {}\n用法：{}", self.name, detail, self.usage()))
+    }
+
+    fn find_option(&self, arg: &str) -> Option<&'static Opt> {
+        self.options.iter().find(|o| o.matches(arg))
+    }
+
+    // 合并写法的短开关，如 `-la`
+    fn split_flags(&self, arg: &str) -> Option<Vec<&'static Opt>> {
+        let letters = arg.strip_prefix('-')?;
+        if letters.len() < 2 || letters.starts_with('-') {
+            return None;
+        }
+        letters
+            .chars()
+            .map(|c| {
+                self.options.iter().find(|o| {
+                    o.value.is_none() && o.short.is_some_and(|s| s.len() == 2 && s.ends_with(c))
+                })
+            })
+            .collect()
+    }
+
+    fn positional_at(&self, index: usize) -> Option<&'static Positional> {
+        match self.positionals.get(index) {
+            Some(p) => Some(p),
+            None => self.positionals.last().filter(|p| p.repeated),
+        }
+    }
+
+    /// 按规格解析参数，`args[0]` 为命令名
+    ///
+    /// 出错时返回可直接作为命令结果的错误；遇到 `--help` 时返回帮助文本。
+    pub fn parse<'a>(&self, args: &[&'a str]) -> Result<Matches<'a>, CommandResult> {
+        let mut matches = Matches::default();
+        let mut options_done = false;
+        let mut iter = args.iter().skip(1);
+
+        while let Some(&arg) = iter.next() {
+            let literal = options_done
+                || arg == "-"
+                || !arg.starts_with('-')
+                || (self.trailing && !matches.positionals.is_empty());
+            if literal {
+                let positional = match self.positional_at(matches.positionals.len()) {
+                    Some(p) => p,
+                    None if self.trailing && !matches.positionals.is_empty() => {
+                        matches.positionals.push(arg);
+                        continue;
+                    }
+                    None => return Err(self.usage_error(format!("多余的参数: {}", arg))),
+                };
+                if let Err(e) = positional.kind.check(arg) {
+                    return Err(self.usage_error(format!("参数 <{}> 的值 {}", positional.name, e)));
+                }
+                matches.positionals.push(arg);
+                continue;
+            }
+
+            if arg == "--" {
+                options_done = true;
+                continue;
+            }
+            if arg == "--help" {
+                return Err(CommandResult::ok(self.help()));
+            }
+
+            // 支持 `--name=value` 写法
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+                _ => (arg, None),
+            };
+            let opt = match self.find_option(name) {
+                Some(opt) => opt,
+                None => match self.split_flags(arg) {
+                    Some(flags) => {
+                        matches.flags.extend(flags.iter().map(|o| o.name));
+                        continue;
+                    }
+                    None => return Err(self.usage_error(format!("未知选项: {}", arg))),
+                },
+            };
+
+            match opt.value {
+                None => {
+                    if inline.is_some() {
+                        return Err(self.usage_error(format!("选项 {} 不接受值", opt.name)));
+                    }
+                    matches.flags.push(opt.name);
+                }
+                Some(value_name) => {
+                    let value = match inline.or_else(|| iter.next().copied()) {
+                        Some(value) => value,
+                        None => {
+                            return Err(self.usage_error(format!(
+                                "选项 {} 缺少值 <{}>",
+                                opt.name, value_name
+                            )));
+                        }
+                    };
+                    if let Err(e) = opt.kind.check(value) {
+                        return Err(self.usage_error(format!("选项 {} 的值 {}", opt.name, e)));
+                    }
+                    matches.values.insert(opt.name, value);
+                }
+            }
+        }
+
+        if let Some(missing) = self
+            .positionals
+            .iter()
+            .skip(matches.positionals.len())
+            .find(|p| p.required)
+        {
+            return Err(self.usage_error(format!("缺少参数 <{}>", missing.name)));
+        }
+        if let Some(missing) = self
+            .options
+            .iter()
+            .find(|o| o.required && !matches.values.contains_key(o.name) && !matches.flag(o.name))
+        {
+            return Err(self.usage_error(format!("缺少选项 {}", missing.usage())));
+        }
+
+        Ok(matches)
+    }
+
+    /// 选项所在范围的结束位置：`--` 的下标，`trailing` 命令为第一个位置参数的下标
+    ///
+    /// 全局选项只在这个范围内识别，之后的参数原样交给命令。
+    pub fn options_end(&self, args: &[&str]) -> usize {
+        let mut index = 1;
+        while index < args.len() {
+            let arg = args[index];
+            if arg == "--" || (self.trailing && (arg == "-" || !arg.starts_with('-'))) {
+                return index;
+            }
+            let takes_value = (self.output && arg == "--output")
+                || (arg.starts_with('-')
+                    && !arg.contains('=')
+                    && self.find_option(arg).is_some_and(|o| o.value.is_some()));
+            index += if takes_value { 2 } else { 1 };
+        }
+        args.len()
+    }
+
+    /// `sudo` 这类以命令开头的命令中，被执行的命令在 `args` 中的下标
+    pub fn nested_command(&self, args: &[&str]) -> Option<usize> {
+        let first = self.positionals.first()?;
+        if !self.trailing || first.kind != ValueKind::Command {
+            return None;
+        }
+        let end = self.options_end(args);
+        (end < args.len() && args[end] != "--").then_some(end)
+    }
+
+    /// 需要隐去的机密参数，返回参数下标及替换后的文本，`args[0]` 为命令名
+    ///
+    /// 按 `parse` 的规则识别位置参数和选项，但不报错。`sudo` 这类以命令开头的
+    /// 命令，其后的命令按 `lookup` 找到的规格继续识别。
+    pub fn secrets(
+        &self,
+        args: &[&str],
+        lookup: &dyn Fn(&str) -> Option<&'static CommandSpec>,
+    ) -> Vec<(usize, String)> {
+        let mut secrets = Vec::new();
+        let mut count = 0;
+        let mut options_done = false;
+        let mut index = 1;
+        while index < args.len() {
+            let arg = args[index];
+            let literal =
+                options_done || arg == "-" || !arg.starts_with('-') || (self.trailing && count > 0);
+            if literal {
+                match self.positional_at(count) {
+                    Some(p) if count == 0 && self.trailing && p.kind == ValueKind::Command => {
+                        if let Some(spec) = lookup(arg) {
+                            let nested = spec.secrets(&args[index..], lookup);
+                            secrets.extend(nested.into_iter().map(|(i, text)| (index + i, text)));
+                        }
+                        break;
+                    }
+                    Some(p) if p.secret => secrets.push((index, MASK.to_string())),
+                    _ => {}
+                }
+                count += 1;
+            } else if arg == "--" {
+                options_done = true;
+            } else {
+                let (name, inline) = match arg.split_once('=') {
+                    Some((name, _)) if name.starts_with("--") => (name, true),
+                    _ => (arg, false),
+                };
+                if let Some(opt) = self.find_option(name).filter(|o| o.value.is_some()) {
+                    if inline {
+                        if opt.secret {
+                            secrets.push((index, format!("{}={}", name, MASK)));
+                        }
+                    } else {
+                        index += 1;
+                        if opt.secret && index < args.len() {
+                            secrets.push((index, MASK.to_string()));
+                        }
+                    }
+                }
+            }
+            index += 1;
+        }
+        secrets
+    }
+
+    /// 光标处的单词应取的值类型，`previous` 为光标前已输入的参数（不含命令名）
+    ///
+    /// 用于补全：前一个单词是带值选项时补全选项值，否则按位置参数补全。
+    pub fn expected(&self, previous: &[String]) -> Option<ValueKind> {
+        let mut count = 0;
+        let mut options_done = false;
+        let mut iter = previous.iter();
+        while let Some(arg) = iter.next() {
+            if self.trailing && count > 0 {
+                return None;
+            }
+            if options_done || arg == "-" || !arg.starts_with('-') {
+                count += 1;
+            } else if arg == "--" {
+                options_done = true;
+            } else if let Some(opt) = self.find_option(arg) {
+                if opt.value.is_some() && iter.next().is_none() {
+                    return Some(opt.kind);
+                }
+            }
+        }
+        if self.trailing && count > 0 {
+            return None;
+        }
+        self.positional_at(count).map(|p| p.kind)
+    }
+
+    /// 补全用的选项名及说明
+    pub fn option_names(&self) -> Vec<(&'static str, &'static str)> {
+        let mut names = Vec::new();
+        for opt in self.options {
+            names.push((opt.name, opt.help));
+            if let Some(short) = opt.short {
+                names.push((short, opt.help));
+            }
+        }
+        if self.output {
+            names.push(("--output", "以 json、yaml、csv 或 table 格式输出结果"));
+        }
+        if self.color {
+            names.push(("--color", "是否输出颜色：auto、always 或 never"));
+        }
+        names.push(("--help", "显示帮助"));
+        names
+    }
+}
+
+/// 解析结果，选项按 `name` 查询
+#[derive(Debug, Default)]
+pub struct Matches<'a> {
+    positionals: Vec<&'a str>,
+    values: HashMap<&'static str, &'a str>,
+    flags: Vec<&'static str>,
+}
+
+impl<'a> Matches<'a> {
+    /// 开关是否出现
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    /// 带值选项的值
+    pub fn value(&self, name: &str) -> Option<&'a str> {
+        self.values.get(name).copied()
+    }
+
+    /// 按类型取出带值选项，类型已在解析时校验
+    pub fn value_as<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.value(name).and_then(|v| v.parse().ok())
+    }
+
+    /// 第 `index` 个位置参数
+    pub fn positional(&self, index: usize) -> Option<&'a str> {
+        self.positionals.get(index).copied()
+    }
+
+    /// 从第 `index` 个开始的全部位置参数
+    pub fn rest(&self, index: usize) -> &[&'a str] {
+        self.positionals.get(index..).unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: CommandSpec = CommandSpec::new("useradd", "添加用户")
+        .positionals(&[
+            Positional::new("用户名", "新用户的用户名").required(),
+            Positional::new("密码", "初始密码").secret(),
+        ])
+        .options(&[
+            Opt::flag("--admin", "授予管理员角色").short("-a"),
+            Opt::flag("--verbose", "显示详细信息").short("-v"),
+            Opt::value("--email", "邮箱", "联系邮箱"),
+            Opt::value("--limit", "数量", "最多数量").kind(ValueKind::Number),
+            Opt::value("--token", "token", "访问令牌").secret(),
+        ])
+        .output();
+
+    const SUDO: CommandSpec = CommandSpec::new("sudo", "以其他用户身份执行命令")
+        .positionals(&[
+            Positional::new("命令", "要执行的命令")
+                .required()
+                .kind(ValueKind::Command),
+            Positional::new("参数", "命令的参数").repeated(),
+        ])
+        .options(&[Opt::value("-u", "用户", "目标用户")])
+        .trailing();
+
+    fn error(args: &[&str]) -> String {
+        SPEC.parse(args).unwrap_err().stderr
+    }
+
+    #[test]
+    fn parse_collects_positionals_flags_and_values() {
+        let matches = SPEC
+            .parse(&[
+                "useradd",
+                "-av",
+                "bob",
+                "--email=bob@example.com",
+                "--limit",
+                "3",
+            ])
+            .unwrap();
+        assert_eq!(matches.positional(0), Some("bob"));
+        assert_eq!(matches.positional(1), None);
+        assert!(matches.flag("--admin") && matches.flag("--verbose"));
+        assert_eq!(matches.value("--email"), Some("bob@example.com"));
+        assert_eq!(matches.value_as::<u32>("--limit"), Some(3));
+
+        // `--` 之后以 `-` 开头的参数也是位置参数
+        let matches = SPEC.parse(&["useradd", "--", "-bob"]).unwrap();
+        assert_eq!(matches.positional(0), Some("-bob"));
+    }
+
+    #[test]
+    fn parse_reports_errors_with_usage() {
+        let usage = "\n用法：useradd [-a] [-v] <用户名> [密码] [--email <邮箱>] [--limit <数量>] [--token <token>]";
+        let cases = [
+            (vec!["useradd"], "缺少参数 <用户名>"),
+            (vec!["useradd", "bob", "pw", "extra"], "多余的参数: extra"),
+            (vec!["useradd", "bob", "--nope"], "未知选项: --nope"),
+            (
+                vec!["useradd", "bob", "--email"],
+                "选项 --email 缺少值 <邮箱>",
+            ),
+            (
+                vec!["useradd", "bob", "--admin=yes"],
+                "选项 --admin 不接受值",
+            ),
+            (
+                vec!["useradd", "bob", "--limit", "many"],
+                "选项 --limit 的值 many 不是有效的数字",
+            ),
+        ];
+        for (args, detail) in cases {
+            assert_eq!(error(&args), format!("useradd: {}{}", detail, usage));
+        }
+        assert!(SPEC
+            .parse(&["useradd", "--help"])
+            .unwrap_err()
+            .stdout
+            .starts_with("useradd - 添加用户"));
+    }
+
+    #[test]
+    fn trailing_spec_keeps_arguments_after_command() {
+        let matches = SUDO
+            .parse(&["sudo", "-u", "bob", "ls", "-la", "--help"])
+            .unwrap();
+        assert_eq!(matches.value("-u"), Some("bob"));
+        assert_eq!(matches.rest(0), ["ls", "-la", "--help"]);
+    }
+
+    #[test]
+    fn options_end_stops_at_double_dash_or_trailing_command() {
+        assert_eq!(
+            SPEC.options_end(&["useradd", "bob", "--output", "json", "-a"]),
+            5
+        );
+        assert_eq!(
+            SPEC.options_end(&["useradd", "--email", "--", "--", "x"]),
+            3
+        );
+        assert_eq!(
+            SUDO.options_end(&["sudo", "-u", "bob", "ls", "--output", "json"]),
+            3
+        );
+        assert_eq!(SUDO.options_end(&["sudo", "-u", "bob"]), 3);
+
+        assert_eq!(
+            SUDO.nested_command(&["sudo", "-u", "bob", "ls", "-l"]),
+            Some(3)
+        );
+        assert_eq!(SUDO.nested_command(&["sudo", "--", "ls"]), None);
+        assert_eq!(SUDO.nested_command(&["sudo", "-u", "bob"]), None);
+        assert_eq!(SPEC.nested_command(&["useradd", "bob"]), None);
+    }
+
+    #[test]
+    fn secrets_masks_secret_positionals_and_options() {
+        let lookup = |name: &str| match name {
+            "useradd" => Some(&SPEC),
+            _ => None,
+        };
+        assert_eq!(
+            SPEC.secrets(
+                &["useradd", "bob", "pw", "--token", "t0k", "--token=t1k"],
+                &lookup
+            ),
+            [
+                (2, MASK.to_string()),
+                (4, MASK.to_string()),
+                (5, format!("--token={}", MASK)),
+            ]
+        );
+        // 带值选项的值不算位置参数
+        assert_eq!(
+            SPEC.secrets(&["useradd", "--email", "pw", "bob"], &lookup),
+            []
+        );
+        assert_eq!(
+            SUDO.secrets(&["sudo", "-u", "root", "useradd", "bob", "pw"], &lookup),
+            [(5, MASK.to_string())]
+        );
+        assert_eq!(SUDO.secrets(&["sudo", "unknown", "pw"], &lookup), []);
+    }
+}
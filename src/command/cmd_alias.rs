@@ -0,0 +1,94 @@
+use super::alias::{format_alias, is_valid_alias_name, load_aliases, save_aliases};
+use super::args::Positional;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, ErrorKind};
+use async_trait::async_trait;
+use log::{debug, error, info, warn};
+use serde_json::json;
+
+pub struct AliasCommand;
+
+impl AliasCommand {
+    pub fn new() -> Self {
+        AliasCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("alias", "定义或显示命令别名")
+    .positionals(&[Positional::new("name[=command]", "别名，带 = 时定义别名").repeated()])
+    .trailing()
+    .examples(&[
+        ("alias", "列出所有别名"),
+        ("alias docs='cd Documents'", "定义别名 docs"),
+        ("alias docs", "显示别名 docs 的定义"),
+    ])
+    .see_also(&["unalias"])
+    .output();
+
+#[async_trait]
+impl CommandHandler for AliasCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 alias 命令");
+
+        // 验证登录状态并查询用户身份
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let mut aliases = match load_aliases(ctx.state, &user).await {
+            Ok(aliases) => aliases,
+            Err(e) => {
+                error!("读取别名失败: {}", e);
+                return CommandResult::error(ErrorKind::Internal, format!("读取别名失败: {}", e));
+            }
+        };
+
+        // 不带参数时列出所有别名
+        if args.len() == 1 {
+            return CommandResult::ok(
+                aliases
+                    .iter()
+                    .map(|(name, value)| format!("alias {}", format_alias(name, value)))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+            .with_data(json!(aliases));
+        }
+
+        let mut shown = Vec::new();
+        let mut changed = false;
+        for arg in &args[1..] {
+            match arg.split_once('=') {
+                Some((name, value)) => {
+                    if !is_valid_alias_name(name) {
+                        warn!("无效的别名名称: {}", name);
+                        return CommandResult::invalid(format!("alias: 无效的别名名称: {}", name));
+                    }
+                    debug!("定义别名: {} -> {}", name, value);
+                    aliases.insert(name.to_string(), value.to_string());
+                    changed = true;
+                }
+                None => match aliases.get(*arg) {
+                    Some(value) => shown.push(format!("alias {}", format_alias(arg, value))),
+                    None => {
+                        return CommandResult::invalid(format!("alias: {}: 未找到", arg));
+                    }
+                },
+            }
+        }
+
+        if changed {
+            if let Err(e) = save_aliases(ctx.state, &user, &aliases).await {
+                error!("保存别名失败: {}", e);
+                return CommandResult::error(ErrorKind::Internal, format!("保存别名失败: {}", e));
+            }
+            info!("用户 {} 的别名已保存", user.username);
+        }
+
+        CommandResult::ok(shown.join("\n"))
+    }
+}
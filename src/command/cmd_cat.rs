@@ -0,0 +1,60 @@
+use super::args::{Positional, ValueKind};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{info, warn};
+
+pub struct CatCommand;
+
+impl CatCommand {
+    pub fn new() -> Self {
+        CatCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("cat", "显示文件内容")
+    .positionals(&[Positional::new("文件", "要显示的文件")
+        .kind(ValueKind::Path)
+        .required()
+        .repeated()])
+    .examples(&[
+        ("cat notes.md", "显示 notes.md 的内容"),
+        ("cat a.md b.md", "依次显示多个文件"),
+    ])
+    .see_also(&["echo", "ls"]);
+
+#[async_trait]
+impl CommandHandler for CatCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 cat 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+
+        // 验证登录状态并查询用户身份
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let files = matches.rest(0);
+        let mut output = String::new();
+        for (i, file) in files.iter().enumerate() {
+            ctx.progress(i as u64, Some(files.len() as u64), *file);
+            match ctx.state.vfs_manager.read_file(&user, file, ctx.cwd).await {
+                Ok(content) => output.push_str(&content),
+                Err(e) => {
+                    warn!("读取文件失败: {}", e);
+                    return CommandResult::invalid(format!("cat: {}: {}", file, e));
+                }
+            }
+        }
+
+        CommandResult::ok(output.trim_end_matches('\n').to_string())
+    }
+}
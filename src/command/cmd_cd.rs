@@ -1,12 +1,10 @@
-use super::CommandHandler;
-use crate::auth::validate_token;
-use crate::vfs::model::{Role, User as VfsUser, VfsError, VfsOp};
+use super::args::{Positional, ValueKind};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, SideEffect};
+use crate::vfs::model::VfsError;
+use crate::vfs::path_normalizer::PathNormalizer;
 use crate::vfs::permission::PermissionManager;
-use actix_web::{web, HttpResponse};
 use async_trait::async_trait;
-use log::{debug, error, info};
-use serde_json::json;
-use std::path::{Path, PathBuf};
+use log::{debug, error, info, warn};
 
 pub struct CdCommand;
 
@@ -14,142 +12,101 @@ impl CdCommand {
     pub fn new() -> Self {
         CdCommand
     }
+}
 
-    fn normalize_path(current_path: &str, target_path: &str) -> String {
-        let mut result = PathBuf::from(current_path);
+/// 校验目标目录并返回规范化后的绝对路径
+///
+/// 检查登录状态、进入权限以及目录是否存在，供 cd、pushd、popd 共用。
+pub(super) async fn resolve_dir(
+    ctx: &CommandContext<'_>,
+    target: &str,
+) -> Result<String, CommandResult> {
+    // 验证登录状态并查询用户身份
+    let user = ctx.user().await?;
 
-        // 如果是绝对路径，直接使用target_path
-        if target_path.starts_with('/') {
-            result = PathBuf::from(target_path);
-        } else {
-            // 否则拼接当前路径和目标路径
-            result.push(target_path);
+    let path = match PathNormalizer::normalize(target, ctx.cwd) {
+        Ok(path) => path,
+        Err(e) => {
+            return Err(CommandResult::invalid(e.to_string()));
         }
+    };
+    debug!("解析目标目录: {} -> {}", target, path);
 
-        // 规范化路径
-        let mut components = result.components().collect::<Vec<_>>();
-        let mut normalized_path = PathBuf::new();
+    // 检查用户是否有权限进入目标目录
+    if let Err(e) = PermissionManager::can_enter(&user, &path) {
+        return Err(CommandResult::forbidden(e.to_string()));
+    }
 
-        for component in components {
-            match component {
-                // 跳过当前目录 '.'
-                std::path::Component::CurDir => {}
-                // 如果是父级目录 '..'，则移除上一个有效的路径部分
-                std::path::Component::ParentDir => {
-                    normalized_path.pop();
-                }
-                // 其他情况，将路径部分添加到规范路径中
-                _ => normalized_path.push(component),
-            }
+    // 检查目标是否为已存在的目录
+    match ctx.state.vfs_manager.stat(&path, "/").await {
+        Ok(node) if node.is_dir => Ok(path),
+        Ok(_) => Err(CommandResult::invalid(format!("不是目录: {}", target))),
+        Err(VfsError::NodeNotFound(_)) => {
+            Err(CommandResult::invalid(format!("目录不存在: {}", target)))
+        }
+        Err(e) => {
+            error!("获取目录信息失败: {:?}", e);
+            Err(CommandResult::internal())
         }
-
-        normalized_path.to_string_lossy().to_string()
     }
 }
 
+const SPEC: CommandSpec = CommandSpec::new("cd", "切换当前工作目录")
+    .positionals(&[
+        Positional::new("目录", "目标目录，省略时回到家目录，- 表示上一个目录")
+            .kind(ValueKind::Path),
+    ])
+    .examples(&[
+        ("cd Documents", "进入 Documents 目录"),
+        ("cd", "回到家目录"),
+        ("cd -", "回到上一个目录"),
+    ])
+    .see_also(&["pwd", "pushd", "popd"])
+    .foreground()
+    .output();
+
 #[async_trait]
 impl CommandHandler for CdCommand {
-    fn name(&self) -> &'static str {
-        "cd"
-    }
-
-    fn description(&self) -> &'static str {
-        "切换当前工作目录"
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
     }
 
-    async fn handle(
-        &self,
-        args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        _cwd: &str,
-    ) -> HttpResponse {
-        // 检查参数
-        if args.len() != 2 {
-            return HttpResponse::BadRequest().json(super::CommandResponse {
-                success: false,
-                message: "用法: cd <目录路径>".to_string(),
-                data: None,
-            });
-        }
-
-        let target_path = Self::normalize_path(_cwd, args[1]);
-        let normalized_path = target_path.replace("\\", "/");
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 cd 命令");
 
-        debug!("切换目录: {} -> {}", _cwd, normalized_path);
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
 
-        // 验证 JWT
-        let claims = match validate_token(session_id) {
-            Ok(c) => c,
-            Err(_) => {
-                error!("无效的 token");
-                return HttpResponse::Unauthorized().json(super::CommandResponse {
-                    success: false,
-                    message: "请先登录".to_string(),
-                    data: None,
-                });
+        let session = match ctx.state.session_manager.get(ctx.token) {
+            Some(session) => session,
+            None => {
+                warn!("会话不存在");
+                return CommandResult::unauthorized("请先登录");
             }
         };
-        if data.auth_manager.is_token_blacklisted(session_id) {
-            debug!("Token 已失效");
-            return HttpResponse::Unauthorized().json(super::CommandResponse {
-                success: false,
-                message: "Token 已失效".to_string(),
-                data: None,
-            });
-        }
 
-        // 查询用户身份
-        let user = match sqlx::query!("SELECT username, role FROM users WHERE id = $1", claims.sub)
-            .fetch_optional(&data.db)
-            .await
-        {
-            Ok(Some(rec)) => VfsUser {
-                id: claims.sub.clone(),
-                username: rec.username,
-                roles: vec![match rec.role.as_str() {
-                    "admin" => Role::Admin,
-                    "user" => Role::Author,
-                    _ => Role::Guest,
-                }],
-            },
-            Ok(None) => {
-                error!("用户不存在");
-                return HttpResponse::Unauthorized().json(super::CommandResponse {
-                    success: false,
-                    message: "用户不存在".to_string(),
-                    data: None,
-                });
-            }
-            Err(e) => {
-                error!("数据库查询错误: {}", e);
-                return HttpResponse::InternalServerError().json(super::CommandResponse {
-                    success: false,
-                    message: "服务器内部错误".to_string(),
-                    data: None,
-                });
-            }
+        // 无参数时回到家目录，`-` 回到上一个目录
+        let target = match matches.positional(0) {
+            None => session.env.get("HOME").cloned().unwrap_or_default(),
+            Some("-") => session.env.get("OLDPWD").cloned().unwrap_or_default(),
+            Some(path) => path.to_string(),
         };
 
-        // 检查用户是否有权限进入目标目录
-        let result = PermissionManager::can_enter(&user, &normalized_path);
-        match result {
-            Ok(_) => {
-                return HttpResponse::Ok().json(super::CommandResponse {
-                    success: true,
-                    message: "目录切换成功".to_string(),
-                    data: Some(serde_json::json!({
-                        "path": normalized_path
-                    })),
-                })
-            }
-            Err(e) => {
-                return HttpResponse::Forbidden().json(super::CommandResponse {
-                    success: false,
-                    message: e.to_string(),
-                    data: None,
-                });
-            }
+        let path = match resolve_dir(ctx, &target).await {
+            Ok(path) => path,
+            Err(response) => return response,
+        };
+
+        debug!("切换目录: {} -> {}", ctx.cwd, path);
+        if let Err(e) = ctx.state.session_manager.change_dir(ctx.token, &path) {
+            warn!("切换目录失败: {}", e);
+            return CommandResult::unauthorized(e);
         }
+
+        CommandResult::ok("目录切换成功")
+            .with_data(serde_json::json!({ "path": path }))
+            .with_effect(SideEffect::ChangeDir(path))
     }
 }
@@ -0,0 +1,150 @@
+use super::args::{Positional, ValueKind};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::json;
+
+pub struct ChmodCommand;
+
+impl ChmodCommand {
+    pub fn new() -> Self {
+        ChmodCommand
+    }
+}
+
+/// 解析权限模式，支持八进制（如 `755`）和符号形式（如 `u+x`、`go-w`、`a=r`）
+pub fn parse_mode(spec: &str, current: i16) -> Option<i16> {
+    if !spec.is_empty() && spec.len() <= 4 && spec.chars().all(|c| ('0'..='7').contains(&c)) {
+        return i16::from_str_radix(spec, 8).ok().map(|m| m & 0o777);
+    }
+
+    let mut mode = current;
+    for clause in spec.split(',') {
+        let op_index = clause.find(['+', '-', '='])?;
+        let (who, rest) = clause.split_at(op_index);
+        let op = rest.chars().next()?;
+
+        // 未指定对象时作用于所有人
+        let mut mask = 0;
+        for c in who.chars() {
+            mask |= match c {
+                'u' => 0o700,
+                'g' => 0o070,
+                'o' => 0o007,
+                'a' => 0o777,
+                _ => return None,
+            };
+        }
+        if who.is_empty() {
+            mask = 0o777;
+        }
+
+        let mut bits = 0;
+        for c in rest[1..].chars() {
+            bits |= match c {
+                'r' => 0o444,
+                'w' => 0o222,
+                'x' => 0o111,
+                _ => return None,
+            };
+        }
+        let bits = bits & mask;
+
+        mode = match op {
+            '+' => mode | bits,
+            '-' => mode & !bits,
+            _ => (mode & !mask) | bits,
+        };
+    }
+    Some(mode & 0o777)
+}
+
+/// 以 `rwxr-xr-x` 形式显示权限
+pub fn format_mode(mode: i16) -> String {
+    let mut result = String::new();
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        result.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        result.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        result.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    result
+}
+
+const SPEC: CommandSpec = CommandSpec::new("chmod", "修改文件权限")
+    .positionals(&[
+        Positional::new("模式", "八进制（如 755）或符号形式（如 u+x、go-w、a=r）").required(),
+        Positional::new("文件", "要修改的文件")
+            .kind(ValueKind::Path)
+            .required()
+            .repeated(),
+    ])
+    .examples(&[
+        ("chmod 755 deploy.sh", "设置为 rwxr-xr-x"),
+        ("chmod u+x deploy.sh", "为所有者添加执行权限"),
+        ("chmod go-w notes.md", "去掉组和其他用户的写权限"),
+    ])
+    .see_also(&["ls", "sh"])
+    .output();
+
+#[async_trait]
+impl CommandHandler for ChmodCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 chmod 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let spec = matches.positional(0).unwrap_or_default();
+
+        // 验证登录状态并查询用户身份
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let mut changed = Vec::new();
+        let files = matches.rest(1);
+        for (i, file) in files.iter().enumerate() {
+            ctx.progress(i as u64, Some(files.len() as u64), *file);
+            let node = match ctx.state.vfs_manager.stat(file, ctx.cwd).await {
+                Ok(node) => node,
+                Err(e) => {
+                    warn!("获取节点信息失败: {}", e);
+                    return CommandResult::invalid(format!("chmod: {}: {}", file, e));
+                }
+            };
+
+            let mode = match parse_mode(spec, node.permissions) {
+                Some(mode) => mode,
+                None => {
+                    return CommandResult::invalid(format!("chmod: 无效的模式: {}", spec));
+                }
+            };
+
+            match ctx
+                .state
+                .vfs_manager
+                .chmod(&user, file, ctx.cwd, mode)
+                .await
+            {
+                Ok(node) => changed.push(json!({
+                    "name": node.name,
+                    "permissions": format!("{:o}", node.permissions),
+                    "mode": format_mode(node.permissions)
+                })),
+                Err(e) => {
+                    warn!("修改权限失败: {}", e);
+                    return CommandResult::forbidden(format!("chmod: {}: {}", file, e));
+                }
+            }
+        }
+
+        CommandResult::ok("").with_data(json!(changed))
+    }
+}
@@ -1,5 +1,4 @@
-use super::CommandHandler;
-use actix_web::{web, HttpResponse};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, SideEffect};
 use async_trait::async_trait;
 use log::{debug, info};
 
@@ -11,30 +10,25 @@ impl ClearCommand {
     }
 }
 
+const SPEC: CommandSpec = CommandSpec::new("clear", "清除屏幕")
+    .examples(&[("clear", "清除屏幕")])
+    .foreground();
+
 #[async_trait]
 impl CommandHandler for ClearCommand {
-    fn name(&self) -> &'static str {
-        "clear"
-    }
-
-    fn description(&self) -> &'static str {
-        "清除屏幕，用法：clear"
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
     }
 
-    async fn handle(
-        &self,
-        args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        cwd: &str,
-    ) -> HttpResponse {
+    async fn handle(&self, args: &[&str], _ctx: &CommandContext<'_>) -> CommandResult {
         info!("开始处理清除命令");
+
+        if let Err(result) = SPEC.parse(args) {
+            return result;
+        }
+
         debug!("清除屏幕");
 
-        HttpResponse::Ok().json(super::CommandResponse {
-            success: true,
-            message: "".to_string(),
-            data: None,
-        })
+        CommandResult::ok("").with_effect(SideEffect::ClearScreen)
     }
 }
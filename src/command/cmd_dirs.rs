@@ -0,0 +1,64 @@
+use super::args::Opt;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::json;
+
+pub struct DirsCommand;
+
+impl DirsCommand {
+    pub fn new() -> Self {
+        DirsCommand
+    }
+}
+
+/// 按 shell 习惯格式化目录栈：当前目录在前，栈顶紧随其后
+pub(super) fn format_dirs(cwd: &str, stack: &[String]) -> String {
+    std::iter::once(cwd)
+        .chain(stack.iter().rev().map(|s| s.as_str()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+const SPEC: CommandSpec = CommandSpec::new("dirs", "显示目录栈")
+    .options(&[Opt::flag("-c", "清空目录栈")])
+    .examples(&[("dirs", "显示当前目录和目录栈"), ("dirs -c", "清空目录栈")])
+    .see_also(&["pushd", "popd"])
+    .foreground()
+    .output();
+
+#[async_trait]
+impl CommandHandler for DirsCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 dirs 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+
+        let session = match ctx.state.session_manager.get(ctx.token) {
+            Some(session) => session,
+            None => {
+                warn!("会话不存在");
+                return CommandResult::unauthorized("请先登录");
+            }
+        };
+
+        if matches.flag("-c") {
+            if let Err(e) = ctx.state.session_manager.clear_dirs(ctx.token) {
+                return CommandResult::unauthorized(e);
+            }
+            return CommandResult::ok("");
+        }
+
+        CommandResult::ok(format_dirs(&session.cwd, &session.dir_stack)).with_data(json!({
+            "path": session.cwd,
+            "stack": session.dir_stack.iter().rev().collect::<Vec<_>>()
+        }))
+    }
+}
@@ -0,0 +1,132 @@
+use super::args::{Opt, Positional};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::vfs::model::VfsError;
+use async_trait::async_trait;
+use log::{debug, info, warn};
+
+pub struct EchoCommand;
+
+impl EchoCommand {
+    pub fn new() -> Self {
+        EchoCommand
+    }
+}
+
+// 处理 -e 选项下的转义序列
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+const SPEC: CommandSpec = CommandSpec::new("echo", "输出文本，可用 > 或 >> 写入文件")
+    .positionals(&[Positional::new("文本", "要输出的文本").repeated()])
+    .options(&[
+        Opt::flag("-n", "写入文件时不追加换行"),
+        Opt::flag("-e", "解释 \\n、\\t 等转义序列"),
+    ])
+    .examples(&[
+        ("echo hello $USER", "输出文本并展开变量"),
+        ("echo -e \"a\\tb\"", "输出制表符"),
+        ("echo \"# 标题\" > post.md", "写入文件"),
+        ("echo \"更多内容\" >> post.md", "追加到文件末尾"),
+    ])
+    .see_also(&["cat"]);
+
+#[async_trait]
+impl CommandHandler for EchoCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 echo 命令");
+
+        // 解析选项
+        let mut newline = true;
+        let mut escapes = false;
+        let mut rest = &args[1..];
+        while let Some(&flag) = rest.first() {
+            match flag {
+                "-n" => newline = false,
+                "-e" => escapes = true,
+                _ => break,
+            }
+            rest = &rest[1..];
+        }
+
+        // 解析输出重定向
+        let (words, redirect) = match rest.iter().position(|&a| a == ">" || a == ">>") {
+            Some(i) if i + 2 == rest.len() => (&rest[..i], Some((rest[i] == ">>", rest[i + 1]))),
+            Some(_) => {
+                return CommandResult::invalid(
+                    "用法：echo [-n] [-e] [文本 ...] [> 文件 | >> 文件]",
+                );
+            }
+            None => (rest, None),
+        };
+
+        let mut text = words.join(" ");
+        if escapes {
+            text = unescape(&text);
+        }
+
+        let (append, file) = match redirect {
+            Some(redirect) => redirect,
+            None => {
+                return CommandResult::ok(text);
+            }
+        };
+        if newline {
+            text.push('\n');
+        }
+        debug!("写入文件: {} (追加: {})", file, append);
+
+        // 写文件需要登录
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        // 追加时保留原有内容
+        if append {
+            match ctx.state.vfs_manager.read_file(&user, file, ctx.cwd).await {
+                Ok(existing) => text = existing + &text,
+                Err(VfsError::NodeNotFound(_)) => {}
+                Err(e) => {
+                    warn!("读取文件失败: {}", e);
+                    return CommandResult::invalid(format!("echo: {}", e));
+                }
+            }
+        }
+
+        match ctx
+            .state
+            .vfs_manager
+            .write_file(&user, file, ctx.cwd, &text)
+            .await
+        {
+            Ok(_) => CommandResult::ok(""),
+            Err(e) => {
+                warn!("写入文件失败: {}", e);
+                CommandResult::invalid(format!("echo: {}", e))
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::json;
+
+pub struct EnvCommand;
+
+impl EnvCommand {
+    pub fn new() -> Self {
+        EnvCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("env", "显示当前会话的环境变量")
+    .examples(&[("env", "列出所有环境变量")])
+    .see_also(&["export", "unset"])
+    .output();
+
+#[async_trait]
+impl CommandHandler for EnvCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 env 命令");
+
+        if let Err(result) = SPEC.parse(args) {
+            return result;
+        }
+
+        // 验证 token
+        if let Err(result) = ctx.claims() {
+            return result;
+        }
+
+        match ctx.state.session_manager.get(ctx.token) {
+            Some(session) => {
+                let mut lines: Vec<String> = session
+                    .env
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect();
+                lines.sort();
+                CommandResult::ok(lines.join("\n")).with_data(json!(session.env))
+            }
+            None => {
+                warn!("会话不存在");
+                CommandResult::unauthorized("会话不存在，请重新登录")
+            }
+        }
+    }
+}
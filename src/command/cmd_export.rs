@@ -0,0 +1,73 @@
+use super::args::Positional;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::json;
+
+pub struct ExportCommand;
+
+impl ExportCommand {
+    pub fn new() -> Self {
+        ExportCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("export", "设置环境变量")
+    .positionals(&[Positional::new("NAME=value", "要设置的变量，省略时列出所有变量").repeated()])
+    .examples(&[
+        ("export EDITOR=vim", "设置变量 EDITOR"),
+        ("export", "列出所有变量"),
+    ])
+    .see_also(&["env", "unset"])
+    .foreground()
+    .output();
+
+#[async_trait]
+impl CommandHandler for ExportCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 export 命令");
+
+        // 验证 token
+        if let Err(result) = ctx.claims() {
+            return result;
+        }
+
+        let session = match ctx.state.session_manager.get(ctx.token) {
+            Some(session) => session,
+            None => {
+                warn!("会话不存在");
+                return CommandResult::unauthorized("会话不存在，请重新登录");
+            }
+        };
+
+        // 不带参数时列出所有变量
+        if args.len() == 1 {
+            let mut lines: Vec<String> = session
+                .env
+                .iter()
+                .map(|(name, value)| format!("export {}=\"{}\"", name, value))
+                .collect();
+            lines.sort();
+            return CommandResult::ok(lines.join("\n")).with_data(json!(session.env));
+        }
+
+        for arg in &args[1..] {
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, value.to_string()),
+                // 仅给出变量名时保留已有值
+                None => (*arg, session.env.get(*arg).cloned().unwrap_or_default()),
+            };
+
+            if let Err(e) = ctx.state.session_manager.set_var(ctx.token, name, &value) {
+                warn!("设置环境变量失败: {}", e);
+                return CommandResult::invalid(format!("export: {}", e));
+            }
+        }
+
+        CommandResult::ok("")
+    }
+}
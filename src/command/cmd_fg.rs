@@ -0,0 +1,43 @@
+use super::args::Positional;
+use super::cmd_wait::{resolve_job, wait_for};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::info;
+
+pub struct FgCommand;
+
+impl FgCommand {
+    pub fn new() -> Self {
+        FgCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("fg", "将后台任务切回前台，等待结束并显示输出")
+    .positionals(&[Positional::new("任务", "任务编号，默认为最近启动的任务")])
+    .examples(&[("fg", "等待最近启动的任务"), ("fg %2", "等待任务 2")])
+    .see_also(&["jobs", "wait", "kill"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for FgCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 fg 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        if let Err(result) = ctx.claims() {
+            return result;
+        }
+
+        match resolve_job("fg", matches.positional(0), ctx) {
+            Ok(id) => wait_for("fg", id, ctx).await,
+            Err(result) => result,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use super::account::{find_user, require_admin};
+use super::args::{Opt, Positional};
+use super::group::{find_group, require_group_admin};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{error, info};
+
+pub struct GpasswdCommand;
+
+impl GpasswdCommand {
+    pub fn new() -> Self {
+        GpasswdCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("gpasswd", "管理组成员和组管理员")
+    .positionals(&[Positional::new("组名", "要管理的组").required()])
+    .options(&[
+        Opt::value("--add", "用户", "把用户加入组").short("-a"),
+        Opt::value("--delete", "用户", "把用户移出组").short("-d"),
+        Opt::value(
+            "--administrators",
+            "用户,...",
+            "设置组管理员（需要管理员权限），逗号分隔，空字符串表示清空",
+        )
+        .short("-A"),
+    ])
+    .examples(&[
+        ("gpasswd -a bob writers", "把 bob 加入 writers 组"),
+        ("gpasswd -d bob writers", "把 bob 移出 writers 组"),
+        (
+            "sudo gpasswd -A alice,bob writers",
+            "设置 alice 和 bob 为 writers 的组管理员",
+        ),
+    ])
+    .see_also(&["groupadd", "groups", "id"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for GpasswdCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 gpasswd 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let add = matches.value("--add");
+        let delete = matches.value("--delete");
+        let administrators = matches.value("--administrators");
+        if [add, delete, administrators]
+            .iter()
+            .filter(|value| value.is_some())
+            .count()
+            != 1
+        {
+            return SPEC.usage_error("需要且只能指定 -a、-d、-A 中的一个");
+        }
+
+        let name = matches.positional(0).unwrap_or_default();
+        let group_id = match find_group(ctx, "gpasswd", name).await {
+            Ok(group_id) => group_id,
+            Err(result) => return result,
+        };
+
+        // 设置组管理员只能由站点管理员完成
+        if let Some(list) = administrators {
+            let admin = match require_admin(ctx, "gpasswd").await {
+                Ok(admin) => admin,
+                Err(result) => return result,
+            };
+            let usernames: Vec<&str> = list
+                .split(',')
+                .map(str::trim)
+                .filter(|username| !username.is_empty())
+                .collect();
+            let mut user_ids = Vec::new();
+            for username in &usernames {
+                match find_user(ctx, "gpasswd", username).await {
+                    Ok(user_id) => user_ids.push(user_id),
+                    Err(result) => return result,
+                }
+            }
+
+            // 组管理员同时是组成员
+            let updated = async {
+                let mut tx = ctx.state.db.begin().await?;
+                sqlx::query!(
+                    "UPDATE user_group_members SET is_admin = FALSE WHERE group_id = $1",
+                    group_id
+                )
+                .execute(&mut tx)
+                .await?;
+                for user_id in &user_ids {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO user_group_members (user_id, group_id, is_admin)
+                        VALUES ($1, $2, TRUE)
+                        ON CONFLICT (user_id, group_id) DO UPDATE SET is_admin = TRUE
+                        "#,
+                        user_id,
+                        group_id
+                    )
+                    .execute(&mut tx)
+                    .await?;
+                }
+                tx.commit().await
+            };
+            if let Err(e) = updated.await {
+                error!("设置组管理员失败: {}", e);
+                return CommandResult::internal();
+            }
+            info!(
+                "管理员 {} 设置了组 {} 的管理员: {:?}",
+                admin.username, name, usernames
+            );
+            return if usernames.is_empty() {
+                CommandResult::ok(format!("已清空组 {} 的管理员", name))
+            } else {
+                CommandResult::ok(format!("组 {} 的管理员: {}", name, usernames.join(", ")))
+            };
+        }
+
+        // 增删成员可以由组管理员完成
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+        if let Err(result) = require_group_admin(ctx, "gpasswd", &user, group_id).await {
+            return result;
+        }
+
+        if let Some(username) = add {
+            let user_id = match find_user(ctx, "gpasswd", username).await {
+                Ok(user_id) => user_id,
+                Err(result) => return result,
+            };
+            match sqlx::query!(
+                r#"
+                INSERT INTO user_group_members (user_id, group_id) VALUES ($1, $2)
+                ON CONFLICT (user_id, group_id) DO NOTHING
+                "#,
+                user_id,
+                group_id
+            )
+            .execute(&ctx.state.db)
+            .await
+            {
+                Ok(result) if result.rows_affected() == 0 => {
+                    CommandResult::ok(format!("{} 已经是组 {} 的成员", username, name))
+                }
+                Ok(_) => {
+                    info!("{} 把 {} 加入组 {}", user.username, username, name);
+                    CommandResult::ok(format!("已将 {} 加入组 {}", username, name))
+                }
+                Err(e) => {
+                    error!("添加组成员失败: {}", e);
+                    CommandResult::internal()
+                }
+            }
+        } else {
+            let username = delete.unwrap_or_default();
+            let user_id = match find_user(ctx, "gpasswd", username).await {
+                Ok(user_id) => user_id,
+                Err(result) => return result,
+            };
+            match sqlx::query!(
+                "DELETE FROM user_group_members WHERE user_id = $1 AND group_id = $2",
+                user_id,
+                group_id
+            )
+            .execute(&ctx.state.db)
+            .await
+            {
+                Ok(result) if result.rows_affected() == 0 => {
+                    CommandResult::invalid(format!("gpasswd: {} 不是组 {} 的成员", username, name))
+                }
+                Ok(_) => {
+                    info!("{} 把 {} 移出组 {}", user.username, username, name);
+                    CommandResult::ok(format!("已将 {} 移出组 {}", username, name))
+                }
+                Err(e) => {
+                    error!("删除组成员失败: {}", e);
+                    CommandResult::internal()
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use super::account::require_admin;
+use super::args::Positional;
+use super::group::validate_group_name;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{error, info};
+
+pub struct GroupaddCommand;
+
+impl GroupaddCommand {
+    pub fn new() -> Self {
+        GroupaddCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("groupadd", "创建组（需要管理员权限）")
+    .positionals(&[
+        Positional::new("组名", "以字母开头，1-32 个字母、数字、下划线或连字符").required(),
+    ])
+    .examples(&[("sudo groupadd writers", "创建 writers 组")])
+    .see_also(&["groupdel", "gpasswd", "groups"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for GroupaddCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 groupadd 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let admin = match require_admin(ctx, "groupadd").await {
+            Ok(admin) => admin,
+            Err(result) => return result,
+        };
+
+        let name = matches.positional(0).unwrap_or_default();
+        if let Err(e) = validate_group_name(name) {
+            return CommandResult::invalid(format!("groupadd: {}", e));
+        }
+        match sqlx::query!(
+            "INSERT INTO user_groups (name) VALUES ($1) ON CONFLICT (name) DO NOTHING",
+            name
+        )
+        .execute(&ctx.state.db)
+        .await
+        {
+            Ok(result) if result.rows_affected() == 0 => {
+                CommandResult::invalid(format!("groupadd: 组 {} 已存在", name))
+            }
+            Ok(_) => {
+                info!("管理员 {} 创建了组 {}", admin.username, name);
+                CommandResult::ok(format!("已创建组 {}", name))
+            }
+            Err(e) => {
+                error!("创建组失败: {}", e);
+                CommandResult::internal()
+            }
+        }
+    }
+}
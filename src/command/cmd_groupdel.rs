@@ -0,0 +1,68 @@
+use super::account::require_admin;
+use super::args::Positional;
+use super::group::find_group;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{error, info};
+
+pub struct GroupdelCommand;
+
+impl GroupdelCommand {
+    pub fn new() -> Self {
+        GroupdelCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("groupdel", "删除组（需要管理员权限）")
+    .positionals(&[Positional::new("组名", "要删除的组").required()])
+    .examples(&[("sudo groupdel writers", "删除 writers 组，成员关系一并删除")])
+    .see_also(&["groupadd", "gpasswd"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for GroupdelCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 groupdel 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let admin = match require_admin(ctx, "groupdel").await {
+            Ok(admin) => admin,
+            Err(result) => return result,
+        };
+
+        let name = matches.positional(0).unwrap_or_default();
+        let group_id = match find_group(ctx, "groupdel", name).await {
+            Ok(group_id) => group_id,
+            Err(result) => return result,
+        };
+
+        // 先删除成员关系，再删除组
+        let deleted = async {
+            let mut tx = ctx.state.db.begin().await?;
+            sqlx::query!(
+                "DELETE FROM user_group_members WHERE group_id = $1",
+                group_id
+            )
+            .execute(&mut tx)
+            .await?;
+            sqlx::query!("DELETE FROM user_groups WHERE id = $1", group_id)
+                .execute(&mut tx)
+                .await?;
+            tx.commit().await
+        };
+        if let Err(e) = deleted.await {
+            error!("删除组失败: {}", e);
+            return CommandResult::internal();
+        }
+
+        info!("管理员 {} 删除了组 {}", admin.username, name);
+        CommandResult::ok(format!("已删除组 {}", name))
+    }
+}
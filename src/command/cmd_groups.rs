@@ -0,0 +1,72 @@
+use super::account::find_user;
+use super::args::Positional;
+use super::group::user_groups;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{error, info};
+use serde_json::json;
+
+pub struct GroupsCommand;
+
+impl GroupsCommand {
+    pub fn new() -> Self {
+        GroupsCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("groups", "显示用户所属的组")
+    .positionals(&[Positional::new("用户", "要查询的用户，默认为当前用户")])
+    .examples(&[
+        ("groups", "显示自己所属的组"),
+        (
+            "groups bob --output table",
+            "以表格显示 bob 所属的组及是否为组管理员",
+        ),
+    ])
+    .see_also(&["id", "gpasswd"])
+    .output();
+
+#[async_trait]
+impl CommandHandler for GroupsCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 groups 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let claims = match ctx.claims() {
+            Ok(claims) => claims,
+            Err(result) => return result,
+        };
+        let target = matches.positional(0);
+        let user_id = match target {
+            Some(username) => match find_user(ctx, "groups", username).await {
+                Ok(user_id) => user_id,
+                Err(result) => return result,
+            },
+            None => claims.sub,
+        };
+
+        let groups = match user_groups(&ctx.state.db, &user_id).await {
+            Ok(groups) => groups,
+            Err(e) => {
+                error!("查询用户组失败: {}", e);
+                return CommandResult::internal();
+            }
+        };
+        let names: Vec<&str> = groups.iter().map(|group| group.name.as_str()).collect();
+        // 与 Unix 一致，指定用户时以 `用户 : ` 开头
+        let message = match target {
+            Some(username) => format!("{} : {}", username, names.join(" ")),
+            None => names.join(" "),
+        };
+        CommandResult::ok(message.trim_end())
+            .with_data(json!(groups))
+            .with_records(&groups)
+    }
+}
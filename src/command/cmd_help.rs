@@ -1,7 +1,8 @@
-use super::CommandHandler;
-use actix_web::{web, HttpResponse};
+use super::args::{Positional, ValueKind};
+use super::man;
+use super::style::Style;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
 use async_trait::async_trait;
-use serde_json::Value;
 
 pub struct HelpCommand;
 
@@ -11,40 +12,57 @@ impl HelpCommand {
     }
 }
 
+const SPEC: CommandSpec = CommandSpec::new("help", "显示所有可用命令的帮助信息")
+    .positionals(&[Positional::new("命令", "显示该命令的手册").kind(ValueKind::Command)])
+    .examples(&[
+        ("help", "列出所有可用命令"),
+        ("help ls", "显示 ls 命令的手册，与 man ls 相同"),
+    ])
+    .see_also(&["man"])
+    .color();
+
 #[async_trait]
 impl CommandHandler for HelpCommand {
-    fn name(&self) -> &'static str {
-        "help"
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
     }
 
-    fn description(&self) -> &'static str {
-        "显示所有可用命令的帮助信息"
-    }
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+
+        // help <命令> 显示该命令的手册
+        if let Some(name) = matches.positional(0) {
+            return match man::page(ctx.state, name).await {
+                Some(page) => CommandResult::ok(page),
+                None => CommandResult::invalid(format!("help: 没有 {} 的帮助信息", name)),
+            };
+        }
 
-    async fn handle(
-        &self,
-        args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        cwd: &str,
-    ) -> HttpResponse {
         let mut commands_info = Vec::new();
 
         // 获取所有已注册的命令
-        for (name, handler) in &data.command_registry.commands {
-            commands_info.push(format!("- {}: {}", name, handler.description()));
+        for (name, handler) in &ctx.state.command_registry.commands {
+            commands_info.push((name, handler.description()));
         }
 
         // 按命令名称排序
         commands_info.sort();
+        let commands_info: Vec<String> = commands_info
+            .into_iter()
+            .map(|(name, description)| {
+                format!("- {}: {}", ctx.paint(Style::Highlight, name), description)
+            })
+            .collect();
 
         // 构建帮助信息
-        let help_text = format!("可用命令:\n{}", commands_info.join("\n"));
+        let help_text = format!(
+            "可用命令:\n{}\n\n输入 help <命令> 或 man <命令> 查看命令的详细手册",
+            commands_info.join("\n")
+        );
 
-        HttpResponse::Ok().json(super::CommandResponse {
-            success: true,
-            message: help_text,
-            data: None,
-        })
+        CommandResult::ok(help_text)
     }
 }
@@ -0,0 +1,92 @@
+use super::args::{Opt, Positional, ValueKind};
+use super::history;
+use super::output::Record;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{error, info};
+use serde_json::json;
+
+pub struct HistoryCommand;
+
+impl HistoryCommand {
+    pub fn new() -> Self {
+        HistoryCommand
+    }
+}
+
+impl Record for history::HistoryEntry {
+    const COLUMNS: &'static [&'static str] =
+        &["number", "created_at", "command", "cwd", "exit_status"];
+}
+
+const SPEC: CommandSpec = CommandSpec::new("history", "显示或清除命令历史")
+    .positionals(&[Positional::new("N", "只显示最近 N 条").kind(ValueKind::Number)])
+    .options(&[Opt::flag("-c", "清除命令历史")])
+    .examples(&[
+        ("history", "显示全部历史"),
+        ("history 10", "显示最近 10 条"),
+        ("history -c", "清除历史"),
+        ("!!", "重新执行上一条命令"),
+    ])
+    .output();
+
+#[async_trait]
+impl CommandHandler for HistoryCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 history 命令");
+
+        // 验证 token
+        let claims = match ctx.claims() {
+            Ok(claims) => claims,
+            Err(result) => return result,
+        };
+
+        // 解析参数
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let clear = matches.flag("-c");
+        let limit = matches.positional(0).and_then(|n| n.parse::<i64>().ok());
+
+        if clear {
+            return match history::clear(&ctx.state.db, &claims.sub).await {
+                Ok(count) => {
+                    info!("清除了 {} 条命令历史", count);
+                    CommandResult::ok("")
+                }
+                Err(e) => {
+                    error!("清除命令历史失败: {}", e);
+                    CommandResult::internal()
+                }
+            };
+        }
+
+        match history::recent(&ctx.state.db, &claims.sub, limit).await {
+            Ok(entries) => CommandResult::ok(
+                entries
+                    .iter()
+                    .map(|entry| {
+                        format!(
+                            "{:>5}  {}  {}",
+                            entry.number,
+                            entry.created_at.format("%Y-%m-%d %H:%M:%S"),
+                            entry.command
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+            .with_data(json!(entries))
+            .with_records(&entries),
+            Err(e) => {
+                error!("查询命令历史失败: {}", e);
+                CommandResult::internal()
+            }
+        }
+    }
+}
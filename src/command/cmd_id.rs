@@ -1,8 +1,10 @@
-use super::CommandHandler;
-use crate::auth::{validate_token, AuthManager};
-use actix_web::{web, HttpResponse};
+use super::group::user_groups;
+use super::output::Record;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::auth::validate_token;
 use async_trait::async_trait;
-use log::{debug, info};
+use log::{debug, error, info};
+use serde::Serialize;
 use serde_json::json;
 
 pub struct IdCommand;
@@ -13,35 +15,52 @@ impl IdCommand {
     }
 }
 
+/// 用户身份，访客没有 ID
+///
+/// `groups` 为所属的组，格式与 Unix 的 `id` 相同：`组ID(组名)`，以逗号分隔。
+#[derive(Serialize)]
+struct Identity {
+    id: Option<String>,
+    username: String,
+    role: String,
+    groups: String,
+    is_guest: bool,
+}
+
+impl Record for Identity {
+    const COLUMNS: &'static [&'static str] = &["id", "username", "role", "groups", "is_guest"];
+}
+
+fn identity_result(message: impl Into<String>, identity: Identity) -> CommandResult {
+    CommandResult::ok(message)
+        .with_data(json!(identity))
+        .with_records(&[identity])
+}
+
+const SPEC: CommandSpec = CommandSpec::new("id", "显示当前用户信息")
+    .examples(&[("id", "显示当前用户的 ID、用户名、角色和所属的组")])
+    .see_also(&["profile", "groups"])
+    .output();
+
 #[async_trait]
 impl CommandHandler for IdCommand {
-    fn name(&self) -> &'static str {
-        "id"
-    }
-
-    fn description(&self) -> &'static str {
-        "显示当前用户信息，用法：id"
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
     }
 
-    async fn handle(
-        &self,
-        args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        cwd: &str,
-    ) -> HttpResponse {
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
         info!("开始处理ID命令");
 
+        if let Err(result) = SPEC.parse(args) {
+            return result;
+        }
+
         // 验证 token
-        if let Ok(claims) = validate_token(session_id) {
+        if let Ok(claims) = validate_token(ctx.token) {
             // 检查 token 是否在黑名单中
-            if data.auth_manager.is_token_blacklisted(session_id) {
+            if ctx.state.auth_manager.is_token_blacklisted(ctx.token) {
                 debug!("Token 已失效");
-                return HttpResponse::Unauthorized().json(super::CommandResponse {
-                    success: false,
-                    message: "Token 已失效".to_string(),
-                    data: None,
-                });
+                return CommandResult::unauthorized("Token 已失效");
             }
 
             // 从数据库获取用户信息
@@ -49,54 +68,58 @@ impl CommandHandler for IdCommand {
                 "SELECT id, username, role FROM users WHERE id = $1",
                 claims.sub
             )
-            .fetch_optional(&data.db)
+            .fetch_optional(&ctx.state.db)
             .await
             {
                 Ok(Some(user)) => {
                     debug!("显示用户权限信息: {}", user.username);
-                    HttpResponse::Ok().json(super::CommandResponse {
-                        success: true,
-                        message: format!(
-                            "用户ID: {}\n用户名: {}\n角色: {}",
-                            user.id, user.username, user.role
+                    let groups = match user_groups(&ctx.state.db, &user.id).await {
+                        Ok(groups) => groups
+                            .iter()
+                            .map(|group| format!("{}({})", group.id, group.name))
+                            .collect::<Vec<_>>()
+                            .join(","),
+                        Err(e) => {
+                            error!("查询用户组失败: {}", e);
+                            return CommandResult::internal();
+                        }
+                    };
+                    identity_result(
+                        format!(
+                            "用户ID: {}\n用户名: {}\n角色: {}\ngroups={}",
+                            user.id, user.username, user.role, groups
                         ),
-                        data: Some(json!({
-                            "id": user.id,
-                            "username": user.username,
-                            "role": user.role,
-                            "is_guest": false
-                        })),
-                    })
+                        Identity {
+                            id: Some(user.id),
+                            username: user.username,
+                            role: user.role,
+                            groups,
+                            is_guest: false,
+                        },
+                    )
                 }
                 Ok(None) => {
                     debug!("用户不存在");
-                    HttpResponse::Unauthorized().json(super::CommandResponse {
-                        success: false,
-                        message: "用户不存在".to_string(),
-                        data: None,
-                    })
+                    CommandResult::unauthorized("用户不存在")
                 }
                 Err(e) => {
                     debug!("数据库查询错误: {}", e);
-                    HttpResponse::InternalServerError().json(super::CommandResponse {
-                        success: false,
-                        message: "服务器内部错误".to_string(),
-                        data: None,
-                    })
+                    CommandResult::internal()
                 }
             }
         } else {
             // 访客模式
             debug!("显示访客信息");
-            HttpResponse::Ok().json(super::CommandResponse {
-                success: true,
-                message: "当前为访客模式".to_string(),
-                data: Some(json!({
-                    "username": "guest",
-                    "role": "guest",
-                    "is_guest": true
-                })),
-            })
+            identity_result(
+                "当前为访客模式",
+                Identity {
+                    id: None,
+                    username: "guest".to_string(),
+                    role: "guest".to_string(),
+                    groups: String::new(),
+                    is_guest: true,
+                },
+            )
         }
     }
 }
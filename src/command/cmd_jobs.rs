@@ -0,0 +1,66 @@
+use super::output::Record;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::job::{Job, JobStatus};
+use async_trait::async_trait;
+use log::info;
+use serde_json::json;
+
+pub struct JobsCommand;
+
+impl JobsCommand {
+    pub fn new() -> Self {
+        JobsCommand
+    }
+}
+
+/// 任务的单行摘要，如 `[1] 运行中 (12 第 3 行) sh build.sh`，运行中的任务附带进度
+pub(super) fn format_job(job: &Job) -> String {
+    match job.status {
+        JobStatus::Running => format!(
+            "[{}] {} ({}) {}",
+            job.id,
+            job.status.label(),
+            job.progress,
+            job.command
+        ),
+        _ => format!("[{}] {} {}", job.id, job.status.label(), job.command),
+    }
+}
+
+impl Record for Job {
+    const COLUMNS: &'static [&'static str] =
+        &["id", "status", "command", "started_at", "finished_at"];
+}
+
+const SPEC: CommandSpec = CommandSpec::new("jobs", "列出当前会话的后台任务")
+    .examples(&[
+        ("sh build.sh &", "在后台执行脚本"),
+        ("jobs", "查看后台任务的状态和进度"),
+    ])
+    .see_also(&["wait", "fg", "kill"])
+    .foreground()
+    .output();
+
+#[async_trait]
+impl CommandHandler for JobsCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 jobs 命令");
+
+        if let Err(result) = SPEC.parse(args) {
+            return result;
+        }
+        if let Err(result) = ctx.claims() {
+            return result;
+        }
+
+        let jobs = ctx.state.job_manager.list(ctx.token);
+        let output = jobs.iter().map(format_job).collect::<Vec<_>>().join("\n");
+        CommandResult::ok(output)
+            .with_data(json!({ "jobs": jobs }))
+            .with_records(&jobs)
+    }
+}
@@ -0,0 +1,72 @@
+use super::args::Positional;
+use super::cmd_jobs::format_job;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::job::parse_job_id;
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::json;
+
+pub struct KillCommand;
+
+impl KillCommand {
+    pub fn new() -> Self {
+        KillCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("kill", "终止后台任务")
+    .positionals(&[Positional::new("任务", "任务编号，如 1 或 %1")
+        .required()
+        .repeated()])
+    .examples(&[("kill 1", "终止任务 1"), ("kill %1 %2", "终止多个任务")])
+    .see_also(&["jobs", "wait", "fg"])
+    .foreground()
+    .output();
+
+#[async_trait]
+impl CommandHandler for KillCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 kill 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        if let Err(result) = ctx.claims() {
+            return result;
+        }
+
+        let mut killed = Vec::new();
+        let mut errors = Vec::new();
+        for spec in matches.rest(0) {
+            let id = match parse_job_id(spec) {
+                Some(id) => id,
+                None => {
+                    errors.push(format!("kill: 无效的任务编号: {}", spec));
+                    continue;
+                }
+            };
+            match ctx.state.job_manager.kill(ctx.token, id) {
+                Ok(job) => killed.push(job),
+                Err(e) => {
+                    warn!("终止任务失败: {}", e);
+                    errors.push(format!("kill: {}", e));
+                }
+            }
+        }
+
+        let output = killed.iter().map(format_job).collect::<Vec<_>>().join("\n");
+        let ids: Vec<u64> = killed.iter().map(|job| job.id).collect();
+        if errors.is_empty() {
+            return CommandResult::ok(output).with_data(json!({ "killed": ids }));
+        }
+        let mut result =
+            CommandResult::invalid(errors.join("\n")).with_data(json!({ "killed": ids }));
+        result.stdout = output;
+        result
+    }
+}
@@ -0,0 +1,120 @@
+use super::account::require_admin;
+use super::args::{Opt, Positional, ValueKind};
+use super::login_history::{self, LoginRecord};
+use super::output::{align, Record};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::vfs::model::Role;
+use async_trait::async_trait;
+use chrono::Local;
+use log::{error, info};
+use serde_json::json;
+
+pub struct LastCommand;
+
+impl LastCommand {
+    pub fn new() -> Self {
+        LastCommand
+    }
+}
+
+// 默认和最多显示的记录数
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 1000;
+
+impl Record for LoginRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "created_at",
+        "username",
+        "success",
+        "method",
+        "via",
+        "ip",
+        "user_agent",
+    ];
+}
+
+const SPEC: CommandSpec = CommandSpec::new("last", "显示登录历史")
+    .positionals(&[Positional::new("用户", "只显示该用户的登录记录")])
+    .options(&[
+        Opt::value("-n", "N", "显示最近 N 条（默认 20）").kind(ValueKind::Number),
+        Opt::flag("--failed", "只显示失败的登录").short("-f"),
+    ])
+    .examples(&[
+        ("last", "显示自己最近的登录记录"),
+        ("last -n 50", "显示自己最近 50 条登录记录"),
+        ("sudo last", "管理员查看所有用户的登录记录"),
+        ("sudo last --failed", "管理员查看失败的登录，排查暴力破解"),
+        ("sudo last bob", "管理员查看 bob 的登录记录"),
+    ])
+    .see_also(&["who", "w", "login"])
+    .output();
+
+#[async_trait]
+impl CommandHandler for LastCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 last 命令");
+
+        // 解析参数
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+        let failed_only = matches.flag("--failed");
+        let limit = matches
+            .value_as::<i64>("-n")
+            .unwrap_or(DEFAULT_LIMIT)
+            .clamp(1, MAX_LIMIT);
+
+        // 普通用户只能查看自己的记录，管理员不指定用户时查看所有用户
+        let username = match matches.positional(0) {
+            Some(name) if name != user.username => {
+                if let Err(result) = require_admin(ctx, "last").await {
+                    return result;
+                }
+                Some(name)
+            }
+            Some(name) => Some(name),
+            None if user.roles.contains(&Role::Admin) => None,
+            None => Some(user.username.as_str()),
+        };
+
+        let records = match login_history::recent(&ctx.state.db, username, failed_only, limit).await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                error!("查询登录历史失败: {}", e);
+                return CommandResult::internal();
+            }
+        };
+
+        let rows: Vec<Vec<String>> = records
+            .iter()
+            .map(|record| {
+                vec![
+                    record.username.clone(),
+                    record.via.clone(),
+                    record.ip.clone().unwrap_or_else(|| "-".to_string()),
+                    record
+                        .created_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string(),
+                    record.method.clone(),
+                    if record.success { "成功" } else { "失败" }.to_string(),
+                ]
+            })
+            .collect();
+
+        CommandResult::ok(align(&rows))
+            .with_data(json!(records))
+            .with_records(&records)
+    }
+}
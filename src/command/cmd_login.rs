@@ -1,11 +1,12 @@
-use super::CommandHandler;
+use super::args::{Opt, Positional};
+use super::login_history;
+use super::message::{render, take_pending};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, ErrorKind, SideEffect};
 use crate::auth::{generate_token, AuthManager};
 use crate::captcha::CaptchaManager;
-use actix_web::{web, HttpResponse};
 use async_trait::async_trait;
 use log::{debug, error, info, warn};
 use serde_json::json;
-use sqlx::PgPool;
 
 pub struct LoginCommand;
 
@@ -15,160 +16,165 @@ impl LoginCommand {
     }
 }
 
+// 登录命令使用密码认证
+const METHOD: &str = "password";
+
+const SPEC: CommandSpec = CommandSpec::new("login", "用户登录")
+    .positionals(&[
+        Positional::new("username", "用户名").required(),
+        Positional::new("password", "密码").required().secret(),
+    ])
+    .options(&[Opt::value("--captcha", "code", "验证码")])
+    .examples(&[("login alice secret --captcha abcd", "使用验证码登录")])
+    .see_also(&["logout", "register"])
+    .foreground();
+
 #[async_trait]
 impl CommandHandler for LoginCommand {
-    fn name(&self) -> &'static str {
-        "login"
-    }
-
-    fn description(&self) -> &'static str {
-        "用户登录，用法：login <username> <password> [--captcha <code>]"
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
     }
 
-    async fn handle(
-        &self,
-        args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        cwd: &str,
-    ) -> HttpResponse {
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
         info!("开始处理登录命令");
 
-        if args.len() < 3 {
-            warn!("登录命令参数不足");
-            return HttpResponse::BadRequest().json(super::CommandResponse {
-                success: false,
-                message: "请提供用户名和密码".to_string(),
-                data: None,
-            });
-        }
-
-        let username = args[1];
-        let password = args[2];
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let username = matches.positional(0).unwrap_or_default();
+        let password = matches.positional(1).unwrap_or_default();
         debug!("尝试登录用户: {}", username);
 
         // 检查登录尝试次数
-        if let Err(e) = data.auth_manager.check_login_attempts(username) {
+        if let Err(e) = ctx.state.auth_manager.check_login_attempts(username) {
             warn!("登录尝试次数过多: {}", e);
-            return HttpResponse::TooManyRequests().json(super::CommandResponse {
-                success: false,
-                message: e,
-                data: None,
-            });
-        }
-
-        // 解析参数
-        let mut captcha_code = None;
-        let mut i = 3;
-        while i < args.len() {
-            match args[i] {
-                "--captcha" => {
-                    if i + 1 < args.len() {
-                        captcha_code = Some(args[i + 1]);
-                        i += 2;
-                    } else {
-                        warn!("缺少验证码");
-                        return HttpResponse::BadRequest().json(super::CommandResponse {
-                            success: false,
-                            message: "请提供验证码".to_string(),
-                            data: None,
-                        });
-                    }
-                }
-                _ => {
-                    warn!("未知参数: {}", args[i]);
-                    return HttpResponse::BadRequest().json(super::CommandResponse {
-                        success: false,
-                        message: format!("未知参数: {}", args[i]),
-                        data: None,
-                    });
-                }
-            }
+            login_history::record(&ctx.state.db, None, username, METHOD, ctx.client, false)
+                .await;
+            return CommandResult::error(ErrorKind::TooManyRequests, e);
         }
 
         // 验证验证码
-        if let Some(code) = captcha_code {
+        if let Some(code) = matches.value("--captcha") {
             debug!("验证验证码: {}", code);
-            if !CaptchaManager::verify_captcha(&data.captcha_manager, session_id, code) {
+            if !CaptchaManager::verify_captcha(&ctx.state.captcha_manager, ctx.session_id, code) {
                 warn!("验证码错误");
-                return HttpResponse::BadRequest().json(super::CommandResponse {
-                    success: false,
-                    message: "验证码错误".to_string(),
-                    data: None,
-                });
+                return CommandResult::invalid("验证码错误");
             }
         }
 
         // 查找用户
         match sqlx::query!(
-            "SELECT id, username, password_hash, role FROM users WHERE username = $1",
+            "SELECT id, username, password_hash, role, must_change_password, locked, token_version FROM users WHERE username = $1",
             username
         )
-        .fetch_optional(&data.db)
+        .fetch_optional(&ctx.state.db)
         .await
         {
             Ok(Some(user)) => {
                 if AuthManager::verify_password(password, &user.password_hash).unwrap_or(false) {
+                    // 密码正确后才提示锁定，避免泄露账号状态
+                    if user.locked {
+                        warn!("用户 {} 已被锁定", username);
+                        login_history::record(
+                            &ctx.state.db,
+                            Some(&user.id),
+                            username,
+                            METHOD,
+                            ctx.client,
+                            false,
+                        )
+                        .await;
+                        return CommandResult::forbidden("账号已被锁定，请联系管理员");
+                    }
                     info!("用户 {} 登录成功", username);
 
                     // 生成 JWT token
-                    let token = match generate_token(&user.id, &user.username, &user.role) {
+                    let token = match generate_token(
+                        &user.id,
+                        &user.username,
+                        &user.role,
+                        user.token_version,
+                    ) {
                         Ok(token) => token,
                         Err(e) => {
                             error!("生成token失败: {}", e);
-                            return HttpResponse::InternalServerError().json(
-                                super::CommandResponse {
-                                    success: false,
-                                    message: "服务器内部错误".to_string(),
-                                    data: None,
-                                },
-                            );
+                            return CommandResult::internal();
                         }
                     };
 
                     // 重置登录尝试次数
-                    data.auth_manager.reset_login_attempts(username);
+                    ctx.state.auth_manager.reset_login_attempts(username);
+                    login_history::record(
+                        &ctx.state.db,
+                        Some(&user.id),
+                        username,
+                        METHOD,
+                        ctx.client,
+                        true,
+                    )
+                    .await;
+
+                    let mut message = if user.must_change_password {
+                        "登录成功，管理员已重置你的密码，请用 passwd 修改密码".to_string()
+                    } else {
+                        "登录成功".to_string()
+                    };
+
+                    // 显示离线期间收到的消息
+                    let pending = match take_pending(&ctx.state.db, &user.id).await {
+                        Ok(pending) => pending,
+                        Err(e) => {
+                            error!("读取离线消息失败: {}", e);
+                            Vec::new()
+                        }
+                    };
+                    if !pending.is_empty() {
+                        message.push_str(&format!("\n你有 {} 条离线消息:", pending.len()));
+                        for line in pending.iter().filter_map(render) {
+                            message.push('\n');
+                            message.push_str(&line);
+                        }
+                    }
 
-                    HttpResponse::Ok().json(super::CommandResponse {
-                        success: true,
-                        message: "登录成功".to_string(),
-                        data: Some(json!({
+                    CommandResult::ok(message)
+                        .with_data(json!({
                             "token": token,
+                            "messages": pending,
                             "user": {
                                 "id": user.id,
                                 "username": user.username,
                                 "role": user.role
                             }
-                        })),
-                    })
+                        }))
+                        .with_effect(SideEffect::SetToken(token))
                 } else {
                     // 记录失败的登录尝试
-                    data.auth_manager.record_failed_attempt(username);
+                    ctx.state.auth_manager.record_failed_attempt(username);
+                    login_history::record(
+                        &ctx.state.db,
+                        Some(&user.id),
+                        username,
+                        METHOD,
+                        ctx.client,
+                        false,
+                    )
+                    .await;
                     warn!("用户 {} 密码错误", username);
-                    HttpResponse::Unauthorized().json(super::CommandResponse {
-                        success: false,
-                        message: "用户名或密码错误".to_string(),
-                        data: None,
-                    })
+                    CommandResult::unauthorized("用户名或密码错误")
                 }
             }
             Ok(None) => {
                 // 记录失败的登录尝试
-                data.auth_manager.record_failed_attempt(username);
+                ctx.state.auth_manager.record_failed_attempt(username);
+                login_history::record(&ctx.state.db, None, username, METHOD, ctx.client, false)
+                    .await;
                 warn!("用户 {} 不存在", username);
-                HttpResponse::Unauthorized().json(super::CommandResponse {
-                    success: false,
-                    message: "用户名或密码错误".to_string(),
-                    data: None,
-                })
+                CommandResult::unauthorized("用户名或密码错误")
             }
             Err(e) => {
                 error!("数据库查询错误: {}", e);
-                HttpResponse::InternalServerError().json(super::CommandResponse {
-                    success: false,
-                    message: "服务器内部错误".to_string(),
-                    data: None,
-                })
+                CommandResult::internal()
             }
         }
     }
@@ -1,8 +1,6 @@
-use super::CommandHandler;
-use crate::auth::AuthManager;
-use actix_web::{web, HttpResponse};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, SideEffect};
 use async_trait::async_trait;
-use log::{debug, error, info, warn};
+use log::{debug, info, warn};
 
 pub struct LogoutCommand;
 
@@ -12,43 +10,36 @@ impl LogoutCommand {
     }
 }
 
+const SPEC: CommandSpec = CommandSpec::new("logout", "用户登出")
+    .examples(&[("logout", "退出当前登录")])
+    .see_also(&["login"])
+    .foreground();
+
 #[async_trait]
 impl CommandHandler for LogoutCommand {
-    fn name(&self) -> &'static str {
-        "logout"
-    }
-
-    fn description(&self) -> &'static str {
-        "用户登出，用法：logout"
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
     }
 
-    async fn handle(
-        &self,
-        _args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        cwd: &str,
-    ) -> HttpResponse {
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
         info!("开始处理登出命令");
 
-        // 从 session_id 中获取 token
-        if session_id.is_empty() {
+        if let Err(result) = SPEC.parse(args) {
+            return result;
+        }
+
+        // 从 ctx.token 中获取 token
+        if ctx.token.is_empty() {
             warn!("未提供 token");
-            return HttpResponse::BadRequest().json(super::CommandResponse {
-                success: false,
-                message: "未提供 token".to_string(),
-                data: None,
-            });
+            return CommandResult::invalid("未提供 token");
         }
 
         debug!("将 token 加入黑名单");
-        data.auth_manager.blacklist_token(session_id);
+        ctx.state.auth_manager.blacklist_token(ctx.token);
+        ctx.state.session_manager.remove(ctx.token);
+        ctx.state.job_manager.remove_session(ctx.token);
 
         info!("用户登出成功");
-        HttpResponse::Ok().json(super::CommandResponse {
-            success: true,
-            message: "登出成功".to_string(),
-            data: None,
-        })
+        CommandResult::ok("登出成功").with_effect(SideEffect::ClearToken)
     }
 }
@@ -1,10 +1,15 @@
-use super::CommandHandler;
-use crate::auth::validate_token;
-use crate::vfs::model::{Role, User as VfsUser};
-use actix_web::{web, HttpResponse};
+use super::args::{display_width, Opt, Positional, ValueKind};
+use super::cmd_chmod::format_mode;
+use super::output::Record;
+use super::style::Style;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::vfs::model::VfsNode;
 use async_trait::async_trait;
-use log::{debug, error, info};
-use serde_json::json;
+use chrono::{DateTime, Local, Utc};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
 
 pub struct LsCommand;
 
@@ -14,117 +19,398 @@ impl LsCommand {
     }
 }
 
-#[async_trait]
-impl CommandHandler for LsCommand {
-    fn name(&self) -> &'static str {
-        "ls"
+// 递归列出时最多显示的条目数，避免对根目录执行 `ls -R` 时输出过多
+const MAX_ENTRIES: usize = 2000;
+
+const SPEC: CommandSpec = CommandSpec::new("ls", "显示目录内容")
+    .positionals(&[Positional::new("路径", "要显示的目录或文件，默认为当前目录")
+        .kind(ValueKind::Path)
+        .repeated()])
+    .options(&[
+        Opt::flag("--long", "长格式，显示权限、所有者、组、大小和修改时间").short("-l"),
+        Opt::flag("--all", "显示以 . 开头的隐藏文件").short("-a"),
+        Opt::flag("--human-readable", "以 K、M、G 为单位显示大小").short("-h"),
+        Opt::flag("--recursive", "递归列出子目录").short("-R"),
+        Opt::flag("--time", "按修改时间排序，最新的在前").short("-t"),
+        Opt::flag("--size", "按大小排序，最大的在前").short("-S"),
+        Opt::flag("--reverse", "逆序排列").short("-r"),
+    ])
+    .examples(&[
+        ("ls", "列出当前目录"),
+        ("ls -la", "以长格式列出当前目录，包括隐藏文件"),
+        ("ls -lhS /home", "按大小排序列出指定目录"),
+        ("ls -R Documents", "递归列出 Documents"),
+    ])
+    .see_also(&["cd", "pwd", "chmod"])
+    .output()
+    .color();
+
+// 排序方式
+#[derive(Clone, Copy)]
+enum SortKey {
+    Name,
+    Time,
+    Size,
+}
+
+// 一个目录（或作为参数给出的文件）的列出结果
+struct Listing {
+    path: String,
+    nodes: Vec<VfsNode>,
+}
+
+// 路径的最后一段，根目录为 `/`
+fn basename(path: &str) -> &str {
+    match path.trim_end_matches('/').rsplit('/').next() {
+        Some("") | None => "/",
+        Some(name) => name,
+    }
+}
+
+fn is_hidden(node: &VfsNode) -> bool {
+    basename(&node.name).starts_with('.')
+}
+
+fn sort_nodes(nodes: &mut [VfsNode], key: SortKey, reverse: bool) {
+    nodes.sort_by(|a, b| {
+        let by_name = basename(&a.name).cmp(basename(&b.name));
+        match key {
+            SortKey::Name => by_name,
+            SortKey::Time => b.updated_at.cmp(&a.updated_at).then(by_name),
+            SortKey::Size => b.size.cmp(&a.size).then(by_name),
+        }
+    });
+    if reverse {
+        nodes.reverse();
     }
+}
 
-    fn description(&self) -> &'static str {
-        "显示目录内容，用法：ls [路径]"
+/// 以 `-h` 的方式格式化大小，如 `812`、`1.5K`、`23M`
+fn human_size(size: i64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if size < 1024 {
+        return size.to_string();
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
     }
+    if value < 10.0 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
 
-    async fn handle(
-        &self,
-        args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        _cwd: &str,
-    ) -> HttpResponse {
+/// 类型和权限，如 `drwxr-xr-x`
+fn mode_string(node: &VfsNode) -> String {
+    let kind = if node.is_dir { 'd' } else { '-' };
+    format!("{}{}", kind, format_mode(node.permissions))
+}
+
+// 所有者的用户名，查不到时显示 ID（如系统目录的 `system`）
+fn owner_name<'a>(node: &'a VfsNode, names: &'a HashMap<String, String>) -> &'a str {
+    names
+        .get(&node.owner_id)
+        .map(|s| s.as_str())
+        .unwrap_or(&node.owner_id)
+}
+
+fn pad_left(text: &str, width: usize) -> String {
+    format!("{}{}", " ".repeat(width - display_width(text)), text)
+}
+
+fn pad_right(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width - display_width(text)))
+}
+
+// 文件名，目录和可执行文件加上样式
+fn display_name(node: &VfsNode, ctx: &CommandContext<'_>) -> String {
+    let name = basename(&node.name);
+    if node.is_dir {
+        ctx.paint(Style::Directory, name)
+    } else if node.permissions & 0o111 != 0 {
+        ctx.paint(Style::Executable, name)
+    } else {
+        name.to_string()
+    }
+}
+
+// 长格式的各行，每列按最宽的值对齐
+fn long_lines(
+    nodes: &[VfsNode],
+    names: &HashMap<String, String>,
+    human: bool,
+    ctx: &CommandContext<'_>,
+) -> Vec<String> {
+    let rows: Vec<[String; 6]> = nodes
+        .iter()
+        .map(|node| {
+            let owner = owner_name(node, names).to_string();
+            let size = if human {
+                human_size(node.size)
+            } else {
+                node.size.to_string()
+            };
+            let mtime = node
+                .updated_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string();
+            [
+                mode_string(node),
+                owner.clone(),
+                // 每个用户有一个同名的私有组
+                owner,
+                size,
+                mtime,
+                display_name(node, ctx),
+            ]
+        })
+        .collect();
+
+    // 最后一列的文件名不需要对齐
+    let mut widths = [0; 6];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(&row[..5]) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            format!(
+                "{} {} {} {} {} {}",
+                row[0],
+                pad_right(&row[1], widths[1]),
+                pad_right(&row[2], widths[2]),
+                pad_left(&row[3], widths[3]),
+                row[4],
+                row[5]
+            )
+        })
+        .collect()
+}
+
+/// `--output` 输出的一个条目
+#[derive(Serialize)]
+struct Entry<'a> {
+    mode: String,
+    owner: &'a str,
+    group: &'a str,
+    size: i64,
+    modified: DateTime<Utc>,
+    name: &'a str,
+    path: &'a str,
+}
+
+impl Record for Entry<'_> {
+    const COLUMNS: &'static [&'static str] =
+        &["mode", "owner", "group", "size", "modified", "name", "path"];
+}
+
+fn entry<'a>(node: &'a VfsNode, names: &'a HashMap<String, String>) -> Entry<'a> {
+    let owner = owner_name(node, names);
+    Entry {
+        mode: mode_string(node),
+        owner,
+        group: owner,
+        size: node.size,
+        modified: node.updated_at,
+        name: basename(&node.name),
+        path: &node.name,
+    }
+}
+
+fn node_json(node: &VfsNode, names: &HashMap<String, String>) -> Value {
+    let owner = owner_name(node, names);
+    json!({
+        "name": node.name,
+        "is_directory": node.is_dir,
+        "owner": node.owner_id,
+        "owner_name": owner,
+        "group": owner,
+        "permissions": format!("{:o}", node.permissions),
+        "mode": mode_string(node),
+        "size": node.size,
+        "created_at": node.created_at,
+        "updated_at": node.updated_at
+    })
+}
+
+#[async_trait]
+impl CommandHandler for LsCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
         info!("开始处理 ls 命令");
 
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let long = matches.flag("--long");
+        let all = matches.flag("--all");
+        let human = matches.flag("--human-readable");
+        let recursive = matches.flag("--recursive");
+        let reverse = matches.flag("--reverse");
+        let key = if matches.flag("--size") {
+            SortKey::Size
+        } else if matches.flag("--time") {
+            SortKey::Time
+        } else {
+            SortKey::Name
+        };
+
         // 获取路径参数，默认为当前目录
-        let path = if args.len() > 1 { args[1] } else { "." };
-        debug!("处理 ls 命令，路径: {}", path);
-
-        // 验证 token
-        let claims = match validate_token(session_id) {
-            Ok(claims) => claims,
-            Err(_) => {
-                error!("无效的 token");
-                return HttpResponse::Unauthorized().json(super::CommandResponse {
-                    success: false,
-                    message: "请先登录".to_string(),
-                    data: None,
-                });
-            }
+        let paths = match matches.rest(0) {
+            [] => vec!["."],
+            paths => paths.to_vec(),
+        };
+        debug!("处理 ls 命令，路径: {:?}", paths);
+
+        // 验证登录状态并查询用户身份
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
         };
 
-        // 检查 token 是否在黑名单中
-        if data.auth_manager.is_token_blacklisted(session_id) {
-            debug!("Token 已失效");
-            return HttpResponse::Unauthorized().json(super::CommandResponse {
-                success: false,
-                message: "Token 已失效".to_string(),
-                data: None,
-            });
+        // 作为参数给出的文件直接列出，目录依次展开，递归时子目录紧跟在父目录之后；
+        // 查看参数本身需要列出其父目录的权限
+        let mut files = Vec::new();
+        let mut pending = Vec::new();
+        for path in &paths {
+            match ctx.state.vfs_manager.stat_as(&user, path, ctx.cwd).await {
+                Ok(node) if node.is_dir => pending.push(node.name),
+                Ok(node) => files.push(node),
+                Err(e) => {
+                    warn!("获取节点信息失败: {}", e);
+                    return CommandResult::invalid(format!("ls: 无法访问 {}: {}", path, e));
+                }
+            }
         }
+        pending.reverse();
 
-        // 从数据库获取用户信息
-        let user = match sqlx::query!("SELECT username, role FROM users WHERE id = $1", claims.sub)
-            .fetch_optional(&data.db)
-            .await
-        {
-            Ok(Some(user)) => VfsUser {
-                id: claims.sub,
-                username: user.username,
-                roles: vec![match user.role.as_str() {
-                    "admin" => Role::Admin,
-                    "user" => Role::Author,
-                    _ => Role::Guest,
-                }],
-            },
-            Ok(None) => {
-                error!("用户不存在");
-                return HttpResponse::Unauthorized().json(super::CommandResponse {
-                    success: false,
-                    message: "用户不存在".to_string(),
-                    data: None,
-                });
+        let mut listings = Vec::new();
+        let mut errors = Vec::new();
+        let mut total = files.len();
+        let mut truncated = false;
+        while let Some(dir) = pending.pop() {
+            if total >= MAX_ENTRIES {
+                truncated = true;
+                break;
             }
+            ctx.progress(listings.len() as u64, None, dir.as_str());
+            let mut nodes = match ctx.state.vfs_manager.list_dir(&user, &dir, ctx.cwd).await {
+                Ok(nodes) => nodes,
+                Err(e) => {
+                    error!("获取目录内容失败: {:?}", e);
+                    errors.push(format!("ls: 无法打开目录 {}: {}", dir, e));
+                    continue;
+                }
+            };
+            nodes.retain(|node| all || !is_hidden(node));
+            sort_nodes(&mut nodes, key, reverse);
+            total += nodes.len();
+            if recursive {
+                pending.extend(
+                    nodes
+                        .iter()
+                        .rev()
+                        .filter(|node| node.is_dir)
+                        .map(|node| node.name.clone()),
+                );
+            }
+            listings.push(Listing { path: dir, nodes });
+        }
+        sort_nodes(&mut files, key, reverse);
+
+        // 批量查询所有者的用户名
+        let mut owners: Vec<String> = files
+            .iter()
+            .chain(listings.iter().flat_map(|l| &l.nodes))
+            .map(|node| node.owner_id.clone())
+            .collect();
+        owners.sort();
+        owners.dedup();
+        let names: HashMap<String, String> = match sqlx::query!(
+            "SELECT id, username FROM users WHERE id = ANY($1)",
+            &owners[..]
+        )
+        .fetch_all(&ctx.state.db)
+        .await
+        {
+            Ok(rows) => rows.into_iter().map(|r| (r.id, r.username)).collect(),
             Err(e) => {
-                error!("数据库查询错误: {}", e);
-                return HttpResponse::InternalServerError().json(super::CommandResponse {
-                    success: false,
-                    message: "服务器内部错误".to_string(),
-                    data: None,
-                });
+                error!("查询所有者失败: {}", e);
+                return CommandResult::internal();
             }
         };
 
-        // 设置当前目录为前端传递过来的当前工作目录
-        let cwd = format!("{}", _cwd);
-
-        // 获取目录内容
-        match data.vfs_manager.list_dir(&user, path, &cwd).await {
-            Ok(nodes) => {
-                info!("成功获取目录内容: {}", path);
-                HttpResponse::Ok().json(super::CommandResponse {
-                    success: true,
-                    message: "目录内容获取成功".to_string(),
-                    data: Some(json!({
-                        "path": path,
-                                "contents": nodes.iter().map(|node| {
-                                    json!({
-                                        "name": node.name,
-                                        "is_directory": node.is_dir,
-                                        "owner": node.owner_id,
-                                        "permissions": format!("{:o}", node.permissions),
-                                        "created_at": node.created_at,
-                                        "updated_at": node.updated_at
-                                    })
-                                }).collect::<Vec<_>>()
-                    })),
-                })
+        let format = |nodes: &[VfsNode]| -> Vec<String> {
+            if long {
+                long_lines(nodes, &names, human, ctx)
+            } else if nodes.is_empty() {
+                Vec::new()
+            } else {
+                let names: Vec<String> = nodes.iter().map(|n| display_name(n, ctx)).collect();
+                vec![names.join("  ")]
             }
-            Err(e) => {
-                error!("获取目录内容失败: {:?}", e);
-                HttpResponse::BadRequest().json(super::CommandResponse {
-                    success: false,
-                    message: format!("获取目录内容失败: {:?}", e),
-                    data: None,
-                })
+        };
+
+        // 多个目录或递归时在每个目录前显示 `路径:` 标题
+        let headers = recursive || listings.len() + files.len() > 1;
+        let mut sections = Vec::new();
+        if !files.is_empty() {
+            sections.push(format(&files).join("\n"));
+        }
+        for listing in &listings {
+            let mut lines = format(&listing.nodes);
+            if headers {
+                lines.insert(0, format!("{}:", listing.path));
             }
+            sections.push(lines.join("\n"));
+        }
+        let separator = if headers { "\n\n" } else { "\n" };
+        let stdout = sections
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(separator);
+
+        if truncated {
+            errors.push(format!("ls: 条目超过 {} 个，已停止列出", MAX_ENTRIES));
+        }
+
+        let contents: Vec<Value> = match (listings.first(), paths.len()) {
+            (Some(listing), 1) => listing.nodes.iter().map(|n| node_json(n, &names)).collect(),
+            _ => files.iter().map(|n| node_json(n, &names)).collect(),
+        };
+        let data = json!({
+            "path": paths[0],
+            "contents": contents,
+            "listings": listings.iter().map(|listing| json!({
+                "path": listing.path,
+                "contents": listing.nodes.iter().map(|n| node_json(n, &names)).collect::<Vec<_>>()
+            })).collect::<Vec<_>>()
+        });
+
+        info!("成功获取目录内容: {:?}", paths);
+        let entries: Vec<Entry> = files
+            .iter()
+            .chain(listings.iter().flat_map(|l| &l.nodes))
+            .map(|node| entry(node, &names))
+            .collect();
+        let mut result = CommandResult::ok(stdout)
+            .with_data(data)
+            .with_records(&entries);
+        if !errors.is_empty() {
+            result.stderr = errors.join("\n");
+            result.exit_code = super::history::EXIT_FAILURE;
         }
+        result
     }
 }
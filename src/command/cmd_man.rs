@@ -0,0 +1,60 @@
+use super::args::{Opt, Positional, ValueKind};
+use super::man;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{debug, info};
+use serde_json::json;
+
+pub struct ManCommand;
+
+impl ManCommand {
+    pub fn new() -> Self {
+        ManCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("man", "显示命令手册")
+    .positionals(&[
+        Positional::new("页面", "命令名或 /usr/share/man 下的手册页名").kind(ValueKind::Command),
+    ])
+    .options(&[Opt::value("--apropos", "关键字", "按关键字搜索手册页").short("-k")])
+    .examples(&[
+        ("man mkdir", "显示 mkdir 命令的手册"),
+        ("man -k 目录", "列出名称或说明中包含“目录”的手册页"),
+    ])
+    .see_also(&["help"])
+    .output();
+
+#[async_trait]
+impl CommandHandler for ManCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 man 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+
+        if let Some(keyword) = matches.value("--apropos") {
+            debug!("搜索手册页: {}", keyword);
+            let results = man::search(ctx.state, keyword).await;
+            if results.is_empty() {
+                return CommandResult::invalid(format!("man: 没有找到与 {} 相关的手册页", keyword));
+            }
+            return CommandResult::ok(results.join("\n")).with_data(json!(results));
+        }
+
+        let name = match matches.positional(0) {
+            Some(name) => name,
+            None => return SPEC.usage_error("请指定要查看的页面"),
+        };
+        match man::page(ctx.state, name).await {
+            Some(page) => CommandResult::ok(page),
+            None => CommandResult::invalid(format!("man: 没有 {} 的手册页", name)),
+        }
+    }
+}
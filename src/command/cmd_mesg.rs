@@ -0,0 +1,81 @@
+use super::args::{Positional, ValueKind};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{error, info};
+use serde_json::json;
+
+pub struct MesgCommand;
+
+impl MesgCommand {
+    pub fn new() -> Self {
+        MesgCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("mesg", "允许或拒绝其他用户用 write 发来的消息")
+    .positionals(
+        &[Positional::new("y|n", "y 允许，n 拒绝；省略时显示当前状态")
+            .kind(ValueKind::Choice(&["y", "n"]))],
+    )
+    .examples(&[
+        ("mesg", "查看当前是否接收消息"),
+        ("mesg n", "不再接收 write 发来的消息，管理员的公告除外"),
+    ])
+    .see_also(&["write", "wall"])
+    .foreground()
+    .output();
+
+#[async_trait]
+impl CommandHandler for MesgCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 mesg 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let accept = match matches.positional(0) {
+            Some(value) => {
+                let accept = value == "y";
+                if let Err(e) = sqlx::query!(
+                    "UPDATE users SET accept_messages = $1 WHERE id = $2",
+                    accept,
+                    user.id
+                )
+                .execute(&ctx.state.db)
+                .await
+                {
+                    error!("更新消息接收设置失败: {}", e);
+                    return CommandResult::internal();
+                }
+                info!("用户 {} 设置 mesg {}", user.username, value);
+                accept
+            }
+            None => match sqlx::query_scalar!(
+                "SELECT accept_messages FROM users WHERE id = $1",
+                user.id
+            )
+            .fetch_one(&ctx.state.db)
+            .await
+            {
+                Ok(accept) => accept,
+                Err(e) => {
+                    error!("数据库查询错误: {}", e);
+                    return CommandResult::internal();
+                }
+            },
+        };
+
+        CommandResult::ok(if accept { "is y" } else { "is n" })
+            .with_data(json!({ "accept_messages": accept }))
+    }
+}
@@ -0,0 +1,102 @@
+use super::args::{Positional, ValueKind};
+use super::output::align;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::vfs::front_matter::{self, FrontMatter};
+use async_trait::async_trait;
+use chrono::Local;
+use log::{info, warn};
+use serde_json::json;
+
+pub struct MetaCommand;
+
+impl MetaCommand {
+    pub fn new() -> Self {
+        MetaCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("meta", "显示文章 front matter 中的元数据")
+    .positionals(&[Positional::new("文章", "要查看的文章")
+        .required()
+        .kind(ValueKind::Path)])
+    .examples(&[
+        (
+            "meta Documents/drafts/hello.md",
+            "显示标题、日期、标签等字段",
+        ),
+        ("meta hello.md --output json", "以 JSON 输出解析结果"),
+    ])
+    .see_also(&["publish", "cat"])
+    .output();
+
+// 按固定顺序列出字段，未设置的字段显示为 `-`
+fn render(meta: &FrontMatter) -> String {
+    let text = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    let rows = vec![
+        ("format", format!("{:?}", meta.format).to_lowercase()),
+        ("title", text(&meta.title)),
+        (
+            "date",
+            meta.date.map_or("-".to_string(), |date| {
+                date.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            }),
+        ),
+        (
+            "tags",
+            if meta.tags.is_empty() {
+                "-".to_string()
+            } else {
+                meta.tags.join(", ")
+            },
+        ),
+        ("summary", text(&meta.summary)),
+        ("cover", text(&meta.cover)),
+        ("draft", meta.draft.to_string()),
+        ("slug", text(&meta.slug)),
+        ("language", text(&meta.language)),
+    ];
+    let rows: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|(key, value)| vec![format!("{}:", key), value])
+        .collect();
+    align(&rows)
+}
+
+#[async_trait]
+impl CommandHandler for MetaCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 meta 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let file = matches.positional(0).unwrap_or_default();
+        let content = match ctx.state.vfs_manager.read_file(&user, file, ctx.cwd).await {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("读取文件失败: {}", e);
+                return CommandResult::invalid(format!("meta: {}: {}", file, e));
+            }
+        };
+
+        match front_matter::parse(&content) {
+            Ok(Some(meta)) => CommandResult::ok(render(&meta)).with_data(json!(meta)),
+            Ok(None) => {
+                CommandResult::ok(format!("{}: 没有 front matter", file)).with_data(json!(null))
+            }
+            Err(e) => CommandResult::invalid(format!("meta: {}: {}", file, e)),
+        }
+    }
+}
@@ -1,7 +1,6 @@
-use super::CommandHandler;
-use crate::auth::validate_token;
-use crate::vfs::model::{Role, User as VfsUser, VfsError};
-use actix_web::{web, HttpResponse};
+use super::args::{Opt, Positional, ValueKind};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::vfs::model::VfsError;
 use async_trait::async_trait;
 use log::{debug, error, info};
 use serde_json::json;
@@ -14,108 +13,44 @@ impl MkdirCommand {
     }
 }
 
+const SPEC: CommandSpec = CommandSpec::new("mkdir", "创建目录")
+    .positionals(&[Positional::new("目录", "要创建的目录")
+        .kind(ValueKind::Path)
+        .required()])
+    .options(&[Opt::flag("--parents", "同时创建不存在的上级目录，目录已存在时不报错").short("-p")])
+    .examples(&[
+        ("mkdir drafts", "在当前目录创建 drafts"),
+        ("mkdir -p Documents/2024/notes", "同时创建不存在的上级目录"),
+    ])
+    .see_also(&["ls", "cd"])
+    .output();
+
 #[async_trait]
 impl CommandHandler for MkdirCommand {
-    fn name(&self) -> &'static str {
-        "mkdir"
-    }
-
-    fn description(&self) -> &'static str {
-        "创建目录，用法：mkdir [-p] <目录名>"
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
     }
 
-    async fn handle(
-        &self,
-        args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        _cwd: &str,
-    ) -> HttpResponse {
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
         info!("开始处理 mkdir 命令");
 
-        // 参数检查
-        if args.len() < 2 {
-            error!("mkdir 命令缺少参数");
-            return HttpResponse::BadRequest().json(super::CommandResponse {
-                success: false,
-                message: "用法：mkdir [-p] <目录名>".to_string(),
-                data: None,
-            });
-        }
-
-        // 解析 -p 选项
-        let mut recursive = false;
-        let mut dir_name = args[1];
-        if args[1] == "-p" {
-            if args.len() < 3 {
-                error!("mkdir -p 命令缺少目录名");
-                return HttpResponse::BadRequest().json(super::CommandResponse {
-                    success: false,
-                    message: "用法：mkdir -p <目录名>".to_string(),
-                    data: None,
-                });
-            }
-            recursive = true;
-            dir_name = args[2];
-        }
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let recursive = matches.flag("--parents");
+        let dir_name = matches.positional(0).unwrap_or_default();
 
         debug!("创建目录: {}, 递归: {}", dir_name, recursive);
 
-        // 验证 JWT
-        let claims = match validate_token(session_id) {
-            Ok(c) => c,
-            Err(_) => {
-                error!("无效的 token");
-                return HttpResponse::Unauthorized().json(super::CommandResponse {
-                    success: false,
-                    message: "请先登录".to_string(),
-                    data: None,
-                });
-            }
-        };
-        if data.auth_manager.is_token_blacklisted(session_id) {
-            debug!("Token 已失效");
-            return HttpResponse::Unauthorized().json(super::CommandResponse {
-                success: false,
-                message: "Token 已失效".to_string(),
-                data: None,
-            });
-        }
-
-        // 查询用户身份
-        let user = match sqlx::query!("SELECT username, role FROM users WHERE id = $1", claims.sub)
-            .fetch_optional(&data.db)
-            .await
-        {
-            Ok(Some(rec)) => VfsUser {
-                id: claims.sub.clone(),
-                username: rec.username,
-                roles: vec![match rec.role.as_str() {
-                    "admin" => Role::Admin,
-                    "user" => Role::Author,
-                    _ => Role::Guest,
-                }],
-            },
-            Ok(None) => {
-                error!("用户不存在");
-                return HttpResponse::Unauthorized().json(super::CommandResponse {
-                    success: false,
-                    message: "用户不存在".to_string(),
-                    data: None,
-                });
-            }
-            Err(e) => {
-                error!("数据库查询错误: {}", e);
-                return HttpResponse::InternalServerError().json(super::CommandResponse {
-                    success: false,
-                    message: "服务器内部错误".to_string(),
-                    data: None,
-                });
-            }
+        // 验证登录状态并查询用户身份
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
         };
 
         // 当前目录
-        let cwd = format!("{}", _cwd);
+        let cwd = ctx.cwd.to_string();
 
         if recursive {
             // 拆分路径组件
@@ -145,32 +80,34 @@ impl CommandHandler for MkdirCommand {
                 full.push_str(part);
 
                 // 先检查目录是否存在（读权限+存在性）
-                match data.vfs_manager.list_dir(&user, &full, &parent_cwd).await {
+                match ctx
+                    .state
+                    .vfs_manager
+                    .list_dir(&user, &full, &parent_cwd)
+                    .await
+                {
                     Ok(_) => {
                         debug!("目录已存在，跳过: {}", full);
                     }
                     Err(VfsError::NodeNotFound(_)) => {
                         // 不存在才去创建
-                        match data.vfs_manager.create_dir(&user, &full, &parent_cwd).await {
+                        match ctx
+                            .state
+                            .vfs_manager
+                            .create_dir(&user, &full, &parent_cwd)
+                            .await
+                        {
                             Ok(_) => debug!("成功创建目录: {}", full),
                             Err(e) => {
                                 error!("创建目录失败: {:?}", e);
-                                return HttpResponse::BadRequest().json(super::CommandResponse {
-                                    success: false,
-                                    message: format!("创建目录失败: {:?}", e),
-                                    data: None,
-                                });
+                                return CommandResult::invalid(format!("创建目录失败: {:?}", e));
                             }
                         }
                     }
                     Err(e) => {
                         // 其他错误（权限/路径错误等）
                         error!("检查目录状态失败: {:?}", e);
-                        return HttpResponse::InternalServerError().json(super::CommandResponse {
-                            success: false,
-                            message: "服务器内部错误".to_string(),
-                            data: None,
-                        });
+                        return CommandResult::internal();
                     }
                 }
 
@@ -178,36 +115,29 @@ impl CommandHandler for MkdirCommand {
                 parent_cwd = full.clone();
             }
 
-            HttpResponse::Ok().json(super::CommandResponse {
-                success: true,
-                message: format!("目录 {} 创建成功", dir_name),
-                data: None,
-            })
+            CommandResult::ok(format!("目录 {} 创建成功", dir_name))
         } else {
             // 非递归创建
-            match data.vfs_manager.create_dir(&user, dir_name, &cwd).await {
+            match ctx
+                .state
+                .vfs_manager
+                .create_dir(&user, dir_name, &cwd)
+                .await
+            {
                 Ok(node) => {
                     info!("成功创建目录: {}", dir_name);
-                    HttpResponse::Ok().json(super::CommandResponse {
-                        success: true,
-                        message: format!("目录 {} 创建成功", dir_name),
-                        data: Some(json!({
-                            "name": node.name,
-                            "is_directory": node.is_dir,
-                            "owner": node.owner_id,
-                            "permissions": format!("{:o}", node.permissions),
-                            "created_at": node.created_at,
-                            "updated_at": node.updated_at
-                        })),
-                    })
+                    CommandResult::ok(format!("目录 {} 创建成功", dir_name)).with_data(json!({
+                        "name": node.name,
+                        "is_directory": node.is_dir,
+                        "owner": node.owner_id,
+                        "permissions": format!("{:o}", node.permissions),
+                        "created_at": node.created_at,
+                        "updated_at": node.updated_at
+                    }))
                 }
                 Err(e) => {
                     error!("创建目录失败: {:?}", e);
-                    HttpResponse::BadRequest().json(super::CommandResponse {
-                        success: false,
-                        message: format!("创建目录失败: {:?}", e),
-                        data: None,
-                    })
+                    CommandResult::invalid(format!("创建目录失败: {:?}", e))
                 }
             }
         }
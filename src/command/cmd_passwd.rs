@@ -0,0 +1,171 @@
+use super::account::{end_sessions, validate_password};
+use super::args::{Opt, Positional};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, ErrorKind, SideEffect};
+use crate::auth::{generate_token, validate_token, AuthManager};
+use crate::vfs::model::Role;
+use async_trait::async_trait;
+use log::{error, info, warn};
+use serde_json::json;
+
+pub struct PasswdCommand;
+
+impl PasswdCommand {
+    pub fn new() -> Self {
+        PasswdCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("passwd", "修改密码")
+    .positionals(&[Positional::new(
+        "用户",
+        "要重置密码的用户，只有管理员可以指定，默认为当前用户",
+    )])
+    .options(&[
+        Opt::value(
+            "--current-password",
+            "密码",
+            "当前密码，修改自己的密码时必须提供",
+        )
+        .secret(),
+        Opt::value("--new-password", "密码", "新密码，6-32 个字符")
+            .required()
+            .secret(),
+    ])
+    .examples(&[
+        (
+            "passwd --current-password secret --new-password s3cret!",
+            "修改自己的密码，其他设备上的登录随之失效",
+        ),
+        (
+            "sudo passwd bob --new-password temp123",
+            "管理员重置 bob 的密码，bob 下次登录后必须修改密码",
+        ),
+    ])
+    .see_also(&["sudo", "login"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for PasswdCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 passwd 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+        let target = matches.positional(0).unwrap_or(&user.username);
+        let reset = target != user.username;
+        if reset && !user.roles.contains(&Role::Admin) {
+            warn!("用户 {} 尝试修改 {} 的密码", user.username, target);
+            return CommandResult::forbidden(
+                "passwd: 只有管理员可以重置其他用户的密码，请使用 sudo",
+            );
+        }
+
+        let new_password = matches.value("--new-password").unwrap_or_default();
+        if let Err(e) = validate_password(new_password) {
+            return CommandResult::invalid(format!("passwd: {}", e));
+        }
+
+        let account = match sqlx::query!(
+            "SELECT id, username, password_hash, role FROM users WHERE username = $1",
+            target
+        )
+        .fetch_optional(&ctx.state.db)
+        .await
+        {
+            Ok(Some(account)) => account,
+            Ok(None) => return CommandResult::invalid(format!("passwd: 用户 {} 不存在", target)),
+            Err(e) => {
+                error!("数据库查询错误: {}", e);
+                return CommandResult::internal();
+            }
+        };
+
+        // 修改自己的密码需要验证当前密码，错误次数与登录分开计算
+        if !reset {
+            let Some(current) = matches.value("--current-password") else {
+                return CommandResult::invalid("passwd: 需要提供当前密码 --current-password");
+            };
+            let attempt_key = format!("passwd:{}", user.username);
+            if let Err(e) = ctx.state.auth_manager.check_login_attempts(&attempt_key) {
+                return CommandResult::error(ErrorKind::TooManyRequests, format!("passwd: {}", e));
+            }
+            match AuthManager::verify_password(current, &account.password_hash) {
+                Ok(true) => ctx.state.auth_manager.reset_login_attempts(&attempt_key),
+                Ok(false) => {
+                    warn!("用户 {} 修改密码时当前密码错误", user.username);
+                    ctx.state.auth_manager.record_failed_attempt(&attempt_key);
+                    return CommandResult::unauthorized("passwd: 当前密码错误");
+                }
+                Err(e) => {
+                    error!("验证密码失败: {}", e);
+                    return CommandResult::internal();
+                }
+            }
+            if current == new_password {
+                return CommandResult::invalid("passwd: 新密码不能与当前密码相同");
+            }
+        }
+
+        let password_hash = match AuthManager::hash_password(new_password) {
+            Ok(hash) => hash,
+            Err(e) => {
+                error!("密码加密失败: {}", e);
+                return CommandResult::internal();
+            }
+        };
+        // 管理员重置的密码只是临时密码，用户登录后必须修改；递增 token 版本使此前的登录全部失效
+        let token_version = match sqlx::query_scalar!(
+            "UPDATE users SET password_hash = $1, must_change_password = $2, token_version = token_version + 1 WHERE id = $3 RETURNING token_version",
+            password_hash,
+            reset,
+            account.id
+        )
+        .fetch_one(&ctx.state.db)
+        .await
+        {
+            Ok(version) => version,
+            Err(e) => {
+                error!("更新密码失败: {}", e);
+                return CommandResult::internal();
+            }
+        };
+
+        if reset {
+            end_sessions(ctx, &account.id, None);
+            info!("管理员 {} 重置了用户 {} 的密码", user.username, target);
+            return CommandResult::ok(format!(
+                "已重置 {} 的密码，该用户下次登录后必须修改密码",
+                target
+            ));
+        }
+
+        // 修改自己的密码时为当前登录签发新版本的 token，会话随之转移，当前会话的后台任务终止
+        let token =
+            match generate_token(&account.id, &account.username, &account.role, token_version) {
+                Ok(token) => token,
+                Err(e) => {
+                    error!("生成token失败: {}", e);
+                    return CommandResult::internal();
+                }
+            };
+        end_sessions(ctx, &account.id, Some(ctx.token));
+        ctx.state.job_manager.remove_session(ctx.token);
+        if let Ok(claims) = validate_token(&token) {
+            ctx.state.session_manager.rename(ctx.token, &token, &claims);
+        }
+        info!("用户 {} 修改了密码", user.username);
+        CommandResult::ok("密码已修改，其他设备上的登录已失效")
+            .with_data(json!({ "token": token }))
+            .with_effect(SideEffect::SetToken(token))
+    }
+}
@@ -0,0 +1,75 @@
+use super::cmd_cd::resolve_dir;
+use super::cmd_dirs::format_dirs;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, SideEffect};
+use async_trait::async_trait;
+use log::{info, warn};
+use serde_json::json;
+
+pub struct PopdCommand;
+
+impl PopdCommand {
+    pub fn new() -> Self {
+        PopdCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("popd", "弹出目录栈顶并切换到该目录")
+    .examples(&[("popd", "回到最近一次 pushd 之前的目录")])
+    .see_also(&["pushd", "dirs"])
+    .foreground()
+    .output();
+
+#[async_trait]
+impl CommandHandler for PopdCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 popd 命令");
+
+        if let Err(result) = SPEC.parse(args) {
+            return result;
+        }
+
+        let session = match ctx.state.session_manager.get(ctx.token) {
+            Some(session) => session,
+            None => {
+                warn!("会话不存在");
+                return CommandResult::unauthorized("请先登录");
+            }
+        };
+
+        // 栈顶目录可能已被删除或失去权限，切换前重新校验
+        let top = match session.dir_stack.last() {
+            Some(top) => top.clone(),
+            None => {
+                return CommandResult::invalid("popd: 目录栈为空");
+            }
+        };
+        if let Err(response) = resolve_dir(ctx, &top).await {
+            return response;
+        }
+
+        match ctx.state.session_manager.pop_dir(ctx.token) {
+            Ok(path) => {
+                let stack = ctx
+                    .state
+                    .session_manager
+                    .get(ctx.token)
+                    .map(|s| s.dir_stack)
+                    .unwrap_or_default();
+                CommandResult::ok(format_dirs(&path, &stack))
+                    .with_data(json!({
+                        "path": path,
+                        "stack": stack.iter().rev().collect::<Vec<_>>()
+                    }))
+                    .with_effect(SideEffect::ChangeDir(path))
+            }
+            Err(e) => {
+                warn!("popd 失败: {}", e);
+                CommandResult::invalid(format!("popd: {}", e))
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use super::args::{Opt, Positional, ValueKind};
+use super::post::{self, DRAFTS_DIR, PUBLISHED_DIR};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::vfs::front_matter;
+use crate::vfs::model::{Role, VfsError};
+use crate::vfs::path_normalizer::PathNormalizer;
+use async_trait::async_trait;
+use chrono::Utc;
+use log::{error, info, warn};
+use serde_json::json;
+
+pub struct PublishCommand;
+
+impl PublishCommand {
+    pub fn new() -> Self {
+        PublishCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("publish", "发布草稿")
+    .positionals(&[Positional::new("草稿", "Documents/drafts 下的文章")
+        .required()
+        .kind(ValueKind::Path)])
+    .options(&[Opt::value(
+        "--slug",
+        "slug",
+        "指定文章链接中的标识，默认使用 front matter 中的 slug 或由文件名生成",
+    )])
+    .examples(&[
+        (
+            "publish Documents/drafts/hello.md",
+            "移动到 Documents/published/hello.md 并发布",
+        ),
+        (
+            "publish hello.md --slug hello-world",
+            "在草稿目录中发布并指定 slug",
+        ),
+    ])
+    .see_also(&["unpublish"])
+    .foreground()
+    .output();
+
+#[async_trait]
+impl CommandHandler for PublishCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 publish 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let draft = matches.positional(0).unwrap_or_default();
+        let path = match PathNormalizer::normalize(draft, ctx.cwd) {
+            Ok(path) => path,
+            Err(e) => return CommandResult::invalid(format!("publish: {}", e)),
+        };
+        let (home, relative) = match post::split(&path, DRAFTS_DIR) {
+            Some(location) => location,
+            None => {
+                return CommandResult::invalid(format!(
+                    "publish: {} 不在 ~/{} 目录下",
+                    draft, DRAFTS_DIR
+                ))
+            }
+        };
+
+        let node = match ctx.state.vfs_manager.stat(&path, "/").await {
+            Ok(node) if !node.is_dir => node,
+            Ok(_) => return CommandResult::invalid(format!("publish: {} 是一个目录", draft)),
+            Err(VfsError::NodeNotFound(_)) => {
+                return CommandResult::invalid(format!("publish: {}: 没有那个文件", draft))
+            }
+            Err(e) => {
+                error!("获取文件信息失败: {}", e);
+                return CommandResult::internal();
+            }
+        };
+        if node.owner_id != user.id && !user.roles.contains(&Role::Admin) {
+            warn!("用户 {} 尝试发布他人的文章 {}", user.username, path);
+            return CommandResult::forbidden(format!("publish: 只能发布自己的文章: {}", draft));
+        }
+
+        // front matter 提供标题和 slug，标记为草稿的文章不能发布
+        let meta = match ctx.state.vfs_manager.read_file(&user, &path, "/").await {
+            Ok(content) => match front_matter::parse(&content) {
+                Ok(meta) => meta,
+                Err(e) => return CommandResult::invalid(format!("publish: {}: {}", draft, e)),
+            },
+            Err(e) => {
+                warn!("读取草稿失败: {}", e);
+                return CommandResult::invalid(format!("publish: {}", e));
+            }
+        };
+        if meta.as_ref().is_some_and(|meta| meta.draft) {
+            return CommandResult::invalid(format!(
+                "publish: {} 的 front matter 中 draft 为 true",
+                draft
+            ));
+        }
+
+        // 优先使用指定的 slug，再次发布撤回过的文章时沿用原来的 slug
+        let db = &ctx.state.db;
+        let existing = match post::find_by_path(db, &path).await {
+            Ok(existing) => existing,
+            Err(e) => {
+                error!("查询文章失败: {}", e);
+                return CommandResult::internal();
+            }
+        };
+        let id = existing.as_ref().map(|post| post.id);
+        let requested = matches
+            .value("--slug")
+            .or_else(|| meta.as_ref().and_then(|meta| meta.slug.as_deref()));
+        let slug = match (requested, existing) {
+            (Some(slug), _) => {
+                if let Err(e) = front_matter::validate_slug(slug) {
+                    return CommandResult::invalid(format!("publish: {}", e));
+                }
+                match post::slug_taken(db, slug, id).await {
+                    Ok(false) => slug.to_string(),
+                    Ok(true) => {
+                        return CommandResult::invalid(format!(
+                            "publish: slug {} 已被其他文章使用",
+                            slug
+                        ))
+                    }
+                    Err(e) => {
+                        error!("查询 slug 失败: {}", e);
+                        return CommandResult::internal();
+                    }
+                }
+            }
+            (None, Some(existing)) => existing.slug,
+            (None, None) => {
+                match post::unique_slug(db, &post::slugify(&post::title_from(&path))).await {
+                    Ok(slug) => slug,
+                    Err(e) => {
+                        error!("生成 slug 失败: {}", e);
+                        return CommandResult::internal();
+                    }
+                }
+            }
+        };
+
+        // 先移动文件，记录失败时移回草稿目录
+        let target = format!("{}/{}/{}", home, PUBLISHED_DIR, relative);
+        let target_dir = PathNormalizer::parent(&target).unwrap_or_default();
+        if let Err(e) = post::ensure_dir(ctx, &node.owner_id, &target_dir).await {
+            warn!("创建发布目录失败: {}", e);
+            return CommandResult::invalid(format!("publish: {}", e));
+        }
+        match ctx
+            .state
+            .vfs_manager
+            .rename(&user, &path, &target, "/")
+            .await
+        {
+            Ok(()) => {}
+            Err(VfsError::NodeExists(_)) => {
+                return CommandResult::invalid(format!("publish: {} 已存在", target))
+            }
+            Err(e) => {
+                warn!("移动文章失败: {}", e);
+                return CommandResult::invalid(format!("publish: {}", e));
+            }
+        }
+
+        let title = meta
+            .and_then(|meta| meta.title)
+            .unwrap_or_else(|| post::title_from(&target));
+        let published =
+            post::save_published(db, id, &node.owner_id, &slug, &title, &target, Utc::now()).await;
+        match published {
+            Ok(post) => {
+                info!("用户 {} 发布了文章 {} ({})", user.username, target, slug);
+                CommandResult::ok(format!("已发布 {}，slug: {}", target, post.slug))
+                    .with_data(json!(post))
+            }
+            Err(e) => {
+                error!("记录发布失败: {}", e);
+                if let Err(e) = ctx
+                    .state
+                    .vfs_manager
+                    .rename(&user, &target, &path, "/")
+                    .await
+                {
+                    error!("恢复草稿失败: {}", e);
+                }
+                CommandResult::internal()
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use super::args::{Positional, ValueKind};
+use super::cmd_cd::resolve_dir;
+use super::cmd_dirs::format_dirs;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, SideEffect};
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use serde_json::json;
+
+pub struct PushdCommand;
+
+impl PushdCommand {
+    pub fn new() -> Self {
+        PushdCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("pushd", "将当前目录压入目录栈并切换目录")
+    .positionals(&[
+        Positional::new("目录", "目标目录，省略时交换当前目录与栈顶目录").kind(ValueKind::Path),
+    ])
+    .examples(&[
+        (
+            "pushd /home/alice/Documents",
+            "保存当前目录并进入 Documents",
+        ),
+        ("pushd", "交换当前目录与栈顶目录"),
+    ])
+    .see_also(&["popd", "dirs", "cd"])
+    .foreground()
+    .output();
+
+#[async_trait]
+impl CommandHandler for PushdCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 pushd 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+
+        let result = match matches.positional(0) {
+            Some(target) => {
+                let path = match resolve_dir(ctx, target).await {
+                    Ok(path) => path,
+                    Err(response) => return response,
+                };
+                debug!("压入目录栈并切换到: {}", path);
+                ctx.state
+                    .session_manager
+                    .push_dir(ctx.token, &path)
+                    .map(|stack| (path, stack))
+            }
+            // 无参数时交换当前目录与栈顶目录
+            None => {
+                let top = match ctx.state.session_manager.get(ctx.token) {
+                    Some(session) => session.dir_stack.last().cloned(),
+                    None => None,
+                };
+                let top = match top {
+                    Some(top) => top,
+                    None => {
+                        return CommandResult::invalid("pushd: 目录栈为空");
+                    }
+                };
+                if let Err(response) = resolve_dir(ctx, &top).await {
+                    return response;
+                }
+                ctx.state
+                    .session_manager
+                    .swap_dir(ctx.token)
+                    .map(|stack| (top, stack))
+            }
+        };
+
+        match result {
+            Ok((path, stack)) => CommandResult::ok(format_dirs(&path, &stack))
+                .with_data(json!({
+                    "path": path,
+                    "stack": stack.iter().rev().collect::<Vec<_>>()
+                }))
+                .with_effect(SideEffect::ChangeDir(path)),
+            Err(e) => {
+                warn!("pushd 失败: {}", e);
+                CommandResult::invalid(format!("pushd: {}", e))
+            }
+        }
+    }
+}
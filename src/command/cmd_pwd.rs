@@ -1,6 +1,4 @@
-use super::CommandHandler;
-use crate::auth::validate_token;
-use actix_web::{web, HttpResponse};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
 use async_trait::async_trait;
 use log::{debug, info};
 
@@ -12,53 +10,27 @@ impl PwdCommand {
     }
 }
 
+const SPEC: CommandSpec = CommandSpec::new("pwd", "显示当前工作目录")
+    .examples(&[("pwd", "显示当前工作目录")])
+    .see_also(&["cd"])
+    .output();
+
 #[async_trait]
 impl CommandHandler for PwdCommand {
-    fn name(&self) -> &'static str {
-        "pwd"
-    }
-
-    fn description(&self) -> &'static str {
-        "显示当前工作目录，用法：pwd"
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
     }
 
-    async fn handle(
-        &self,
-        _args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        cwd: &str,
-    ) -> HttpResponse {
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
         info!("开始处理 pwd 命令");
 
-        // 获取当前用户名（如果已登录）
-        let path = match validate_token(session_id) {
-            Ok(claims) => {
-                // 从数据库获取用户名
-                match sqlx::query!("SELECT username FROM users WHERE id = $1", claims.sub)
-                    .fetch_optional(&data.db)
-                    .await
-                {
-                    Ok(Some(user)) => {
-                        debug!("用户已登录: {}", user.username);
-                        format!("/home/{}/", user.username)
-                    }
-                    _ => {
-                        debug!("用户不存在");
-                        "/home/guest/".to_string()
-                    }
-                }
-            }
-            Err(_) => {
-                debug!("用户未登录，使用访客目录");
-                "/home/guest/".to_string()
-            }
-        };
+        if let Err(result) = SPEC.parse(args) {
+            return result;
+        }
+
+        // 工作目录由服务端会话维护，访客固定为访客目录
+        debug!("当前工作目录: {}", ctx.cwd);
 
-        HttpResponse::Ok().json(super::CommandResponse {
-            success: true,
-            message: path,
-            data: None,
-        })
+        CommandResult::ok(ctx.cwd).with_data(serde_json::json!({ "path": ctx.cwd }))
     }
 }
@@ -0,0 +1,88 @@
+use super::args::{Positional, ValueKind};
+use super::{script, CommandContext, CommandHandler, CommandResult, CommandSpec, ErrorKind};
+use async_trait::async_trait;
+use log::info;
+use serde_json::json;
+
+pub struct ShCommand;
+
+impl ShCommand {
+    pub fn new() -> Self {
+        ShCommand
+    }
+}
+
+/// 执行脚本，`args[0]` 为脚本路径，其余为位置参数
+///
+/// 脚本中的 `cd` 只改变脚本自己的工作目录，不影响会话。
+pub(super) async fn execute(
+    args: &[&str],
+    ctx: &CommandContext<'_>,
+    require_exec: bool,
+) -> CommandResult {
+    // 验证登录状态并查询用户身份
+    let user = match ctx.user().await {
+        Ok(user) => user,
+        Err(result) => return result,
+    };
+
+    let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    let outcome = script::run(ctx, &user, &args, require_exec).await;
+    info!(
+        "脚本 {} 执行结束，状态: {}，步数: {}",
+        args[0], outcome.exit_status, outcome.steps
+    );
+
+    let mut stderr = outcome.stderr;
+    if let Some(e) = &outcome.error {
+        stderr.push(format!("sh: {}", e));
+    }
+    CommandResult {
+        stdout: outcome.stdout.join("\n"),
+        stderr: stderr.join("\n"),
+        exit_code: outcome.exit_status,
+        data: Some(json!({
+            "exit_status": outcome.exit_status,
+            "steps": outcome.steps
+        })),
+        records: None,
+        error: outcome.error.map(|_| ErrorKind::InvalidInput),
+        effects: Vec::new(),
+    }
+}
+
+const SPEC: CommandSpec =
+    CommandSpec::new("sh", "执行脚本文件，也可以用 ./<脚本> 执行带 x 权限的脚本")
+        .positionals(&[
+            Positional::new("脚本", "脚本文件")
+                .kind(ValueKind::Path)
+                .required(),
+            Positional::new("参数", "传给脚本的位置参数").repeated(),
+        ])
+        .trailing()
+        .examples(&[
+            ("sh deploy.sh", "执行脚本"),
+            (
+                "sh publish.sh a.md b.md",
+                "带参数执行，脚本中用 $1、$2、$@ 读取",
+            ),
+            ("./publish.sh a.md", "执行带 x 权限的脚本"),
+        ])
+        .see_also(&["chmod", "echo"]);
+
+#[async_trait]
+impl CommandHandler for ShCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 sh 命令");
+
+        if args.len() < 2 {
+            return CommandResult::invalid("用法：sh <脚本> [参数 ...]");
+        }
+
+        execute(&args[1..], ctx, false).await
+    }
+}
@@ -0,0 +1,209 @@
+use super::account::system_user;
+use super::args::{Opt, Positional, ValueKind};
+use super::{
+    dispatch, CommandContext, CommandHandler, CommandResult, CommandSpec, ErrorKind, SideEffect,
+};
+use crate::auth::AuthManager;
+use crate::db::SUDOERS_PATH;
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use log::{error, info, warn};
+
+pub struct SudoCommand;
+
+impl SudoCommand {
+    pub fn new() -> Self {
+        SudoCommand
+    }
+}
+
+// 验证密码后免密提权的时长
+const ELEVATION_MINUTES: i64 = 5;
+
+const SPEC: CommandSpec = CommandSpec::new("sudo", "以管理员身份执行命令")
+    .positionals(&[
+        Positional::new("命令", "要执行的命令").kind(ValueKind::Command),
+        Positional::new("参数", "命令的参数").repeated(),
+    ])
+    .options(&[
+        Opt::value("--password", "密码", "当前用户的密码，未提供时由客户端提示输入")
+            .secret(),
+        Opt::flag("--validate", "只验证密码并延长免密时间，不执行命令").short("-v"),
+        Opt::flag("--reset-timestamp", "清除免密时间，下次需要重新输入密码").short("-k"),
+    ])
+    .trailing()
+    .examples(&[
+        ("sudo ls /home", "以管理员身份列出所有用户的目录"),
+        ("sudo -v", "验证密码，之后 5 分钟内无需再次输入"),
+        ("sudo -k", "立即结束免密时间"),
+    ])
+    .see_also(&["id"]);
+
+// 去掉注释后的规则
+fn rule(line: &str) -> &str {
+    line.split('#').next().unwrap_or_default().trim()
+}
+
+// 策略文件的规则：用户名，或以 % 开头的角色名
+fn is_allowed(policy: &str, username: &str, role: &str) -> bool {
+    policy
+        .lines()
+        .map(rule)
+        .any(|rule| rule == username || rule.strip_prefix('%') == Some(role))
+}
+
+/// 去掉策略中授权给 `username` 的规则，没有这样的规则时返回 None
+pub fn without_user(policy: &str, username: &str) -> Option<String> {
+    if !policy.lines().any(|line| rule(line) == username) {
+        return None;
+    }
+    let mut kept: String = policy
+        .lines()
+        .filter(|line| rule(line) != username)
+        .map(|line| format!("{}\n", line))
+        .collect();
+    if !policy.ends_with('\n') {
+        kept.pop();
+    }
+    Some(kept)
+}
+
+#[async_trait]
+impl CommandHandler for SudoCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 sudo 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let claims = match ctx.claims() {
+            Ok(claims) => claims,
+            Err(result) => return result,
+        };
+
+        if matches.flag("--reset-timestamp") {
+            ctx.state.session_manager.drop_elevation(ctx.token);
+            return CommandResult::ok("");
+        }
+        let validate = matches.flag("--validate");
+        let command = matches.rest(0);
+        if command.is_empty() && !validate {
+            return SPEC.usage_error("缺少要执行的命令");
+        }
+
+        let account = match sqlx::query!(
+            "SELECT username, role, password_hash FROM users WHERE id = $1",
+            claims.sub
+        )
+        .fetch_optional(&ctx.state.db)
+        .await
+        {
+            Ok(Some(account)) => account,
+            Ok(None) => return CommandResult::unauthorized("用户不存在"),
+            Err(e) => {
+                error!("数据库查询错误: {}", e);
+                return CommandResult::internal();
+            }
+        };
+
+        // 检查策略文件，该文件不对普通用户开放，以系统身份读取
+        let policy = match ctx
+            .state
+            .vfs_manager
+            .read_file(&system_user("system"), SUDOERS_PATH, "/")
+            .await
+        {
+            Ok(policy) => policy,
+            Err(e) => {
+                warn!("读取 {} 失败: {}", SUDOERS_PATH, e);
+                String::new()
+            }
+        };
+        if !is_allowed(&policy, &account.username, &account.role) {
+            warn!("用户 {} 不在 sudoers 中，拒绝提权", account.username);
+            return CommandResult::forbidden(format!(
+                "sudo: {} 不在 sudoers 文件中，无法提权",
+                account.username
+            ));
+        }
+
+        // 免密时间已过时重新验证密码
+        if !ctx.state.session_manager.is_elevated(ctx.token) {
+            let password = match matches.value("--password") {
+                Some(password) => password,
+                None => {
+                    return CommandResult::unauthorized("sudo: 需要密码").with_effect(
+                        SideEffect::PromptPassword(format!(
+                            "[sudo] {} 的密码: ",
+                            account.username
+                        )),
+                    );
+                }
+            };
+
+            // 密码错误次数与登录分开计算
+            let attempt_key = format!("sudo:{}", account.username);
+            if let Err(e) = ctx.state.auth_manager.check_login_attempts(&attempt_key) {
+                return CommandResult::error(ErrorKind::TooManyRequests, format!("sudo: {}", e));
+            }
+            match AuthManager::verify_password(password, &account.password_hash) {
+                Ok(true) => ctx.state.auth_manager.reset_login_attempts(&attempt_key),
+                Ok(false) => {
+                    warn!("用户 {} sudo 密码错误", account.username);
+                    ctx.state.auth_manager.record_failed_attempt(&attempt_key);
+                    return CommandResult::unauthorized("sudo: 密码错误");
+                }
+                Err(e) => {
+                    error!("验证密码失败: {}", e);
+                    return CommandResult::internal();
+                }
+            }
+        }
+
+        // 每次使用都延长免密时间
+        let until = Utc::now() + Duration::minutes(ELEVATION_MINUTES);
+        if let Err(e) = ctx.state.session_manager.elevate(ctx.token, until) {
+            warn!("记录提权状态失败: {}", e);
+        }
+        if command.is_empty() {
+            return CommandResult::ok("");
+        }
+
+        info!("用户 {} 以管理员身份执行: {}", account.username, command[0]);
+        let elevated = CommandContext {
+            state: ctx.state,
+            token: ctx.token,
+            session_id: ctx.session_id,
+            cwd: ctx.cwd,
+            job: ctx.job,
+            output: ctx.output,
+            color: ctx.color,
+            elevated: true,
+            must_change_password: ctx.must_change_password,
+            client: ctx.client,
+        };
+        dispatch(command, &elevated).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn without_user_removes_only_that_users_rules() {
+        let policy = "# 允许的用户\n%admin\nbob # 临时\nbobby\nbob\n";
+        assert_eq!(
+            without_user(policy, "bob").as_deref(),
+            Some("# 允许的用户\n%admin\nbobby\n")
+        );
+        assert_eq!(without_user("%admin\nbob", "bob").as_deref(), Some("%admin"));
+        assert_eq!(without_user(policy, "alice"), None);
+        assert!(!is_allowed(&without_user(policy, "bob").unwrap(), "bob", "user"));
+    }
+}
@@ -0,0 +1,182 @@
+use super::args::Positional;
+use super::{history, CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::vfs::model::{User as VfsUser, VfsError};
+use async_trait::async_trait;
+use log::info;
+
+// 表达式无效时的退出状态
+const EXIT_INVALID: i32 = 2;
+
+/// `test` 与 `[`，两者只在 `[` 需要以 `]` 结尾上有区别
+pub struct TestCommand {
+    bracket: bool,
+}
+
+impl TestCommand {
+    pub fn new() -> Self {
+        TestCommand { bracket: false }
+    }
+
+    pub fn bracket() -> Self {
+        TestCommand { bracket: true }
+    }
+}
+
+const EXPRESSION: &[Positional] = &[Positional::new(
+    "表达式",
+    "-n/-z <字符串>、-e/-f/-d <路径>、<a> = <b>、<a> != <b>、<a> -eq|-ne|-lt|-le|-gt|-ge <b>，可用 ! 取反",
+)
+.repeated()];
+
+const SPEC: CommandSpec = CommandSpec::new("test", "计算条件表达式，结果为真时退出状态为 0")
+    .positionals(EXPRESSION)
+    .examples(&[
+        ("test -d Documents", "Documents 是目录时退出状态为 0"),
+        ("test $USER = alice", "比较字符串"),
+        ("test ! -e post.md", "文件不存在时为真"),
+    ])
+    .see_also(&["[", "sh"]);
+
+const BRACKET_SPEC: CommandSpec = CommandSpec::new("[", "与 test 相同，最后一个参数必须是 ]")
+    .positionals(EXPRESSION)
+    .examples(&[("[ -f post.md ]", "post.md 是普通文件时退出状态为 0")])
+    .see_also(&["test", "sh"]);
+
+#[async_trait]
+impl CommandHandler for TestCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        if self.bracket {
+            &BRACKET_SPEC
+        } else {
+            &SPEC
+        }
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 {} 命令", args[0]);
+
+        // 表达式中的 -e、-n 等不是选项，不按规格解析
+        let mut expression = &args[1..];
+        if self.bracket {
+            match expression.split_last() {
+                Some((&"]", rest)) => expression = rest,
+                _ => {
+                    return CommandResult::invalid("[: 缺少 ']'").with_exit_code(EXIT_INVALID);
+                }
+            }
+        }
+
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+        match eval_test(ctx.state, &user, ctx.cwd, expression).await {
+            Ok(true) => CommandResult::ok(""),
+            Ok(false) => CommandResult::ok("").with_exit_code(history::EXIT_FAILURE),
+            Err(e) => {
+                CommandResult::invalid(format!("{}: {}", args[0], e)).with_exit_code(EXIT_INVALID)
+            }
+        }
+    }
+}
+
+// 计算 test / [ 表达式，文件测试以 `user` 的身份进行，无权查看的文件视为不存在
+async fn eval_test(
+    state: &crate::AppState,
+    user: &VfsUser,
+    cwd: &str,
+    args: &[&str],
+) -> Result<bool, String> {
+    match args {
+        [] => Ok(false),
+        ["!", rest @ ..] => Box::pin(eval_test(state, user, cwd, rest))
+            .await
+            .map(|result| !result),
+        [value] => Ok(!value.is_empty()),
+        ["-n", value] => Ok(!value.is_empty()),
+        ["-z", value] => Ok(value.is_empty()),
+        [op @ ("-e" | "-f" | "-d"), path] => {
+            match state.vfs_manager.stat_as(user, path, cwd).await {
+                Ok(node) => Ok(match *op {
+                    "-f" => !node.is_dir,
+                    "-d" => node.is_dir,
+                    _ => true,
+                }),
+                Err(VfsError::NodeNotFound(_) | VfsError::PermissionError(_)) => Ok(false),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        [left, "=" | "==", right] => Ok(left == right),
+        [left, "!=", right] => Ok(left != right),
+        [left, op @ ("-eq" | "-ne" | "-lt" | "-le" | "-gt" | "-ge"), right] => {
+            let parse = |value: &str| {
+                value
+                    .parse::<i64>()
+                    .map_err(|_| format!("需要整数表达式: {}", value))
+            };
+            let (left, right) = (parse(left)?, parse(right)?);
+            Ok(match *op {
+                "-eq" => left == right,
+                "-ne" => left != right,
+                "-lt" => left < right,
+                "-le" => left <= right,
+                "-gt" => left > right,
+                _ => left >= right,
+            })
+        }
+        _ => Err("无法识别的表达式".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::model::Role;
+
+    #[tokio::test]
+    async fn eval_test_compares_strings_numbers_and_files() {
+        let Some(state) = crate::testing::state().await else {
+            return;
+        };
+        let user = VfsUser {
+            id: "test".to_string(),
+            username: "test".to_string(),
+            roles: vec![Role::Author],
+            session: None,
+        };
+        let eval = |expression: &'static str| {
+            let state = state.clone();
+            let user = user.clone();
+            async move {
+                let args: Vec<&str> = expression.split_whitespace().collect();
+                eval_test(&state, &user, "/", &args).await
+            }
+        };
+
+        for expression in [
+            "a",
+            "-n",
+            "-n a",
+            "-z",
+            "a = a",
+            "a == a",
+            "a != b",
+            "! a = b",
+            "2 -lt 10",
+            "-3 -le -3",
+            "10 -gt 2",
+            "4 -ne 5",
+            "-d /home",
+            "-e home",
+            "! -f /home",
+            "! -e /home/no-such-user",
+        ] {
+            assert_eq!(eval(expression).await, Ok(true), "{}", expression);
+        }
+        for expression in ["", "-z a", "a = b", "2 -gt 10", "! a", "-f /home"] {
+            assert_eq!(eval(expression).await, Ok(false), "{}", expression);
+        }
+        assert_eq!(eval("a -lt 1").await, Err("需要整数表达式: a".to_string()));
+        assert_eq!(eval("a b c d").await, Err("无法识别的表达式".to_string()));
+    }
+}
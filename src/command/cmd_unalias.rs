@@ -0,0 +1,73 @@
+use super::alias::{load_aliases, save_aliases};
+use super::args::{Opt, Positional};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, ErrorKind};
+use async_trait::async_trait;
+use log::{error, info};
+
+pub struct UnaliasCommand;
+
+impl UnaliasCommand {
+    pub fn new() -> Self {
+        UnaliasCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("unalias", "删除命令别名")
+    .positionals(&[Positional::new("name", "要删除的别名").repeated()])
+    .options(&[Opt::flag("-a", "删除所有别名")])
+    .examples(&[
+        ("unalias docs", "删除别名 docs"),
+        ("unalias -a", "删除所有别名"),
+    ])
+    .see_also(&["alias"]);
+
+#[async_trait]
+impl CommandHandler for UnaliasCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 unalias 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let names = matches.rest(0);
+        if !matches.flag("-a") && names.is_empty() {
+            return SPEC.usage_error("缺少参数 <name>");
+        }
+
+        // 验证登录状态并查询用户身份
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let mut aliases = match load_aliases(ctx.state, &user).await {
+            Ok(aliases) => aliases,
+            Err(e) => {
+                error!("读取别名失败: {}", e);
+                return CommandResult::error(ErrorKind::Internal, format!("读取别名失败: {}", e));
+            }
+        };
+
+        if matches.flag("-a") {
+            aliases.clear();
+        } else {
+            for name in names {
+                if aliases.remove(*name).is_none() {
+                    return CommandResult::invalid(format!("unalias: {}: 未找到", name));
+                }
+            }
+        }
+
+        if let Err(e) = save_aliases(ctx.state, &user, &aliases).await {
+            error!("保存别名失败: {}", e);
+            return CommandResult::error(ErrorKind::Internal, format!("保存别名失败: {}", e));
+        }
+
+        CommandResult::ok("")
+    }
+}
@@ -0,0 +1,154 @@
+use super::args::{Positional, ValueKind};
+use super::post::{self, DRAFTS_DIR, PUBLISHED_DIR};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::vfs::model::{Role, VfsError};
+use crate::vfs::path_normalizer::PathNormalizer;
+use async_trait::async_trait;
+use log::{error, info, warn};
+use serde_json::json;
+
+pub struct UnpublishCommand;
+
+impl UnpublishCommand {
+    pub fn new() -> Self {
+        UnpublishCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("unpublish", "撤回已发布的文章")
+    .positionals(&[
+        Positional::new("文章", "Documents/published 下的文章或文章的 slug")
+            .required()
+            .kind(ValueKind::Path),
+    ])
+    .examples(&[
+        (
+            "unpublish Documents/published/hello.md",
+            "撤回发布，文章移回 Documents/drafts/hello.md",
+        ),
+        ("unpublish hello-world", "按 slug 撤回发布"),
+    ])
+    .see_also(&["publish"])
+    .foreground()
+    .output();
+
+#[async_trait]
+impl CommandHandler for UnpublishCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 unpublish 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        // 先按路径查找，不是路径时按 slug 查找
+        let db = &ctx.state.db;
+        let name = matches.positional(0).unwrap_or_default();
+        let found = match PathNormalizer::normalize(name, ctx.cwd) {
+            Ok(path) => post::find_by_path(db, &path)
+                .await
+                .map(|existing| (path, existing)),
+            Err(e) => return CommandResult::invalid(format!("unpublish: {}", e)),
+        };
+        let (path, existing) = match found {
+            Ok((path, None)) if !name.contains('/') => match post::find_by_slug(db, name).await {
+                Ok(Some(existing)) => (existing.path.clone(), Some(existing)),
+                Ok(None) => (path, None),
+                Err(e) => {
+                    error!("查询文章失败: {}", e);
+                    return CommandResult::internal();
+                }
+            },
+            Ok(found) => found,
+            Err(e) => {
+                error!("查询文章失败: {}", e);
+                return CommandResult::internal();
+            }
+        };
+        if existing
+            .as_ref()
+            .is_some_and(|post| post.published_at.is_none())
+        {
+            return CommandResult::invalid(format!("unpublish: {} 尚未发布", name));
+        }
+        let (home, relative) = match post::split(&path, PUBLISHED_DIR) {
+            Some(location) => location,
+            None => return CommandResult::invalid(format!("unpublish: {} 不是已发布的文章", name)),
+        };
+
+        let node = match ctx.state.vfs_manager.stat(&path, "/").await {
+            Ok(node) if !node.is_dir => node,
+            Ok(_) => return CommandResult::invalid(format!("unpublish: {} 是一个目录", name)),
+            Err(VfsError::NodeNotFound(_)) => {
+                return CommandResult::invalid(format!("unpublish: {}: 没有那个文件", name))
+            }
+            Err(e) => {
+                error!("获取文件信息失败: {}", e);
+                return CommandResult::internal();
+            }
+        };
+        if node.owner_id != user.id && !user.roles.contains(&Role::Admin) {
+            warn!("用户 {} 尝试撤回他人的文章 {}", user.username, path);
+            return CommandResult::forbidden(format!("unpublish: 只能撤回自己的文章: {}", name));
+        }
+
+        // 先移回草稿目录，记录失败时恢复
+        let target = format!("{}/{}/{}", home, DRAFTS_DIR, relative);
+        let target_dir = PathNormalizer::parent(&target).unwrap_or_default();
+        if let Err(e) = post::ensure_dir(ctx, &node.owner_id, &target_dir).await {
+            warn!("创建草稿目录失败: {}", e);
+            return CommandResult::invalid(format!("unpublish: {}", e));
+        }
+        match ctx
+            .state
+            .vfs_manager
+            .rename(&user, &path, &target, "/")
+            .await
+        {
+            Ok(()) => {}
+            Err(VfsError::NodeExists(_)) => {
+                return CommandResult::invalid(format!("unpublish: 草稿 {} 已存在", target))
+            }
+            Err(e) => {
+                warn!("移动文章失败: {}", e);
+                return CommandResult::invalid(format!("unpublish: {}", e));
+            }
+        }
+
+        // 发布功能之前移动到发布目录的文件没有文章记录，只移动文件
+        let existing = match existing {
+            Some(existing) => existing,
+            None => {
+                info!("移回未记录的文章: {} -> {}", path, target);
+                return CommandResult::ok(format!("已移回 {}", target));
+            }
+        };
+        match post::save_unpublished(db, existing.id, &target).await {
+            Ok(post) => {
+                info!("用户 {} 撤回了文章 {} ({})", user.username, path, post.slug);
+                CommandResult::ok(format!("已撤回发布，文章移回 {}", target)).with_data(json!(post))
+            }
+            Err(e) => {
+                error!("记录撤回发布失败: {}", e);
+                if let Err(e) = ctx
+                    .state
+                    .vfs_manager
+                    .rename(&user, &target, &path, "/")
+                    .await
+                {
+                    error!("恢复文章失败: {}", e);
+                }
+                CommandResult::internal()
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use super::args::Positional;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{info, warn};
+
+pub struct UnsetCommand;
+
+impl UnsetCommand {
+    pub fn new() -> Self {
+        UnsetCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("unset", "删除环境变量")
+    .positionals(&[Positional::new("NAME", "要删除的变量")
+        .required()
+        .repeated()])
+    .examples(&[("unset EDITOR", "删除变量 EDITOR")])
+    .see_also(&["export", "env"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for UnsetCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 unset 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+
+        // 验证 token
+        if let Err(result) = ctx.claims() {
+            return result;
+        }
+
+        for name in matches.rest(0) {
+            if let Err(e) = ctx.state.session_manager.unset_var(ctx.token, name) {
+                warn!("删除环境变量失败: {}", e);
+                return CommandResult::invalid(format!("unset: {}", e));
+            }
+        }
+
+        CommandResult::ok("")
+    }
+}
@@ -0,0 +1,83 @@
+use super::account::{create_account, require_admin, validate_password, validate_username, ROLES};
+use super::args::{Opt, Positional, ValueKind};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{error, info};
+
+pub struct UseraddCommand;
+
+impl UseraddCommand {
+    pub fn new() -> Self {
+        UseraddCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("useradd", "创建用户（需要管理员权限）")
+    .positionals(&[Positional::new("用户名", "3-20 个字母、数字或下划线").required()])
+    .options(&[
+        Opt::value("--password", "密码", "初始密码，用户登录后必须修改")
+            .required()
+            .secret(),
+        Opt::value("--role", "角色", "用户的角色，默认为 user").kind(ValueKind::Choice(ROLES)),
+    ])
+    .examples(&[
+        ("sudo useradd bob --password temp123", "创建普通用户 bob"),
+        (
+            "sudo useradd carol --password temp123 --role admin",
+            "创建管理员 carol",
+        ),
+    ])
+    .see_also(&["usermod", "userdel", "passwd"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for UseraddCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 useradd 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let admin = match require_admin(ctx, "useradd").await {
+            Ok(admin) => admin,
+            Err(result) => return result,
+        };
+
+        let username = matches.positional(0).unwrap_or_default();
+        let password = matches.value("--password").unwrap_or_default();
+        let role = matches.value("--role").unwrap_or("user");
+        if let Err(e) = validate_username(username) {
+            return CommandResult::invalid(format!("useradd: {}", e));
+        }
+        if let Err(e) = validate_password(password) {
+            return CommandResult::invalid(format!("useradd: {}", e));
+        }
+
+        let user_id = match create_account(ctx, username, password, role).await {
+            Ok(user_id) => user_id,
+            Err(result) => return result,
+        };
+        // 初始密码由管理员设置，与 passwd 重置一样要求用户修改
+        if let Err(e) = sqlx::query!(
+            "UPDATE users SET must_change_password = TRUE WHERE id = $1",
+            user_id
+        )
+        .execute(&ctx.state.db)
+        .await
+        {
+            error!("更新用户失败: {}", e);
+            return CommandResult::internal();
+        }
+
+        info!(
+            "管理员 {} 创建了用户 {} ({})",
+            admin.username, username, role
+        );
+        CommandResult::ok(format!("已创建用户 {}，角色: {}", username, role))
+    }
+}
@@ -0,0 +1,204 @@
+use super::account::{end_sessions, home_dir, require_admin, system_user};
+use super::args::{Opt, Positional};
+use super::cmd_sudo::without_user;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec, ErrorKind};
+use crate::db::SUDOERS_PATH;
+use crate::vfs::model::VfsError;
+use async_trait::async_trait;
+use chrono::Utc;
+use log::{error, info, warn};
+
+pub struct UserdelCommand;
+
+impl UserdelCommand {
+    pub fn new() -> Self {
+        UserdelCommand
+    }
+}
+
+// 归档的家目录所在的目录
+const ARCHIVE_DIR: &str = "/var/archive";
+
+// 归档的家目录只有管理员可以访问
+const ARCHIVE_PERM: i16 = 0o700;
+
+const SPEC: CommandSpec = CommandSpec::new("userdel", "删除用户（需要管理员权限）")
+    .positionals(&[Positional::new("用户", "要删除的用户").required()])
+    .options(&[
+        Opt::flag("--archive", "把家目录移动到 /var/archive 保存"),
+        Opt::flag("--purge", "删除家目录及其中的所有文件"),
+    ])
+    .examples(&[
+        (
+            "sudo userdel bob --archive",
+            "删除 bob，家目录归档为 /var/archive/bob-<时间>",
+        ),
+        ("sudo userdel bob --purge", "删除 bob 及其所有文件"),
+    ])
+    .see_also(&["useradd", "usermod"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for UserdelCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 userdel 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let admin = match require_admin(ctx, "userdel").await {
+            Ok(admin) => admin,
+            Err(result) => return result,
+        };
+
+        // 家目录的处理方式必须明确指定
+        let archive = matches.flag("--archive");
+        let purge = matches.flag("--purge");
+        if archive == purge {
+            return SPEC.usage_error("请指定 --archive 归档家目录或 --purge 删除家目录");
+        }
+
+        let target = matches.positional(0).unwrap_or_default();
+        let user_id = match sqlx::query_scalar!("SELECT id FROM users WHERE username = $1", target)
+            .fetch_optional(&ctx.state.db)
+            .await
+        {
+            Ok(Some(user_id)) => user_id,
+            Ok(None) => return CommandResult::invalid(format!("userdel: 用户 {} 不存在", target)),
+            Err(e) => {
+                error!("数据库查询错误: {}", e);
+                return CommandResult::internal();
+            }
+        };
+        if user_id == admin.id {
+            return CommandResult::invalid("userdel: 不能删除自己");
+        }
+
+        // 在一个事务中删除账号及其命令历史、登录历史、组成员关系、离线消息、文章记录和 sudo 规则
+        let home = home_dir(target);
+        let deleted = async {
+            let mut tx = ctx.state.db.begin().await?;
+            sqlx::query!("DELETE FROM command_history WHERE user_id = $1", user_id)
+                .execute(&mut tx)
+                .await?;
+            sqlx::query!("DELETE FROM login_history WHERE user_id = $1", user_id)
+                .execute(&mut tx)
+                .await?;
+            // 该用户是唯一组管理员的组改由执行删除的管理员管理
+            sqlx::query!(
+                r#"
+                INSERT INTO user_group_members (user_id, group_id, is_admin)
+                SELECT $2, m.group_id, TRUE
+                FROM user_group_members m
+                WHERE m.user_id = $1
+                    AND m.is_admin
+                    AND NOT EXISTS (
+                        SELECT 1 FROM user_group_members o
+                        WHERE o.group_id = m.group_id AND o.user_id <> $1 AND o.is_admin
+                    )
+                ON CONFLICT (user_id, group_id) DO UPDATE SET is_admin = TRUE
+                "#,
+                user_id,
+                admin.id
+            )
+            .execute(&mut tx)
+            .await?;
+            sqlx::query!("DELETE FROM user_group_members WHERE user_id = $1", user_id)
+                .execute(&mut tx)
+                .await?;
+            sqlx::query!("DELETE FROM messages WHERE recipient_id = $1", user_id)
+                .execute(&mut tx)
+                .await?;
+            sqlx::query!("DELETE FROM posts WHERE author_id = $1", user_id)
+                .execute(&mut tx)
+                .await?;
+            sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
+                .execute(&mut tx)
+                .await?;
+            // 家目录之外属于该用户的文件和目录交给系统
+            sqlx::query!(
+                r#"
+                UPDATE vfs_nodes SET owner_id = 'system', updated_at = NOW()
+                WHERE owner_id = $1 AND name <> $2 AND left(name, length($2) + 1) <> $2 || '/'
+                "#,
+                user_id,
+                home
+            )
+            .execute(&mut tx)
+            .await?;
+            let policy = sqlx::query_scalar!(
+                "SELECT content FROM vfs_nodes WHERE name = $1 FOR UPDATE",
+                SUDOERS_PATH
+            )
+            .fetch_optional(&mut tx)
+            .await?
+            .flatten();
+            if let Some(policy) = policy.and_then(|policy| without_user(&policy, target)) {
+                sqlx::query!(
+                    "UPDATE vfs_nodes SET content = $1, updated_at = NOW() WHERE name = $2",
+                    policy,
+                    SUDOERS_PATH
+                )
+                .execute(&mut tx)
+                .await?;
+            }
+            tx.commit().await
+        };
+        if let Err(e) = deleted.await {
+            error!("删除用户失败: {}", e);
+            return CommandResult::internal();
+        }
+        end_sessions(ctx, &user_id, None);
+        info!("管理员 {} 删除了用户 {}", admin.username, target);
+
+        let system = system_user("system");
+        let handled = if archive {
+            let archived = format!(
+                "{}/{}-{}",
+                ARCHIVE_DIR,
+                target,
+                Utc::now().format("%Y%m%d%H%M%S")
+            );
+            let moved = ctx
+                .state
+                .vfs_manager
+                .rename(&system, &home, &archived, "/")
+                .await;
+            match moved {
+                Ok(()) => ctx
+                    .state
+                    .vfs_manager
+                    .chmod(&system, &archived, "/", ARCHIVE_PERM)
+                    .await
+                    .map(|_| format!("已删除用户 {}，家目录已归档到 {}", target, archived)),
+                Err(e) => Err(e),
+            }
+        } else {
+            ctx.state
+                .vfs_manager
+                .delete(&system, &home, "/")
+                .await
+                .map(|_| format!("已删除用户 {} 及其家目录", target))
+        };
+
+        match handled {
+            Ok(message) => CommandResult::ok(message),
+            Err(VfsError::NodeNotFound(_)) => {
+                warn!("用户 {} 没有家目录", target);
+                CommandResult::ok(format!("已删除用户 {}（没有家目录）", target))
+            }
+            Err(e) => {
+                error!("处理家目录 {} 失败: {}", home, e);
+                CommandResult::error(
+                    ErrorKind::Internal,
+                    format!("userdel: 已删除用户 {}，但处理家目录失败: {}", target, e),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use super::account::{
+    home_dir, require_admin, revoke_logins, system_user, validate_username, ROLES,
+};
+use super::args::{Opt, Positional, ValueKind};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::{error, info, warn};
+
+pub struct UsermodCommand;
+
+impl UsermodCommand {
+    pub fn new() -> Self {
+        UsermodCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("usermod", "修改用户账号（需要管理员权限）")
+    .positionals(&[Positional::new("用户", "要修改的用户").required()])
+    .options(&[
+        Opt::flag("--lock", "锁定账号，禁止登录并使现有登录失效").short("-L"),
+        Opt::flag("--unlock", "解除锁定").short("-U"),
+        Opt::value("--role", "角色", "修改用户的角色").kind(ValueKind::Choice(ROLES)),
+        Opt::value("--login", "新用户名", "修改用户名，家目录随之移动").short("-l"),
+    ])
+    .examples(&[
+        ("sudo usermod -L bob", "锁定 bob 的账号"),
+        ("sudo usermod bob --role admin", "把 bob 设为管理员"),
+        (
+            "sudo usermod bob -l robert",
+            "把 bob 改名为 robert，家目录移动到 /home/robert",
+        ),
+    ])
+    .see_also(&["useradd", "userdel", "passwd"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for UsermodCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 usermod 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let admin = match require_admin(ctx, "usermod").await {
+            Ok(admin) => admin,
+            Err(result) => return result,
+        };
+
+        let target = matches.positional(0).unwrap_or_default();
+        let lock = matches.flag("--lock");
+        let unlock = matches.flag("--unlock");
+        let role = matches.value("--role");
+        let login = matches.value("--login");
+        if lock && unlock {
+            return SPEC.usage_error("--lock 和 --unlock 不能同时使用");
+        }
+        if !lock && !unlock && role.is_none() && login.is_none() {
+            return SPEC.usage_error("没有指定要修改的内容");
+        }
+
+        let account = match sqlx::query!(
+            "SELECT id, username, role, locked FROM users WHERE username = $1",
+            target
+        )
+        .fetch_optional(&ctx.state.db)
+        .await
+        {
+            Ok(Some(account)) => account,
+            Ok(None) => return CommandResult::invalid(format!("usermod: 用户 {} 不存在", target)),
+            Err(e) => {
+                error!("数据库查询错误: {}", e);
+                return CommandResult::internal();
+            }
+        };
+
+        // 避免管理员把自己锁在系统之外
+        if account.id == admin.id && (lock || role.is_some_and(|role| role != "admin")) {
+            return CommandResult::invalid("usermod: 不能锁定自己或取消自己的管理员角色");
+        }
+
+        let mut changes = Vec::new();
+        let mut username = account.username.clone();
+        if let Some(new_name) = login.filter(|name| *name != account.username) {
+            if let Err(e) = validate_username(new_name) {
+                return CommandResult::invalid(format!("usermod: {}", e));
+            }
+            match sqlx::query!("SELECT id FROM users WHERE username = $1", new_name)
+                .fetch_optional(&ctx.state.db)
+                .await
+            {
+                Ok(Some(_)) => {
+                    return CommandResult::invalid(format!("usermod: 用户名 {} 已存在", new_name))
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("数据库查询错误: {}", e);
+                    return CommandResult::internal();
+                }
+            }
+
+            // 先移动家目录，更新用户名失败时移回原处
+            let (old_home, new_home) = (home_dir(&account.username), home_dir(new_name));
+            let system = system_user("system");
+            if let Err(e) = ctx
+                .state
+                .vfs_manager
+                .rename(&system, &old_home, &new_home, "/")
+                .await
+            {
+                warn!("移动家目录失败: {}", e);
+                return CommandResult::invalid(format!("usermod: 移动家目录失败: {}", e));
+            }
+            // 文章记录的路径随家目录一起更新
+            let renamed = async {
+                let mut tx = ctx.state.db.begin().await?;
+                sqlx::query!(
+                    "UPDATE users SET username = $1 WHERE id = $2",
+                    new_name,
+                    account.id
+                )
+                .execute(&mut tx)
+                .await?;
+                sqlx::query!(
+                    r#"
+                    UPDATE posts SET path = $1 || substr(path, length($2) + 1)
+                    WHERE left(path, length($2) + 1) = $2 || '/'
+                    "#,
+                    new_home,
+                    old_home
+                )
+                .execute(&mut tx)
+                .await?;
+                tx.commit().await
+            };
+            if let Err(e) = renamed.await {
+                error!("更新用户名失败: {}", e);
+                if let Err(e) = ctx
+                    .state
+                    .vfs_manager
+                    .rename(&system, &new_home, &old_home, "/")
+                    .await
+                {
+                    error!("恢复家目录失败: {}", e);
+                }
+                return CommandResult::internal();
+            }
+            changes.push(format!(
+                "用户名改为 {}，家目录移动到 {}",
+                new_name, new_home
+            ));
+            username = new_name.to_string();
+        }
+
+        if let Some(role) = role.filter(|role| *role != account.role) {
+            if let Err(e) =
+                sqlx::query!("UPDATE users SET role = $1 WHERE id = $2", role, account.id)
+                    .execute(&ctx.state.db)
+                    .await
+            {
+                error!("更新角色失败: {}", e);
+                return CommandResult::internal();
+            }
+            changes.push(format!("角色改为 {}", role));
+        }
+
+        if (lock && !account.locked) || (unlock && account.locked) {
+            if let Err(e) = sqlx::query!(
+                "UPDATE users SET locked = $1 WHERE id = $2",
+                lock,
+                account.id
+            )
+            .execute(&ctx.state.db)
+            .await
+            {
+                error!("更新锁定状态失败: {}", e);
+                return CommandResult::internal();
+            }
+            changes.push(if lock { "已锁定" } else { "已解除锁定" }.to_string());
+        }
+
+        // 锁定或改名后，现有登录中的用户名和家目录已失效
+        if lock || username != account.username {
+            if let Err(result) = revoke_logins(ctx, &account.id).await {
+                return result;
+            }
+        }
+
+        if changes.is_empty() {
+            return CommandResult::ok(format!("用户 {} 没有变化", username));
+        }
+        info!(
+            "管理员 {} 修改了用户 {}: {}",
+            admin.username,
+            account.username,
+            changes.join("，")
+        );
+        CommandResult::ok(format!("{}: {}", account.username, changes.join("，")))
+    }
+}
@@ -0,0 +1,99 @@
+use super::sessions::{active_sessions, render};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use chrono::Local;
+use log::{debug, info};
+use serde_json::json;
+use std::collections::HashSet;
+
+pub struct WCommand;
+
+impl WCommand {
+    pub fn new() -> Self {
+        WCommand
+    }
+}
+
+// 服务运行时间的来源
+const UPTIME_PATH: &str = "/proc/uptime";
+
+const SPEC: CommandSpec = CommandSpec::new("w", "显示系统运行时间和登录用户的活动")
+    .examples(&[("w", "显示当前时间、运行时间、用户数及每个会话的空闲时间")])
+    .see_also(&["who", "last", "uptime"])
+    .output();
+
+// 运行时间：不到一天显示 `时:分`，否则带上天数
+fn format_uptime(seconds: i64) -> String {
+    let clock = format!("{}:{:02}", seconds % 86400 / 3600, seconds % 3600 / 60);
+    match seconds / 86400 {
+        0 => clock,
+        days => format!("{} days, {}", days, clock),
+    }
+}
+
+#[async_trait]
+impl CommandHandler for WCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 w 命令");
+
+        if let Err(result) = SPEC.parse(args) {
+            return result;
+        }
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let sessions = active_sessions(ctx.state, &user);
+        let users: HashSet<&str> = sessions.iter().map(|s| s.user.as_str()).collect();
+
+        // 与 Unix 一致，运行时间读自 /proc/uptime
+        let uptime = match ctx
+            .state
+            .vfs_manager
+            .read_file(&user, UPTIME_PATH, "/")
+            .await
+        {
+            Ok(content) => content
+                .split_whitespace()
+                .next()
+                .and_then(|seconds| seconds.parse::<f64>().ok())
+                .map(|seconds| format!(" up {},", format_uptime(seconds as i64))),
+            Err(e) => {
+                debug!("读取 {} 失败: {}", UPTIME_PATH, e);
+                None
+            }
+        };
+        let summary = format!(
+            " {}{}  {} users, {} sessions",
+            Local::now().format("%H:%M:%S"),
+            uptime.unwrap_or_default(),
+            users.len(),
+            sessions.len()
+        );
+
+        CommandResult::ok(format!("{}\n{}", summary, render(&sessions, true)))
+            .with_data(json!({
+                "users": users.len(),
+                "sessions": sessions,
+            }))
+            .with_records(&sessions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_uptime_adds_days_after_one_day() {
+        assert_eq!(format_uptime(0), "0:00");
+        assert_eq!(format_uptime(3 * 3600 + 7 * 60 + 59), "3:07");
+        assert_eq!(format_uptime(86399), "23:59");
+        assert_eq!(format_uptime(2 * 86400 + 3600), "2 days, 1:00");
+    }
+}
@@ -0,0 +1,105 @@
+use super::args::Positional;
+use super::cmd_jobs::format_job;
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::job::{parse_job_id, JobStatus};
+use async_trait::async_trait;
+use log::{info, warn};
+use std::time::Duration;
+
+// 单次请求最多等待的时间，超时后任务继续在后台运行
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct WaitCommand;
+
+impl WaitCommand {
+    pub fn new() -> Self {
+        WaitCommand
+    }
+}
+
+/// 解析任务编号，省略时使用最近启动的任务
+pub(super) fn resolve_job(
+    name: &str,
+    spec: Option<&str>,
+    ctx: &CommandContext<'_>,
+) -> Result<u64, CommandResult> {
+    match spec {
+        Some(spec) => parse_job_id(spec)
+            .ok_or_else(|| CommandResult::invalid(format!("{}: 无效的任务编号: {}", name, spec))),
+        None => ctx
+            .state
+            .job_manager
+            .latest(ctx.token)
+            .ok_or_else(|| CommandResult::invalid(format!("{}: 没有后台任务", name))),
+    }
+}
+
+/// 等待任务结束并取回输出
+///
+/// 结果沿用任务的输出和退出状态；等待超时时任务保留在后台。结束的任务仍留在列表中，
+/// 之后 `jobs` 可以看到它的状态，再次 `wait` 或 `fg` 会重新显示输出。
+pub(super) async fn wait_for(name: &str, id: u64, ctx: &CommandContext<'_>) -> CommandResult {
+    let job = match ctx
+        .state
+        .job_manager
+        .wait(ctx.token, id, WAIT_TIMEOUT)
+        .await
+    {
+        Some(job) => job,
+        None => return CommandResult::invalid(format!("{}: 任务 {} 不存在", name, id)),
+    };
+    if job.status == JobStatus::Running {
+        warn!("等待后台任务超时: {}", id);
+        return CommandResult::invalid(format!(
+            "{}: 等待超时，任务仍在运行：{}",
+            name,
+            format_job(&job)
+        ));
+    }
+
+    info!("取回后台任务 [{}] 的输出", id);
+    match job.output {
+        Some(output) => CommandResult {
+            stdout: output.stdout,
+            stderr: output.stderr,
+            exit_code: output.exit_code,
+            data: output.data,
+            ..Default::default()
+        },
+        // 被 kill 终止的任务没有输出
+        None => CommandResult::invalid(format_job(&job)),
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("wait", "等待后台任务结束并显示其输出")
+    .positionals(&[Positional::new("任务", "任务编号，如 1 或 %1").required()])
+    .examples(&[
+        ("sh build.sh &", "在后台执行脚本，返回任务编号"),
+        ("wait 1", "等待任务 1 结束并显示输出"),
+    ])
+    .see_also(&["jobs", "fg", "kill"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for WaitCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 wait 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        if let Err(result) = ctx.claims() {
+            return result;
+        }
+
+        match resolve_job("wait", matches.positional(0), ctx) {
+            Ok(id) => wait_for("wait", id, ctx).await,
+            Err(result) => result,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use super::account::require_admin;
+use super::args::Positional;
+use super::message::{self, MAX_MESSAGE_LEN};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::ws::Event;
+use async_trait::async_trait;
+use chrono::Utc;
+use log::{error, info};
+
+pub struct WallCommand;
+
+impl WallCommand {
+    pub fn new() -> Self {
+        WallCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("wall", "向所有用户发布公告（需要管理员权限）")
+    .positionals(&[Positional::new("消息", "公告内容").required().repeated()])
+    .examples(&[(
+        "sudo wall 今晚 23 点维护，预计 10 分钟",
+        "通知所有用户，不在线的用户下次登录时看到",
+    )])
+    .see_also(&["write", "mesg"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for WallCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 wall 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let admin = match require_admin(ctx, "wall").await {
+            Ok(admin) => admin,
+            Err(result) => return result,
+        };
+
+        let text = matches.rest(0).join(" ");
+        if text.trim().is_empty() {
+            return SPEC.usage_error("缺少公告内容");
+        }
+        if text.chars().count() > MAX_MESSAGE_LEN {
+            return CommandResult::invalid(format!(
+                "wall: 公告不能超过 {} 个字符",
+                MAX_MESSAGE_LEN
+            ));
+        }
+
+        // 公告不受 mesg n 限制；不在线的用户下次登录时显示
+        let sent_at = Utc::now();
+        let event = Event::Message {
+            from: admin.username.clone(),
+            text: text.clone(),
+            broadcast: true,
+            sent_at,
+        };
+        let online = ctx.state.connection_manager.broadcast(event);
+        let mut skipped = online.clone();
+        skipped.push(admin.id.clone());
+        let queued =
+            match message::queue_broadcast(&ctx.state.db, &skipped, &admin.username, &text, sent_at)
+                .await
+            {
+                Ok(queued) => queued,
+                Err(e) => {
+                    error!("保存离线公告失败: {}", e);
+                    return CommandResult::internal();
+                }
+            };
+        info!(
+            "管理员 {} 发布公告，在线送达 {} 人，离线保存 {} 人",
+            admin.username,
+            online.len(),
+            queued
+        );
+        CommandResult::ok(format!(
+            "公告已发送给 {} 位在线用户，{} 位离线用户将在下次登录时看到",
+            online.len(),
+            queued
+        ))
+    }
+}
@@ -0,0 +1,45 @@
+use super::sessions::{active_sessions, render};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use async_trait::async_trait;
+use log::info;
+use serde_json::json;
+
+pub struct WhoCommand;
+
+impl WhoCommand {
+    pub fn new() -> Self {
+        WhoCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("who", "显示当前登录的用户")
+    .examples(&[
+        ("who", "列出登录会话的用户、来源、登录时间和空闲时间"),
+        ("sudo who", "管理员可以看到所有用户的会话"),
+    ])
+    .see_also(&["w", "last"])
+    .output();
+
+#[async_trait]
+impl CommandHandler for WhoCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 who 命令");
+
+        if let Err(result) = SPEC.parse(args) {
+            return result;
+        }
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let sessions = active_sessions(ctx.state, &user);
+        CommandResult::ok(render(&sessions, false))
+            .with_data(json!(sessions))
+            .with_records(&sessions)
+    }
+}
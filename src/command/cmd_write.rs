@@ -0,0 +1,109 @@
+use super::args::Positional;
+use super::message::{self, MAX_MESSAGE_LEN};
+use super::{CommandContext, CommandHandler, CommandResult, CommandSpec};
+use crate::vfs::model::Role;
+use crate::ws::Event;
+use async_trait::async_trait;
+use chrono::Utc;
+use log::{error, info, warn};
+
+pub struct WriteCommand;
+
+impl WriteCommand {
+    pub fn new() -> Self {
+        WriteCommand
+    }
+}
+
+const SPEC: CommandSpec = CommandSpec::new("write", "给其他用户发送消息")
+    .positionals(&[
+        Positional::new("用户", "接收消息的用户").required(),
+        Positional::new("消息", "消息内容").repeated(),
+    ])
+    .examples(&[
+        ("write bob 你好，文章写完了吗？", "发消息给 bob"),
+        ("write bob \"今晚 8 点发布\"", "消息中的空白原样保留"),
+    ])
+    .see_also(&["wall", "mesg"])
+    .foreground();
+
+#[async_trait]
+impl CommandHandler for WriteCommand {
+    fn spec(&self) -> &'static CommandSpec {
+        &SPEC
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+        info!("开始处理 write 命令");
+
+        let matches = match SPEC.parse(args) {
+            Ok(matches) => matches,
+            Err(result) => return result,
+        };
+        let user = match ctx.user().await {
+            Ok(user) => user,
+            Err(result) => return result,
+        };
+
+        let text = matches.rest(1).join(" ");
+        if text.trim().is_empty() {
+            return SPEC.usage_error("缺少消息内容");
+        }
+        if text.chars().count() > MAX_MESSAGE_LEN {
+            return CommandResult::invalid(format!(
+                "write: 消息不能超过 {} 个字符",
+                MAX_MESSAGE_LEN
+            ));
+        }
+
+        let target = matches.positional(0).unwrap_or_default();
+        let recipient = match sqlx::query!(
+            "SELECT id, accept_messages, locked FROM users WHERE username = $1",
+            target
+        )
+        .fetch_optional(&ctx.state.db)
+        .await
+        {
+            Ok(Some(recipient)) if !recipient.locked => recipient,
+            Ok(_) => return CommandResult::invalid(format!("write: 用户 {} 不存在", target)),
+            Err(e) => {
+                error!("数据库查询错误: {}", e);
+                return CommandResult::internal();
+            }
+        };
+
+        // 与 Unix 一致，管理员不受 mesg n 限制
+        if !recipient.accept_messages && !user.roles.contains(&Role::Admin) {
+            warn!("{} 已关闭消息接收，拒绝 {} 的消息", target, user.username);
+            return CommandResult::forbidden(format!("write: {} 已关闭消息接收", target));
+        }
+
+        let sent_at = Utc::now();
+        let event = Event::Message {
+            from: user.username.clone(),
+            text: text.clone(),
+            broadcast: false,
+            sent_at,
+        };
+        let delivered = ctx
+            .state
+            .connection_manager
+            .send_to_user(&recipient.id, event);
+        info!(
+            "{} 向 {} 发送消息，送达 {} 个会话",
+            user.username, target, delivered
+        );
+        if delivered > 0 {
+            return CommandResult::ok(format!("消息已发送给 {}", target));
+        }
+
+        // 对方不在线，保存到下次登录时显示
+        match message::queue(&ctx.state.db, &recipient.id, &user.username, &text, sent_at).await {
+            Ok(()) => CommandResult::ok(format!("{} 当前不在线，消息将在其下次登录时显示", target)),
+            Err(e) => {
+                error!("保存离线消息失败: {}", e);
+                CommandResult::internal()
+            }
+        }
+    }
+}
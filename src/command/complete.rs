@@ -0,0 +1,240 @@
+use super::alias;
+use super::args::ValueKind;
+use super::parser::split_command;
+use super::CommandResponse;
+use crate::auth::{is_token_current, validate_token};
+use crate::vfs::model::{Role, User as VfsUser};
+use actix_web::{web, HttpResponse, Responder};
+use log::{debug, info, warn};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+// 单次补全返回的最大候选数量
+const MAX_CANDIDATES: usize = 100;
+
+// 补全候选项
+#[derive(Debug, Serialize)]
+pub struct Candidate {
+    pub value: String,
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// 补全结果：用候选项替换第 `start` 到 `end` 个字符之间的内容
+#[derive(Debug, Serialize)]
+pub struct Completion {
+    pub start: usize,
+    pub end: usize,
+    pub candidates: Vec<Candidate>,
+}
+
+// 处理补全请求的主函数
+pub async fn handle_complete(
+    body: web::Json<Value>,
+    data: web::Data<crate::AppState>,
+    req: actix_web::HttpRequest,
+) -> impl Responder {
+    let line = body.get("line").and_then(|v| v.as_str()).unwrap_or("");
+    let line_len = line.chars().count();
+    let cursor = body
+        .get("cursor")
+        .and_then(|v| v.as_u64())
+        .map(|c| (c as usize).min(line_len))
+        .unwrap_or(line_len);
+
+    // 从 Authorization header 中获取 token
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+        .unwrap_or("");
+
+    debug!("收到补全请求: {:?} (光标: {})", line, cursor);
+
+    let completion = complete(&data, line, cursor, token).await;
+    HttpResponse::Ok().json(CommandResponse {
+        success: true,
+        message: "".to_string(),
+        data: Some(json!(completion)),
+        exit_code: 0,
+        effects: Vec::new(),
+        spans: Vec::new(),
+    })
+}
+
+/// 补全光标处的单词，与传输方式无关；`cursor` 按字符计
+///
+/// 已登录用户还可以补全别名和路径。
+pub async fn complete(
+    data: &crate::AppState,
+    line: &str,
+    cursor: usize,
+    token: &str,
+) -> Completion {
+    let cursor = cursor.min(line.chars().count());
+
+    // 只补全光标之前的内容，当前单词从最后一个空白之后开始
+    let before: String = line.chars().take(cursor).collect();
+    let start = before
+        .char_indices()
+        .rfind(|(_, c)| c.is_whitespace())
+        .map(|(i, c)| before[..i + c.len_utf8()].chars().count())
+        .unwrap_or(0);
+    let word: String = before.chars().skip(start).collect();
+    let previous: String = before.chars().take(start).collect();
+    let previous_words = split_command(&previous, None).unwrap_or_default();
+
+    // 已登录用户可以补全别名和路径
+    let login = match validate_token(token) {
+        Ok(claims)
+            if !data.auth_manager.is_token_blacklisted(token)
+                && is_token_current(&data.db, &claims).await =>
+        {
+            data.session_manager
+                .validate(token, &claims, None)
+                .ok()
+                .map(|session| (claims, session))
+        }
+        _ => None,
+    };
+    let user = login.as_ref().map(|(claims, _)| VfsUser {
+        id: claims.sub.clone(),
+        username: claims.username.clone(),
+        roles: vec![Role::from_account(&claims.role, false)],
+        session: None,
+    });
+    let aliases = match &user {
+        Some(user) => alias::load_aliases(data, user).await.unwrap_or_else(|e| {
+            warn!("读取别名失败: {}", e);
+            BTreeMap::new()
+        }),
+        None => BTreeMap::new(),
+    };
+
+    let mut candidates = Vec::new();
+    let mut complete_paths = false;
+    if previous_words.is_empty() && word.contains('/') {
+        // 带路径的命令按脚本补全
+        complete_paths = true;
+    } else if previous_words.is_empty() {
+        // 补全命令名
+        for (name, handler) in &data.command_registry.commands {
+            if name.starts_with(&word) {
+                candidates.push(Candidate {
+                    value: name.clone(),
+                    kind: "command",
+                    description: Some(handler.description().to_string()),
+                });
+            }
+        }
+        for (name, value) in &aliases {
+            if name.starts_with(&word) {
+                candidates.push(Candidate {
+                    value: name.clone(),
+                    kind: "alias",
+                    description: Some(value.clone()),
+                });
+            }
+        }
+    } else {
+        // 别名按其展开后的首个命令确定处理器
+        let command = aliases
+            .get(&previous_words[0])
+            .and_then(|value| split_command(value, None).ok())
+            .and_then(|words| words.into_iter().next())
+            .unwrap_or_else(|| previous_words[0].clone());
+
+        if let Some(handler) = data.command_registry.get_handler(&command) {
+            let spec = handler.spec();
+            match spec.expected(&previous_words[1..]) {
+                // 补全选项值或位置参数的可选值
+                Some(ValueKind::Choice(choices)) if !word.starts_with('-') => {
+                    for choice in choices {
+                        if choice.starts_with(word.as_str()) {
+                            candidates.push(Candidate {
+                                value: choice.to_string(),
+                                kind: "value",
+                                description: None,
+                            });
+                        }
+                    }
+                }
+                Some(ValueKind::Path) if !word.starts_with('-') => complete_paths = true,
+                Some(ValueKind::Command) if !word.starts_with('-') => {
+                    for (name, handler) in &data.command_registry.commands {
+                        if name.starts_with(&word) {
+                            candidates.push(Candidate {
+                                value: name.clone(),
+                                kind: "command",
+                                description: Some(handler.description().to_string()),
+                            });
+                        }
+                    }
+                }
+                _ if word.starts_with('-') => {
+                    // 补全选项
+                    for (name, help) in spec.option_names() {
+                        if name.starts_with(word.as_str()) {
+                            candidates.push(Candidate {
+                                value: name.to_string(),
+                                kind: "flag",
+                                description: Some(help.to_string()),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if let (true, Some(user), Some((_, session))) = (complete_paths, &user, &login) {
+        // 补全路径
+        let (dir, prefix) = match word.rfind('/') {
+            Some(i) => (&word[..i + 1], &word[i + 1..]),
+            None => ("", word.as_str()),
+        };
+        let list_path = if dir.is_empty() { "." } else { dir };
+        match data
+            .vfs_manager
+            .list_dir(user, list_path, &session.cwd)
+            .await
+        {
+            Ok(nodes) => {
+                for node in nodes {
+                    let name = node.name.rsplit('/').next().unwrap_or(&node.name);
+                    // 未输入 `.` 时不补全隐藏文件
+                    if !name.starts_with(prefix)
+                        || (name.starts_with('.') && !prefix.starts_with('.'))
+                    {
+                        continue;
+                    }
+                    candidates.push(Candidate {
+                        value: if node.is_dir {
+                            format!("{}{}/", dir, name)
+                        } else {
+                            format!("{}{}", dir, name)
+                        },
+                        kind: if node.is_dir { "directory" } else { "file" },
+                        description: None,
+                    });
+                }
+            }
+            Err(e) => debug!("补全路径失败: {}", e),
+        }
+    }
+
+    candidates.sort_by(|a, b| a.value.cmp(&b.value));
+    candidates.dedup_by(|a, b| a.value == b.value);
+    candidates.truncate(MAX_CANDIDATES);
+    info!("补全 {:?} 得到 {} 个候选项", word, candidates.len());
+
+    Completion {
+        start,
+        end: cursor,
+        candidates,
+    }
+}
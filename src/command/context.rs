@@ -0,0 +1,243 @@
+use crate::auth::{validate_token, Claims};
+use crate::job::Progress;
+use crate::vfs::model::{Role, User as VfsUser};
+use log::{debug, error, warn};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::history;
+use super::output::{Record, Records};
+use super::style::{self, Style};
+
+/// 命令执行上下文，与具体的传输方式（HTTP、WebSocket、SSH、测试）无关
+pub struct CommandContext<'a> {
+    pub state: &'a crate::AppState,
+    /// 登录 token，访客为空
+    pub token: &'a str,
+    /// 验证码会话 ID，供 login/register 校验验证码
+    pub session_id: &'a str,
+    /// 当前工作目录
+    pub cwd: &'a str,
+    /// 在后台运行时的任务编号
+    pub job: Option<u64>,
+    /// 支持流式输出的前端（如 WebSocket）提供的输出通道
+    pub output: Option<&'a OutputSink>,
+    /// 是否输出样式标记，`--color=never` 时为 false
+    pub color: bool,
+    /// 是否通过 `sudo` 提权执行，提权后 `user()` 返回管理员身份
+    pub elevated: bool,
+    /// 管理员重置密码后尚未修改，只能执行修改密码等少数命令
+    pub must_change_password: bool,
+    /// 发起命令的客户端
+    pub client: &'a ClientInfo,
+}
+
+/// 发起命令的客户端，登录时记入登录历史
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    /// 接入方式：`http`、`websocket` 或 `ssh`
+    pub via: &'static str,
+    /// 客户端 IP
+    pub ip: Option<String>,
+    /// HTTP 的 User-Agent，SSH 为客户端的版本字符串
+    pub user_agent: Option<String>,
+}
+
+impl ClientInfo {
+    /// 从 HTTP 请求（包括 WebSocket 握手）中获取客户端信息
+    pub fn from_request(via: &'static str, req: &actix_web::HttpRequest) -> Self {
+        Self {
+            via,
+            ip: req
+                .connection_info()
+                .realip_remote_addr()
+                .map(|addr| addr.to_string()),
+            user_agent: req
+                .headers()
+                .get("User-Agent")
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string()),
+        }
+    }
+}
+
+/// 流式输出通道，命令执行过程中逐段发送 stdout
+///
+/// 发送过的内容仍需放入最终的 `CommandResult`，供不支持流式输出的前端使用。
+pub type OutputSink = UnboundedSender<String>;
+
+impl CommandContext<'_> {
+    /// 校验 token，返回当前用户的 claims
+    pub fn claims(&self) -> Result<Claims, CommandResult> {
+        let claims = validate_token(self.token).map_err(|_| {
+            warn!("未登录");
+            CommandResult::unauthorized("请先登录")
+        })?;
+        if self.state.auth_manager.is_token_blacklisted(self.token) {
+            debug!("Token 已失效");
+            return Err(CommandResult::unauthorized("Token 已失效"));
+        }
+        Ok(claims)
+    }
+
+    /// 报告执行进度，只对后台任务生效，`jobs` 命令会显示最新的进度
+    pub fn progress(&self, current: u64, total: Option<u64>, message: impl Into<String>) {
+        if let Some(id) = self.job {
+            let progress = Progress {
+                current,
+                total,
+                message: message.into(),
+            };
+            self.state.job_manager.set_progress(self.token, id, progress);
+        }
+    }
+
+    /// 为文本加上样式标记，关闭颜色时原样返回
+    pub fn paint(&self, style: Style, text: &str) -> String {
+        if self.color {
+            style::paint(style, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// 校验 token 并从数据库查询当前用户的 VFS 身份，管理员未提权时按作者处理
+    pub async fn user(&self) -> Result<VfsUser, CommandResult> {
+        let claims = self.claims()?;
+        match sqlx::query!("SELECT username, role FROM users WHERE id = $1", claims.sub)
+            .fetch_optional(&self.state.db)
+            .await
+        {
+            Ok(Some(rec)) => Ok(VfsUser {
+                id: claims.sub,
+                username: rec.username,
+                roles: vec![Role::from_account(&rec.role, self.elevated)],
+                session: Some(self.token.to_string()),
+            }),
+            Ok(None) => {
+                error!("用户不存在");
+                Err(CommandResult::unauthorized("用户不存在"))
+            }
+            Err(e) => {
+                error!("数据库查询错误: {}", e);
+                Err(CommandResult::internal())
+            }
+        }
+    }
+}
+
+/// 命令失败的类别，由各前端映射为自己的状态（如 HTTP 状态码）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    InvalidInput,
+    Unauthorized,
+    Forbidden,
+    TooManyRequests,
+    Internal,
+}
+
+/// 命令对会话产生的副作用
+///
+/// 副作用已由命令自身完成，前端据此更新提示符、保存或清除 token。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum SideEffect {
+    ChangeDir(String),
+    SetToken(String),
+    ClearToken,
+    ClearScreen,
+    /// 需要用户输入密码（如 `sudo`），值为提示文字；前端读取密码后带 `--password` 重新执行
+    PromptPassword(String),
+}
+
+/// 结构化的命令执行结果
+#[derive(Debug, Default)]
+pub struct CommandResult {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+    pub data: Option<Value>,
+    /// 类型化的记录，供 `--output` 渲染
+    pub records: Option<Box<Records>>,
+    pub error: Option<ErrorKind>,
+    pub effects: Vec<SideEffect>,
+}
+
+impl CommandResult {
+    /// 成功执行，输出 `stdout`
+    pub fn ok(stdout: impl Into<String>) -> Self {
+        Self {
+            stdout: stdout.into(),
+            ..Default::default()
+        }
+    }
+
+    /// 执行失败，`message` 写入 stderr
+    pub fn error(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            stderr: message.into(),
+            exit_code: history::EXIT_FAILURE,
+            error: Some(kind),
+            ..Default::default()
+        }
+    }
+
+    /// 参数错误或操作无法完成
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::error(ErrorKind::InvalidInput, message)
+    }
+
+    /// 未登录或登录已失效
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::error(ErrorKind::Unauthorized, message)
+    }
+
+    /// 没有权限
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::error(ErrorKind::Forbidden, message)
+    }
+
+    /// 服务器内部错误，不向用户暴露细节
+    pub fn internal() -> Self {
+        Self::error(ErrorKind::Internal, "服务器内部错误")
+    }
+
+    /// 附加结构化数据
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// 附加类型化的记录
+    pub fn with_records<R: Record>(mut self, records: &[R]) -> Self {
+        self.records = Some(Box::new(Records::new(records)));
+        self
+    }
+
+    /// 附加副作用
+    pub fn with_effect(mut self, effect: SideEffect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+
+    /// 指定退出状态
+    pub fn with_exit_code(mut self, exit_code: i32) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    pub fn success(&self) -> bool {
+        self.exit_code == history::EXIT_SUCCESS && self.error.is_none()
+    }
+
+    /// 合并 stdout 与 stderr，用于只有一个输出通道的前端
+    pub fn message(&self) -> String {
+        match (self.stdout.is_empty(), self.stderr.is_empty()) {
+            (_, true) => self.stdout.clone(),
+            (true, false) => self.stderr.clone(),
+            (false, false) => format!("{}\n{}", self.stdout, self.stderr),
+        }
+    }
+}
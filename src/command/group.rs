@@ -0,0 +1,115 @@
+use super::output::Record;
+use super::{CommandContext, CommandResult};
+use crate::vfs::model::{Role, User as VfsUser};
+use log::{error, warn};
+use serde::Serialize;
+use sqlx::PgPool;
+
+/// 用户所属的组
+#[derive(Debug, Serialize)]
+pub struct Membership {
+    pub id: i32,
+    pub name: String,
+    /// 是否为组管理员，组管理员可以增删组成员
+    pub admin: bool,
+}
+
+impl Record for Membership {
+    const COLUMNS: &'static [&'static str] = &["id", "name", "admin"];
+}
+
+/// 组名验证：以字母开头，1-32 个字母、数字、下划线或连字符
+pub fn validate_group_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > 32 {
+        return Err("组名长度必须为1-32个字符".to_string());
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err("组名必须以字母开头".to_string());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err("组名只能包含字母、数字、下划线和连字符".to_string());
+    }
+    Ok(())
+}
+
+/// 查询用户所属的组，按组名排序
+pub async fn user_groups(db: &PgPool, user_id: &str) -> Result<Vec<Membership>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT g.id, g.name, m.is_admin
+        FROM user_group_members m
+        JOIN user_groups g ON g.id = m.group_id
+        WHERE m.user_id = $1
+        ORDER BY g.name
+        "#,
+        user_id
+    )
+    .fetch_all(db)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| Membership {
+            id: row.id,
+            name: row.name,
+            admin: row.is_admin,
+        })
+        .collect())
+}
+
+/// 按组名查询组 ID，组不存在时返回错误结果
+pub async fn find_group(
+    ctx: &CommandContext<'_>,
+    command: &str,
+    name: &str,
+) -> Result<i32, CommandResult> {
+    match sqlx::query_scalar!("SELECT id FROM user_groups WHERE name = $1", name)
+        .fetch_optional(&ctx.state.db)
+        .await
+    {
+        Ok(Some(id)) => Ok(id),
+        Ok(None) => Err(CommandResult::invalid(format!(
+            "{}: 组 {} 不存在",
+            command, name
+        ))),
+        Err(e) => {
+            error!("数据库查询错误: {}", e);
+            Err(CommandResult::internal())
+        }
+    }
+}
+
+/// 要求当前用户是站点管理员（通过 `sudo` 提权）或该组的管理员
+pub async fn require_group_admin(
+    ctx: &CommandContext<'_>,
+    command: &str,
+    user: &VfsUser,
+    group_id: i32,
+) -> Result<(), CommandResult> {
+    if user.roles.contains(&Role::Admin) {
+        return Ok(());
+    }
+    match sqlx::query_scalar!(
+        "SELECT is_admin FROM user_group_members WHERE user_id = $1 AND group_id = $2",
+        user.id,
+        group_id
+    )
+    .fetch_optional(&ctx.state.db)
+    .await
+    {
+        Ok(Some(true)) => Ok(()),
+        Ok(_) => {
+            warn!("用户 {} 不是组 {} 的管理员", user.username, group_id);
+            Err(CommandResult::forbidden(format!(
+                "{}: 只有组管理员或站点管理员可以管理组成员",
+                command
+            )))
+        }
+        Err(e) => {
+            error!("数据库查询错误: {}", e);
+            Err(CommandResult::internal())
+        }
+    }
+}
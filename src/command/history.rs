@@ -0,0 +1,275 @@
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use sqlx::PgPool;
+
+// 命令执行状态码
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_NOT_FOUND: i32 = 127;
+
+// 整行都不记录的命令：参数中包含密码，且重新执行没有意义
+const UNRECORDED_COMMANDS: [&str; 2] = ["login", "register"];
+
+/// 命令是否记入历史，`name` 为别名展开后的命令名
+pub fn is_recorded(name: &str) -> bool {
+    !UNRECORDED_COMMANDS.contains(&name)
+}
+
+// 历史记录条目
+#[derive(Debug, serde::Serialize)]
+pub struct HistoryEntry {
+    pub number: i64,
+    pub command: String,
+    pub cwd: String,
+    pub exit_status: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 记录一条已执行的命令，调用方应先用 `CommandRegistry::redact` 隐去密码
+pub async fn record(db: &PgPool, user_id: &str, command: &str, cwd: &str, exit_status: i32) {
+    debug!("记录命令历史: {} (状态: {})", command, exit_status);
+    if let Err(e) = sqlx::query!(
+        r#"
+        INSERT INTO command_history (user_id, command, cwd, exit_status)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        command,
+        cwd,
+        exit_status
+    )
+    .execute(db)
+    .await
+    {
+        error!("记录命令历史失败: {}", e);
+    }
+}
+
+/// 查询最近的 `limit` 条历史，按时间正序返回，编号从 1 开始
+pub async fn recent(
+    db: &PgPool,
+    user_id: &str,
+    limit: Option<i64>,
+) -> Result<Vec<HistoryEntry>, sqlx::Error> {
+    let mut entries = sqlx::query_as!(
+        HistoryEntry,
+        r#"
+        SELECT number AS "number!", command, cwd, exit_status, created_at
+        FROM (
+            SELECT id, ROW_NUMBER() OVER (ORDER BY id) AS number,
+                   command, cwd, exit_status, created_at
+            FROM command_history
+            WHERE user_id = $1
+        ) h
+        ORDER BY id DESC
+        LIMIT $2
+        "#,
+        user_id,
+        limit
+    )
+    .fetch_all(db)
+    .await?;
+    entries.reverse();
+    Ok(entries)
+}
+
+/// 清空用户的命令历史
+pub async fn clear(db: &PgPool, user_id: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!("DELETE FROM command_history WHERE user_id = $1", user_id)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// 展开命令行中的历史引用：`!!`、`!n`、`!-n` 与 `!prefix`
+///
+/// 单引号内以及后跟空白、`=` 或行尾的 `!` 保持原样。没有任何引用时返回 `None`。
+pub async fn expand_history(
+    db: &PgPool,
+    user_id: &str,
+    line: &str,
+) -> Result<Option<String>, String> {
+    if !line.contains('!') {
+        return Ok(None);
+    }
+
+    let mut result = String::new();
+    let mut expanded = false;
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' if !in_double => {
+                in_single = !in_single;
+                result.push(c);
+            }
+            '"' if !in_single => {
+                in_double = !in_double;
+                result.push(c);
+            }
+            '\\' if !in_single => {
+                result.push(c);
+                if let Some(next) = chars.next() {
+                    result.push(next);
+                }
+            }
+            '!' if !in_single => {
+                // 收集事件标识符
+                let designator = match chars.peek() {
+                    Some('!') => {
+                        chars.next();
+                        "!".to_string()
+                    }
+                    Some(&next) if !next.is_whitespace() && next != '=' && next != '"' => {
+                        let mut word = String::new();
+                        while let Some(&ch) = chars.peek() {
+                            if ch.is_whitespace() || matches!(ch, '\'' | '"' | ';') {
+                                break;
+                            }
+                            word.push(ch);
+                            chars.next();
+                        }
+                        word
+                    }
+                    _ => {
+                        result.push('!');
+                        continue;
+                    }
+                };
+
+                let command = lookup_event(db, user_id, &designator)
+                    .await
+                    .map_err(|e| {
+                        error!("查询命令历史失败: {}", e);
+                        "服务器内部错误".to_string()
+                    })?
+                    .ok_or_else(|| format!("!{}: 未找到事件", designator))?;
+                result.push_str(&command);
+                expanded = true;
+            }
+            _ => result.push(c),
+        }
+    }
+
+    Ok(if expanded { Some(result) } else { None })
+}
+
+// 根据事件标识符查询历史命令
+async fn lookup_event(
+    db: &PgPool,
+    user_id: &str,
+    designator: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    // `!!` 等价于 `!-1`
+    let designator = if designator == "!" { "-1" } else { designator };
+
+    if let Ok(n) = designator.parse::<i64>() {
+        // `!-n` 取倒数第 n 条，取反溢出（如 `!-9223372036854775808`）时按未找到处理
+        let back = n.checked_neg().filter(|back| *back > 0);
+        let record = if n > 0 {
+            sqlx::query_scalar!(
+                r#"
+                SELECT command FROM command_history
+                WHERE user_id = $1
+                ORDER BY id
+                OFFSET $2 LIMIT 1
+                "#,
+                user_id,
+                n - 1
+            )
+            .fetch_optional(db)
+            .await?
+        } else if let Some(back) = back {
+            sqlx::query_scalar!(
+                r#"
+                SELECT command FROM command_history
+                WHERE user_id = $1
+                ORDER BY id DESC
+                OFFSET $2 LIMIT 1
+                "#,
+                user_id,
+                back - 1
+            )
+            .fetch_optional(db)
+            .await?
+        } else {
+            None
+        };
+        return Ok(record);
+    }
+
+    sqlx::query_scalar!(
+        r#"
+        SELECT command FROM command_history
+        WHERE user_id = $1 AND LEFT(command, LENGTH($2)) = $2
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+        user_id,
+        designator
+    )
+    .fetch_optional(db)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn login_and_register_are_not_recorded() {
+        assert!(!is_recorded("login"));
+        assert!(!is_recorded("register"));
+        assert!(is_recorded("passwd"));
+        assert!(is_recorded("ls"));
+    }
+
+    #[tokio::test]
+    async fn history_is_numbered_and_expanded() {
+        let Some(state) = crate::testing::state().await else {
+            return;
+        };
+        let db = state.db.clone();
+        let user_id = uuid::Uuid::new_v4().to_string();
+        for command in ["ls -a", "cd Documents", "cat post.md", "ls -l"] {
+            record(&db, &user_id, command, "/home/test", EXIT_SUCCESS).await;
+        }
+
+        let entries = recent(&db, &user_id, Some(2)).await.unwrap();
+        let listed: Vec<(i64, &str)> = entries
+            .iter()
+            .map(|entry| (entry.number, entry.command.as_str()))
+            .collect();
+        assert_eq!(listed, [(3, "cat post.md"), (4, "ls -l")]);
+
+        let expand = |line: &'static str| {
+            let db = db.clone();
+            let user_id = user_id.clone();
+            async move { expand_history(&db, &user_id, line).await }
+        };
+        assert_eq!(expand("!!").await, Ok(Some("ls -l".to_string())));
+        assert_eq!(
+            expand("!2 && !-2").await,
+            Ok(Some("cd Documents && cat post.md".to_string()))
+        );
+        assert_eq!(expand("sudo !ls").await, Ok(Some("sudo ls -l".to_string())));
+        assert_eq!(
+            expand("!cd\"Documents\"").await,
+            Ok(Some("cd Documents\"Documents\"".to_string()))
+        );
+        // 单引号内、后跟空白或 = 的 ! 不是历史引用
+        assert_eq!(expand("echo '!!' ! a != b").await, Ok(None));
+        assert_eq!(expand("!9").await, Err("!9: 未找到事件".to_string()));
+        assert_eq!(expand("!rm").await, Err("!rm: 未找到事件".to_string()));
+        assert_eq!(expand("!-0").await, Err("!-0: 未找到事件".to_string()));
+        assert_eq!(
+            expand("!-9223372036854775808").await,
+            Err("!-9223372036854775808: 未找到事件".to_string())
+        );
+
+        assert_eq!(clear(&db, &user_id).await.unwrap(), 4);
+        assert_eq!(expand("!!").await, Err("!!: 未找到事件".to_string()));
+    }
+}
@@ -0,0 +1,80 @@
+use super::ClientInfo;
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use sqlx::PgPool;
+
+// 记录的用户名的最大长度，失败的登录可能使用任意长的用户名
+const MAX_USERNAME_LEN: usize = 64;
+
+/// 登录历史中的一条记录
+#[derive(Debug, serde::Serialize)]
+pub struct LoginRecord {
+    pub username: String,
+    pub success: bool,
+    /// 认证方式：`password` 或 `publickey`
+    pub method: String,
+    /// 接入方式：`http`、`websocket` 或 `ssh`
+    pub via: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 记录一次登录，`user_id` 为 None 表示用户不存在；记录失败只写日志，不影响登录
+pub async fn record(
+    db: &PgPool,
+    user_id: Option<&str>,
+    username: &str,
+    method: &str,
+    client: &ClientInfo,
+    success: bool,
+) {
+    let username: String = username.chars().take(MAX_USERNAME_LEN).collect();
+    debug!(
+        "记录登录历史: {} {} {} (成功: {})",
+        username, method, client.via, success
+    );
+    if let Err(e) = sqlx::query!(
+        r#"
+        INSERT INTO login_history (user_id, username, success, method, via, ip, user_agent)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        user_id,
+        username,
+        success,
+        method,
+        client.via,
+        client.ip,
+        client.user_agent
+    )
+    .execute(db)
+    .await
+    {
+        error!("记录登录历史失败: {}", e);
+    }
+}
+
+/// 查询最近的 `limit` 条登录记录，按时间倒序返回；`username` 为 None 时查询所有用户
+pub async fn recent(
+    db: &PgPool,
+    username: Option<&str>,
+    failed_only: bool,
+    limit: i64,
+) -> Result<Vec<LoginRecord>, sqlx::Error> {
+    sqlx::query_as!(
+        LoginRecord,
+        r#"
+        SELECT username, success, method, via, ip, user_agent, created_at
+        FROM login_history
+        WHERE ($1::VARCHAR IS NULL OR username = $1)
+            AND (NOT $2 OR NOT success)
+        ORDER BY id DESC
+        LIMIT $3
+        "#,
+        username,
+        failed_only,
+        limit
+    )
+    .fetch_all(db)
+    .await
+}
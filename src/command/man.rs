@@ -0,0 +1,261 @@
+use super::args::CommandSpec;
+use crate::vfs::model::{Role, User as VfsUser, VfsError};
+use log::{debug, warn};
+use std::collections::BTreeMap;
+
+/// 运维人员放置 Markdown 手册页的 VFS 目录，文件名为 `<页面名>.md`
+pub const MAN_DIR: &str = "/usr/share/man";
+
+// 手册页各节的顺序，未列出的节放在 SEE ALSO 之前
+const SECTION_ORDER: &[&str] = &["NAME", "SYNOPSIS", "DESCRIPTION", "OPTIONS", "EXAMPLES"];
+
+struct Section {
+    title: String,
+    lines: Vec<String>,
+}
+
+impl Section {
+    fn new(title: &str, lines: Vec<String>) -> Self {
+        Section {
+            title: title.to_string(),
+            lines,
+        }
+    }
+
+    fn rank(&self) -> usize {
+        match SECTION_ORDER.iter().position(|t| *t == self.title) {
+            Some(rank) => rank,
+            None if self.title == "SEE ALSO" => SECTION_ORDER.len() + 1,
+            None => SECTION_ORDER.len(),
+        }
+    }
+}
+
+// 手册页是公开文档，以系统身份读取
+fn system_user() -> VfsUser {
+    VfsUser {
+        id: "system".to_string(),
+        username: "system".to_string(),
+        roles: vec![Role::Admin],
+        session: None,
+    }
+}
+
+// 页面名只能是单个文件名，避免以系统身份读取其他目录
+fn is_valid_page_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// 由参数规格生成的各节
+fn spec_sections(spec: &CommandSpec) -> Vec<Section> {
+    let mut sections = vec![
+        Section::new("NAME", vec![format!("{} - {}", spec.name, spec.summary)]),
+        Section::new("SYNOPSIS", vec![spec.usage()]),
+    ];
+
+    let (arguments, options) = spec.table();
+    sections.push(Section::new("OPTIONS", arguments.into_iter().chain(options).collect()));
+
+    if !spec.examples.is_empty() {
+        let mut lines = Vec::new();
+        for (i, (command, explanation)) in spec.examples.iter().enumerate() {
+            if i > 0 {
+                lines.push(String::new());
+            }
+            lines.push(command.to_string());
+            lines.push(format!("    {}", explanation));
+        }
+        sections.push(Section::new("EXAMPLES", lines));
+    }
+    if !spec.see_also.is_empty() {
+        sections.push(Section::new("SEE ALSO", vec![spec.see_also.join(", ")]));
+    }
+    sections
+}
+
+/// 解析 Markdown 手册页：标题行开始新的一节，第一个标题之前的内容归入 DESCRIPTION，
+/// 代码块保留内容、去掉围栏
+fn markdown_sections(text: &str) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if let Some(title) = line.strip_prefix('#').filter(|_| !in_code) {
+            let title = title.trim_start_matches('#').trim().to_uppercase();
+            sections.push(Section::new(&title, Vec::new()));
+            continue;
+        }
+        if sections.is_empty() {
+            if line.is_empty() {
+                continue;
+            }
+            sections.push(Section::new("DESCRIPTION", Vec::new()));
+        }
+        if let Some(section) = sections.last_mut() {
+            section.lines.push(line.to_string());
+        }
+    }
+
+    // 去掉每节首尾的空行
+    for section in &mut sections {
+        while section.lines.last().is_some_and(|l| l.is_empty()) {
+            section.lines.pop();
+        }
+        let leading = section.lines.iter().take_while(|l| l.is_empty()).count();
+        section.lines.drain(..leading);
+    }
+    sections
+}
+
+// 同名的节追加内容，其余的节加入页面
+fn merge(sections: &mut Vec<Section>, extra: Vec<Section>) {
+    for section in extra {
+        match sections.iter_mut().find(|s| s.title == section.title) {
+            Some(existing) => {
+                existing.lines.push(String::new());
+                existing.lines.extend(section.lines);
+            }
+            None => sections.push(section),
+        }
+    }
+    sections.sort_by_key(Section::rank);
+}
+
+fn render(sections: &[Section]) -> String {
+    sections
+        .iter()
+        .map(|section| {
+            let mut lines = vec![section.title.clone()];
+            lines.extend(section.lines.iter().map(|line| {
+                if line.is_empty() {
+                    String::new()
+                } else {
+                    format!("    {}", line)
+                }
+            }));
+            lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// 读取 `MAN_DIR` 下的 Markdown 手册页，不存在时返回 `None`
+async fn read_markdown(state: &crate::AppState, name: &str) -> Option<String> {
+    if !is_valid_page_name(name) {
+        return None;
+    }
+    let path = format!("{}/{}.md", MAN_DIR, name);
+    match state.vfs_manager.read_file(&system_user(), &path, "/").await {
+        Ok(text) => Some(text),
+        Err(VfsError::NodeNotFound(_)) => None,
+        Err(e) => {
+            warn!("读取手册页失败: {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// 列出 `MAN_DIR` 下的 Markdown 手册页名称
+async fn markdown_pages(state: &crate::AppState) -> Vec<String> {
+    match state.vfs_manager.list_dir(&system_user(), MAN_DIR, "/").await {
+        Ok(nodes) => nodes
+            .into_iter()
+            .filter(|node| !node.is_dir)
+            .filter_map(|node| {
+                let file = node.name.rsplit('/').next()?.to_string();
+                file.strip_suffix(".md").map(|name| name.to_string())
+            })
+            .collect(),
+        Err(e) => {
+            debug!("读取手册页目录失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 生成手册页：已注册命令由参数规格生成，再合并同名的 Markdown 页面；
+/// 只有 Markdown 页面的主题（如 `man scripting`）直接渲染该页面
+pub async fn page(state: &crate::AppState, name: &str) -> Option<String> {
+    let spec = state.command_registry.get_handler(name).map(|h| h.spec());
+    let markdown = read_markdown(state, name).await;
+
+    let mut sections = match spec {
+        Some(spec) => spec_sections(spec),
+        None if markdown.is_some() => Vec::new(),
+        None => return None,
+    };
+    if let Some(text) = markdown {
+        merge(&mut sections, markdown_sections(&text));
+    }
+    if !sections.iter().any(|s| s.title == "NAME") {
+        merge(&mut sections, vec![Section::new("NAME", vec![name.to_string()])]);
+    }
+    Some(render(&sections))
+}
+
+// Markdown 页面的简介：NAME 节的第一行，否则取第一行正文
+fn markdown_summary(name: &str, text: &str) -> String {
+    let sections = markdown_sections(text);
+    let line = sections
+        .iter()
+        .find(|s| s.title == "NAME")
+        .or_else(|| sections.first())
+        .and_then(|s| s.lines.iter().find(|l| !l.is_empty()))
+        .cloned()
+        .unwrap_or_default();
+    // 去掉 NAME 行中重复的页面名
+    match line.strip_prefix(name) {
+        Some(rest) => rest.trim_start_matches([' ', '-']).to_string(),
+        None => line,
+    }
+}
+
+/// 按关键字搜索手册页（`man -k`），不区分大小写，返回 `名称 - 简介` 列表
+///
+/// 命令匹配名称、简介和选项说明，Markdown 页面匹配名称和全文。
+pub async fn search(state: &crate::AppState, keyword: &str) -> Vec<String> {
+    let keyword = keyword.to_lowercase();
+    let mut results = BTreeMap::new();
+
+    for handler in state.command_registry.commands.values() {
+        let spec = handler.spec();
+        let (arguments, options) = spec.table();
+        let text = [spec.name.to_string(), spec.summary.to_string()]
+            .into_iter()
+            .chain(arguments)
+            .chain(options)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if text.to_lowercase().contains(&keyword) {
+            results.insert(spec.name.to_string(), spec.summary.to_string());
+        }
+    }
+
+    for name in markdown_pages(state).await {
+        let text = match read_markdown(state, &name).await {
+            Some(text) => text,
+            None => continue,
+        };
+        if !name.to_lowercase().contains(&keyword) && !text.to_lowercase().contains(&keyword) {
+            continue;
+        }
+        let summary = match state.command_registry.get_handler(&name) {
+            Some(handler) => handler.spec().summary.to_string(),
+            None => markdown_summary(&name, &text),
+        };
+        results.entry(name).or_insert(summary);
+    }
+
+    results
+        .into_iter()
+        .map(|(name, summary)| format!("{} - {}", name, summary))
+        .collect()
+}
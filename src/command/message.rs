@@ -0,0 +1,90 @@
+use crate::ws::Event;
+use chrono::{DateTime, Local, Utc};
+use sqlx::PgPool;
+
+/// 单条消息的最大长度（字符数）
+pub const MAX_MESSAGE_LEN: usize = 500;
+
+/// 把消息事件格式化为终端中显示的一行，其他事件返回 None
+pub fn render(event: &Event) -> Option<String> {
+    match event {
+        Event::Message {
+            from,
+            text,
+            broadcast,
+            sent_at,
+        } => {
+            let time = sent_at.with_timezone(&Local).format("%m-%d %H:%M");
+            let kind = if *broadcast { "公告" } else { "消息" };
+            Some(format!("[{} 来自 {} 的{}] {}", time, from, kind, text))
+        }
+        _ => None,
+    }
+}
+
+/// 保存发给离线用户的消息，下次登录时显示
+pub async fn queue(
+    db: &PgPool,
+    recipient_id: &str,
+    sender: &str,
+    body: &str,
+    sent_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO messages (recipient_id, sender, body, created_at) VALUES ($1, $2, $3, $4)",
+        recipient_id,
+        sender,
+        body,
+        sent_at
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// 为 `skipped` 之外的所有未锁定用户保存公告，返回保存的条数
+pub async fn queue_broadcast(
+    db: &PgPool,
+    skipped: &[String],
+    sender: &str,
+    body: &str,
+    sent_at: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO messages (recipient_id, sender, body, broadcast, created_at)
+        SELECT id, $2, $3, TRUE, $4 FROM users
+        WHERE NOT locked AND id <> ALL($1)
+        "#,
+        skipped,
+        sender,
+        body,
+        sent_at
+    )
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// 取出并删除用户尚未收到的消息，按发送顺序排列
+pub async fn take_pending(db: &PgPool, user_id: &str) -> Result<Vec<Event>, sqlx::Error> {
+    let mut rows = sqlx::query!(
+        r#"
+        DELETE FROM messages WHERE recipient_id = $1
+        RETURNING id, sender, body, broadcast, created_at
+        "#,
+        user_id
+    )
+    .fetch_all(db)
+    .await?;
+    rows.sort_by_key(|row| row.id);
+    Ok(rows
+        .into_iter()
+        .map(|row| Event::Message {
+            from: row.sender,
+            text: row.body,
+            broadcast: row.broadcast,
+            sent_at: row.created_at,
+        })
+        .collect())
+}
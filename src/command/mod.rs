@@ -1,33 +1,100 @@
 use actix_web::{web, HttpResponse, Responder};
 use async_trait::async_trait;
 use log::{debug, error, info, warn};
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::Arc;
 
+use crate::auth::{login_status, validate_token};
+use crate::job::{JobOutput, JobStatus};
+use crate::session::Session;
+use crate::vfs::model::{Role, User as VfsUser};
+use crate::ws::Event;
+use output::OutputFormat;
+
+mod account;
+pub mod alias;
+pub mod args;
+mod cmd_alias;
+mod cmd_cat;
 mod cmd_cd;
+mod cmd_chmod;
 mod cmd_clear;
+mod cmd_dirs;
+mod cmd_echo;
+mod cmd_env;
+mod cmd_export;
+mod cmd_fg;
+mod cmd_gpasswd;
+mod cmd_groupadd;
+mod cmd_groupdel;
+mod cmd_groups;
 mod cmd_help;
+mod cmd_history;
 mod cmd_id;
+mod cmd_jobs;
+mod cmd_kill;
+mod cmd_last;
 mod cmd_login;
 mod cmd_logout;
 mod cmd_ls;
+mod cmd_man;
+mod cmd_mesg;
+mod cmd_meta;
 mod cmd_mkdir;
+mod cmd_passwd;
+mod cmd_popd;
 mod cmd_profile;
+mod cmd_publish;
+mod cmd_pushd;
 mod cmd_pwd;
 mod cmd_register;
+mod cmd_sh;
+mod cmd_sudo;
+mod cmd_test;
+mod cmd_unalias;
+mod cmd_unpublish;
+mod cmd_unset;
+mod cmd_useradd;
+mod cmd_userdel;
+mod cmd_usermod;
+mod cmd_w;
+mod cmd_wait;
+mod cmd_wall;
+mod cmd_who;
+mod cmd_write;
+pub mod complete;
+mod context;
+mod group;
+pub mod history;
+pub mod login_history;
+mod man;
+pub mod message;
+pub mod output;
+pub mod parser;
+mod post;
+mod script;
+pub mod sessions;
+pub mod style;
+
+pub use args::CommandSpec;
+pub use context::{ClientInfo, CommandContext, CommandResult, ErrorKind, OutputSink, SideEffect};
 
 // 命令处理器的trait
 #[async_trait]
 pub trait CommandHandler: Send + Sync {
-    fn name(&self) -> &'static str;
-    fn description(&self) -> &'static str;
-    async fn handle(
-        &self,
-        args: &[&str],
-        data: &web::Data<crate::AppState>,
-        session_id: &str,
-        cwd: &str,
-    ) -> HttpResponse;
+    /// 命令的参数规格，用于参数解析、`--help` 和补全
+    fn spec(&self) -> &'static CommandSpec;
+
+    fn name(&self) -> &'static str {
+        self.spec().name
+    }
+
+    fn description(&self) -> &'static str {
+        self.spec().summary
+    }
+
+    async fn handle(&self, args: &[&str], ctx: &CommandContext<'_>) -> CommandResult;
 }
 
 // 命令注册器
@@ -53,6 +120,44 @@ impl CommandRegistry {
         registry.register(Box::new(cmd_pwd::PwdCommand::new()));
         registry.register(Box::new(cmd_mkdir::MkdirCommand::new()));
         registry.register(Box::new(cmd_cd::CdCommand::new()));
+        registry.register(Box::new(cmd_export::ExportCommand::new()));
+        registry.register(Box::new(cmd_unset::UnsetCommand::new()));
+        registry.register(Box::new(cmd_env::EnvCommand::new()));
+        registry.register(Box::new(cmd_pushd::PushdCommand::new()));
+        registry.register(Box::new(cmd_popd::PopdCommand::new()));
+        registry.register(Box::new(cmd_dirs::DirsCommand::new()));
+        registry.register(Box::new(cmd_alias::AliasCommand::new()));
+        registry.register(Box::new(cmd_unalias::UnaliasCommand::new()));
+        registry.register(Box::new(cmd_history::HistoryCommand::new()));
+        registry.register(Box::new(cmd_echo::EchoCommand::new()));
+        registry.register(Box::new(cmd_cat::CatCommand::new()));
+        registry.register(Box::new(cmd_chmod::ChmodCommand::new()));
+        registry.register(Box::new(cmd_sh::ShCommand::new()));
+        registry.register(Box::new(cmd_test::TestCommand::new()));
+        registry.register(Box::new(cmd_test::TestCommand::bracket()));
+        registry.register(Box::new(cmd_man::ManCommand::new()));
+        registry.register(Box::new(cmd_jobs::JobsCommand::new()));
+        registry.register(Box::new(cmd_wait::WaitCommand::new()));
+        registry.register(Box::new(cmd_fg::FgCommand::new()));
+        registry.register(Box::new(cmd_kill::KillCommand::new()));
+        registry.register(Box::new(cmd_sudo::SudoCommand::new()));
+        registry.register(Box::new(cmd_passwd::PasswdCommand::new()));
+        registry.register(Box::new(cmd_useradd::UseraddCommand::new()));
+        registry.register(Box::new(cmd_userdel::UserdelCommand::new()));
+        registry.register(Box::new(cmd_usermod::UsermodCommand::new()));
+        registry.register(Box::new(cmd_groupadd::GroupaddCommand::new()));
+        registry.register(Box::new(cmd_groupdel::GroupdelCommand::new()));
+        registry.register(Box::new(cmd_gpasswd::GpasswdCommand::new()));
+        registry.register(Box::new(cmd_groups::GroupsCommand::new()));
+        registry.register(Box::new(cmd_write::WriteCommand::new()));
+        registry.register(Box::new(cmd_wall::WallCommand::new()));
+        registry.register(Box::new(cmd_mesg::MesgCommand::new()));
+        registry.register(Box::new(cmd_last::LastCommand::new()));
+        registry.register(Box::new(cmd_who::WhoCommand::new()));
+        registry.register(Box::new(cmd_w::WCommand::new()));
+        registry.register(Box::new(cmd_publish::PublishCommand::new()));
+        registry.register(Box::new(cmd_unpublish::UnpublishCommand::new()));
+        registry.register(Box::new(cmd_meta::MetaCommand::new()));
 
         info!("命令注册器初始化完成");
         registry
@@ -67,6 +172,26 @@ impl CommandRegistry {
     pub fn get_handler(&self, command_name: &str) -> Option<&dyn CommandHandler> {
         self.commands.get(command_name).map(|h| h.as_ref())
     }
+
+    /// 隐去命令行中规格标记为机密的参数，用于历史记录、任务列表和日志
+    ///
+    /// 只替换机密参数在原文中的部分，其余内容保持不变；未知命令原样返回。
+    pub fn redact(&self, line: &str) -> String {
+        let words = parser::split_spans(line);
+        let args: Vec<&str> = words.iter().map(|(_, word)| word.as_str()).collect();
+        let spec = match args.first().and_then(|name| self.get_handler(name)) {
+            Some(handler) => handler.spec(),
+            None => return line.to_string(),
+        };
+        let lookup = |name: &str| self.get_handler(name).map(|handler| handler.spec());
+
+        // 从后向前替换，前面参数的位置不受影响
+        let mut redacted = line.to_string();
+        for (index, text) in spec.secrets(&args, &lookup).into_iter().rev() {
+            redacted.replace_range(words[index].0.clone(), &text);
+        }
+        redacted
+    }
 }
 
 // 命令响应结构体
@@ -75,6 +200,388 @@ pub struct CommandResponse {
     pub success: bool,
     pub message: String,
     pub data: Option<Value>,
+    /// 命令的退出状态，供脚本化的客户端（如 tblog）使用
+    pub exit_code: i32,
+    /// 需要客户端处理的副作用，如登录后保存 token、清屏
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<SideEffect>,
+    /// `message` 按样式拆分的片段，没有样式时省略
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<style::Span>,
+}
+
+/// 由命令规格认领的全局选项，在分发前从参数中取出
+#[derive(Debug, Clone, Copy)]
+struct GlobalOptions {
+    /// `--color=never` 时为 false
+    color: bool,
+    /// `--output` 指定的输出格式
+    format: Option<OutputFormat>,
+}
+
+impl GlobalOptions {
+    /// 只在实际执行的命令认领时取出，且只识别选项范围内的参数，其余参数原样保留；
+    /// `sudo` 之后的命令按其自身的规格处理
+    fn take(words: &mut Vec<String>, registry: &CommandRegistry) -> Result<Self, String> {
+        let mut options = GlobalOptions {
+            color: true,
+            format: None,
+        };
+        let mut start = 0;
+        let spec = loop {
+            let args: Vec<&str> = words[start..].iter().map(String::as_str).collect();
+            let spec = match registry.get_handler(args[0]) {
+                Some(handler) => handler.spec(),
+                None => return Ok(options),
+            };
+            match spec.nested_command(&args) {
+                Some(offset) => start += offset,
+                None => break spec,
+            }
+        };
+
+        let mut rest = words.split_off(start);
+        let end = |rest: &[String]| {
+            spec.options_end(&rest.iter().map(String::as_str).collect::<Vec<_>>())
+        };
+        if spec.color {
+            let end = end(&rest);
+            options.color = style::take_color_option(&mut rest, end)?;
+        }
+        if spec.output {
+            let end = end(&rest);
+            options.format = output::take_output_option(&mut rest, end)?;
+        }
+        words.append(&mut rest);
+        Ok(options)
+    }
+}
+
+/// 执行一行命令，与传输方式无关
+///
+/// 依次完成会话校验、历史展开、参数拆分、别名展开和命令分发，并为已登录用户记录历史。
+/// HTTP、WebSocket、SSH 等前端只需提供 token 和验证码会话 ID，再把结果转换为各自的输出格式。
+/// 以 `&` 结尾的命令在后台任务中执行，立即返回任务编号。
+/// 提供 `output` 的前端会在执行过程中收到流式的 stdout。
+pub async fn execute(
+    state: &Arc<crate::AppState>,
+    command: &str,
+    token: &str,
+    session_id: &str,
+    output: Option<&OutputSink>,
+    client: &ClientInfo,
+) -> CommandResult {
+    let claims = match validate_token(token) {
+        Ok(claims) if !state.auth_manager.is_token_blacklisted(token) => Some(claims),
+        _ => None,
+    };
+    // 每条命令查询一次账号状态：修改密码等操作会递增 token 版本，此前签发的 token 按访客处理；
+    // 查询失败时拒绝执行
+    let status = match &claims {
+        Some(claims) => match login_status(&state.db, claims).await {
+            Ok(status) => status,
+            Err(e) => {
+                error!("数据库查询错误: {}", e);
+                return CommandResult::internal();
+            }
+        },
+        None => None,
+    };
+    if let (Some(claims), Some(status)) = (&claims, &status) {
+        if status.locked {
+            warn!("用户 {} 已被锁定", claims.username);
+            return CommandResult::forbidden("账号已被锁定，请联系管理员");
+        }
+    }
+
+    // 校验服务端会话：已登录用户使用保存的 shell 状态，访客使用默认会话
+    let (session, claims) = match claims.filter(|_| status.is_some()) {
+        Some(claims) => {
+            match state
+                .session_manager
+                .validate(token, &claims, client.ip.as_deref())
+            {
+                Ok(session) => (session, Some(claims)),
+                Err(e) => {
+                    warn!("会话校验失败: {}", e);
+                    return CommandResult::unauthorized(e);
+                }
+            }
+        }
+        None => {
+            if !token.is_empty() {
+                debug!("token 无效，按访客处理");
+                state.session_manager.remove(token);
+            }
+            (Session::guest(), None)
+        }
+    };
+    let cwd = session.cwd;
+    let env = session.env;
+
+    // 展开历史引用
+    let command = match &claims {
+        Some(claims) => match history::expand_history(&state.db, &claims.sub, command).await {
+            Ok(Some(expanded)) => {
+                debug!(
+                    "历史展开: {} -> {}",
+                    state.command_registry.redact(command),
+                    state.command_registry.redact(&expanded)
+                );
+                expanded
+            }
+            Ok(None) => command.to_string(),
+            Err(e) => {
+                warn!("历史展开失败: {}", e);
+                return CommandResult::invalid(e);
+            }
+        },
+        None => command.to_string(),
+    };
+
+    // 拆分参数并展开环境变量
+    let line = parser::strip_background(&command);
+    let mut words = match parser::split_command(line.unwrap_or(&command), Some(&env)) {
+        Ok(words) => words,
+        Err(e) => {
+            warn!("命令解析失败: {}", e);
+            return CommandResult::invalid(e);
+        }
+    };
+
+    // 展开用户别名
+    if let Some(claims) = &claims {
+        let user = VfsUser {
+            id: claims.sub.clone(),
+            username: claims.username.clone(),
+            roles: vec![Role::from_account(&claims.role, false)],
+            session: None,
+        };
+        match alias::load_aliases(state, &user).await {
+            Ok(aliases) => match alias::expand_aliases(words, &aliases, &env) {
+                Ok(expanded) => words = expanded,
+                Err(e) => {
+                    warn!("别名展开失败: {}", e);
+                    return CommandResult::invalid(e);
+                }
+            },
+            Err(e) => warn!("读取别名失败: {}", e),
+        }
+    }
+    if words.is_empty() {
+        warn!("空命令");
+        return CommandResult::invalid("命令不能为空");
+    }
+    let options = match GlobalOptions::take(&mut words, &state.command_registry) {
+        Ok(options) => options,
+        Err(e) => {
+            warn!("全局选项无效: {}", e);
+            return CommandResult::invalid(e);
+        }
+    };
+
+    let ctx = CommandContext {
+        state,
+        token,
+        session_id,
+        cwd: &cwd,
+        job: None,
+        output,
+        color: true,
+        elevated: false,
+        must_change_password: status
+            .as_ref()
+            .is_some_and(|status| status.must_change_password),
+        client,
+    };
+
+    // 行尾的 & 表示放到后台运行，后台任务按 token 归属，需要登录
+    if let (Some(line), Some(claims)) = (line, &claims) {
+        let recorded = history::is_recorded(&words[0]);
+        let result = spawn_job(state, line, words, &ctx, options);
+        if recorded {
+            let command = state.command_registry.redact(&command);
+            history::record(&state.db, &claims.sub, &command, &cwd, result.exit_code).await;
+        }
+        return result;
+    }
+    if line.is_some() {
+        warn!("访客不能运行后台任务");
+        return CommandResult::unauthorized("请先登录");
+    }
+
+    let result = dispatch_with(&words, options, &ctx).await;
+    if !result.success() {
+        error!("命令执行失败: {} - 状态 {}", words[0], result.exit_code);
+    }
+
+    // 记录已登录用户的命令历史，密码替换为 ***
+    if let Some(claims) = claims.as_ref().filter(|_| history::is_recorded(&words[0])) {
+        let command = state.command_registry.redact(&command);
+        history::record(&state.db, &claims.sub, &command, &cwd, result.exit_code).await;
+    }
+
+    result
+}
+
+// 必须修改密码时仍可执行的命令
+const PASSWORD_CHANGE_COMMANDS: [&str; 4] = ["passwd", "logout", "help", "man"];
+
+/// 取出全局选项后分发命令，脚本中的每条命令都经过这里
+pub(crate) async fn run_words(mut words: Vec<String>, ctx: &CommandContext<'_>) -> CommandResult {
+    let options = match GlobalOptions::take(&mut words, &ctx.state.command_registry) {
+        Ok(options) => options,
+        Err(e) => {
+            warn!("全局选项无效: {}", e);
+            return CommandResult::invalid(e);
+        }
+    };
+    dispatch_with(&words, options, ctx).await
+}
+
+// 按全局选项分发命令并转换输出格式，外层的 `--color=never` 对内层命令同样有效
+async fn dispatch_with(
+    words: &[String],
+    options: GlobalOptions,
+    ctx: &CommandContext<'_>,
+) -> CommandResult {
+    let ctx = CommandContext {
+        color: ctx.color && options.color,
+        ..*ctx
+    };
+    let args: Vec<&str> = words.iter().map(|s| s.as_str()).collect();
+    let result = dispatch(&args, &ctx).await;
+    match options.format {
+        Some(format) => output::apply(format, args[0], result),
+        None => result,
+    }
+}
+
+// 按命令名分发到处理器或脚本
+async fn dispatch(args: &[&str], ctx: &CommandContext<'_>) -> CommandResult {
+    // 管理员重置密码后，修改密码之前只能执行少数命令
+    if ctx.must_change_password && !PASSWORD_CHANGE_COMMANDS.contains(&args[0]) {
+        warn!("需要先修改密码，拒绝执行: {}", args[0]);
+        return CommandResult::forbidden("管理员已重置你的密码，请先用 passwd 修改密码");
+    }
+    if let Some(handler) = ctx.state.command_registry.get_handler(args[0]) {
+        if args.get(1) == Some(&"--help") {
+            // 所有命令都支持 --help，由参数规格生成
+            CommandResult::ok(handler.spec().help())
+        } else {
+            debug!("执行命令: {}", args[0]);
+            handler.handle(args, ctx).await
+        }
+    } else if args[0].contains('/') {
+        // 带路径的命令按可执行脚本处理
+        debug!("执行脚本: {}", args[0]);
+        cmd_sh::execute(args, ctx, true).await
+    } else {
+        warn!("未知命令: {}", args[0]);
+        CommandResult::invalid(format!("未知命令: {}", args[0]))
+            .with_exit_code(history::EXIT_NOT_FOUND)
+    }
+}
+
+// 在后台任务中执行命令，立即返回任务编号
+fn spawn_job(
+    state: &Arc<crate::AppState>,
+    command: &str,
+    words: Vec<String>,
+    ctx: &CommandContext<'_>,
+    options: GlobalOptions,
+) -> CommandResult {
+    if let Some(handler) = state.command_registry.get_handler(&words[0]) {
+        if handler.spec().foreground {
+            warn!("命令只能在前台执行: {}", words[0]);
+            return CommandResult::invalid(format!("{}: 不能在后台运行", words[0]));
+        }
+    }
+
+    // 任务列表中显示的命令不包含密码
+    let command = &state.command_registry.redact(command);
+    let id = match state.job_manager.start(ctx.token, command) {
+        Ok(id) => id,
+        Err(e) => return CommandResult::error(ErrorKind::TooManyRequests, e),
+    };
+
+    let task_state = Arc::clone(state);
+    let token = ctx.token.to_string();
+    let session_id = ctx.session_id.to_string();
+    let cwd = ctx.cwd.to_string();
+    let must_change_password = ctx.must_change_password;
+    let task_token = token.clone();
+    let handle = tokio::spawn(async move {
+        // 需要客户端信息的 login 等命令只能在前台执行，后台任务不再关联客户端
+        let client = ClientInfo::default();
+        let ctx = CommandContext {
+            state: &task_state,
+            token: &task_token,
+            session_id: &session_id,
+            cwd: &cwd,
+            job: Some(id),
+            output: None,
+            color: true,
+            elevated: false,
+            must_change_password,
+            client: &client,
+        };
+        let result = dispatch_with(&words, options, &ctx).await;
+        let output = JobOutput {
+            exit_code: match result.error {
+                Some(_) if result.exit_code == history::EXIT_SUCCESS => history::EXIT_FAILURE,
+                _ => result.exit_code,
+            },
+            stdout: result.stdout,
+            stderr: result.stderr,
+            data: result.data,
+        };
+        let exit_code = output.exit_code;
+        task_state.job_manager.finish(&task_token, id, output);
+
+        // 通知该会话的 WebSocket 连接，被 kill 终止的任务不再通知
+        if let Some(job) = task_state.job_manager.get(&task_token, id) {
+            if job.status != JobStatus::Cancelled {
+                let event = Event::JobFinished {
+                    job: id,
+                    command: job.command,
+                    status: job.status,
+                    exit_code,
+                };
+                task_state.connection_manager.send_to_token(&task_token, event);
+            }
+        }
+    });
+    state.job_manager.attach(&token, id, handle.abort_handle());
+
+    CommandResult::ok(format!("[{}] 已在后台运行: {}", id, command))
+        .with_data(json!({ "job": id }))
+}
+
+/// 将命令结果转换为 HTTP 响应
+pub fn http_response(result: CommandResult) -> HttpResponse {
+    let success = result.success();
+    let mut builder = match result.error {
+        None if success => HttpResponse::Ok(),
+        None | Some(ErrorKind::InvalidInput) => HttpResponse::BadRequest(),
+        Some(ErrorKind::Unauthorized) => HttpResponse::Unauthorized(),
+        Some(ErrorKind::Forbidden) => HttpResponse::Forbidden(),
+        Some(ErrorKind::TooManyRequests) => HttpResponse::TooManyRequests(),
+        Some(ErrorKind::Internal) => HttpResponse::InternalServerError(),
+    };
+    let message = result.message();
+    builder.json(CommandResponse {
+        success,
+        message: style::strip(&message),
+        data: result.data,
+        exit_code: result.exit_code,
+        effects: result.effects,
+        spans: if style::has_markup(&message) {
+            style::spans(&message)
+        } else {
+            Vec::new()
+        },
+    })
 }
 
 // 处理命令的主函数
@@ -85,10 +592,6 @@ pub async fn handle_command(
 ) -> impl Responder {
     let command = cmd.get("command").and_then(|v| v.as_str()).unwrap_or("");
     let session_id = cmd.get("session_id").and_then(|v| v.as_str()).unwrap_or("");
-    let cwd = cmd
-        .get("cwd")
-        .and_then(|v| v.as_str())
-        .unwrap_or("/home/guest/");
 
     // 从 Authorization header 中获取 token
     let token = req
@@ -98,60 +601,148 @@ pub async fn handle_command(
         .and_then(|s| s.strip_prefix("Bearer "))
         .unwrap_or("");
 
-    info!("收到命令请求: {} (session_id: {})", command, session_id);
+    info!(
+        "收到命令请求: {} (session_id: {})",
+        data.command_registry.redact(command),
+        session_id
+    );
 
-    let args: Vec<&str> = command.split_whitespace().collect();
-    if args.is_empty() {
-        warn!("空命令");
-        return HttpResponse::BadRequest().json(CommandResponse {
-            success: false,
-            message: "命令不能为空".to_string(),
-            data: None,
-        });
+    let client = ClientInfo::from_request("http", &req);
+    http_response(execute(&data, command, token, session_id, None, &client).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::is_token_current;
+
+    #[test]
+    fn redact_hides_secret_positionals() {
+        let registry = CommandRegistry::new();
+        assert_eq!(registry.redact("login alice secret"), "login alice ***");
+        assert_eq!(
+            registry.redact("register bob 'p w' --confirm \"p w\" --captcha abcd"),
+            "register bob *** --confirm *** --captcha abcd"
+        );
     }
 
-    // 获取命令处理器
-    if let Some(handler) = data.command_registry.get_handler(args[0]) {
-        debug!("执行命令: {}", args[0]);
-        // 对于需要认证的命令，使用 token；对于不需要认证的命令，使用 session_id
-        let auth_token = if requires_auth(args[0]) {
-            token
-        } else {
-            session_id
-        };
-        let response = handler.handle(&args, &data, auth_token, cwd).await;
-        if !response.status().is_success() {
-            error!("命令执行失败: {} - {}", args[0], response.status());
-        }
-        response
-    } else {
-        warn!("未知命令: {}", args[0]);
-        HttpResponse::BadRequest().json(CommandResponse {
-            success: false,
-            message: format!("未知命令: {}", args[0]),
-            data: None,
-        })
+    #[test]
+    fn redact_hides_secret_options() {
+        let registry = CommandRegistry::new();
+        assert_eq!(
+            registry.redact("passwd --current-password=old --new-password  new1234"),
+            "passwd --current-password=*** --new-password  ***"
+        );
+        assert_eq!(
+            registry.redact("sudo --password secret useradd bob --password temp123 &"),
+            "sudo --password *** useradd bob --password *** &"
+        );
     }
-}
 
-// 判断命令是否需要认证
-fn requires_auth(command: &str) -> bool {
-    matches!(
-        command,
-        "profile" | "logout" | "id" | "ls" | "pwd" | "mkdir" | "rm" | "mv" | "cd"
-    )
-}
+    #[test]
+    fn redact_keeps_other_commands() {
+        let registry = CommandRegistry::new();
+        assert_eq!(
+            registry.redact("echo --password secret"),
+            "echo --password secret"
+        );
+        assert_eq!(registry.redact("unknown secret"), "unknown secret");
+        assert_eq!(registry.redact("login alice 'unclosed"), "login alice ***");
+        assert_eq!(registry.redact(""), "");
+    }
+
+    fn take(line: &str) -> (Vec<String>, GlobalOptions) {
+        let registry = CommandRegistry::new();
+        let mut words = parser::split_command(line, None).unwrap();
+        let options = GlobalOptions::take(&mut words, &registry).unwrap();
+        (words, options)
+    }
+
+    #[test]
+    fn global_options_are_taken_when_claimed() {
+        let (words, options) = take("ls -a --color=never --output csv Documents");
+        assert_eq!(words, ["ls", "-a", "Documents"]);
+        assert!(!options.color);
+        assert_eq!(options.format, Some(OutputFormat::Csv));
+
+        let (words, options) = take("sudo --password secret ls --output json /home");
+        assert_eq!(words, ["sudo", "--password", "secret", "ls", "/home"]);
+        assert_eq!(options.format, Some(OutputFormat::Json));
+    }
+
+    #[test]
+    fn global_options_stay_in_other_arguments() {
+        let (words, options) = take("echo a --color b --output json");
+        assert_eq!(words, ["echo", "a", "--color", "b", "--output", "json"]);
+        assert!(options.color);
+        assert_eq!(options.format, None);
 
-// 在 register_commands 函数中添加 所有 命令的注册
-pub fn register_commands(registry: &mut CommandRegistry) {
-    registry.register(Box::new(cmd_help::HelpCommand::new()));
-    registry.register(Box::new(cmd_register::RegisterCommand::new()));
-    registry.register(Box::new(cmd_login::LoginCommand::new()));
-    registry.register(Box::new(cmd_logout::LogoutCommand::new()));
-    registry.register(Box::new(cmd_id::IdCommand::new()));
-    registry.register(Box::new(cmd_profile::ProfileCommand::new()));
-    registry.register(Box::new(cmd_ls::LsCommand::new()));
-    registry.register(Box::new(cmd_pwd::PwdCommand::new()));
-    registry.register(Box::new(cmd_mkdir::MkdirCommand::new()));
-    registry.register(Box::new(cmd_cd::CdCommand::new()));
+        let (words, _) = take("write bob --output json");
+        assert_eq!(words, ["write", "bob", "--output", "json"]);
+
+        let (words, _) = take("ls -- --output json");
+        assert_eq!(words, ["ls", "--", "--output", "json"]);
+
+        let (words, _) = take("alias ll --output json");
+        assert_eq!(words, ["alias", "ll", "--output", "json"]);
+    }
+
+    #[tokio::test]
+    async fn password_change_revokes_earlier_tokens() {
+        let Some(state) = crate::testing::state().await else {
+            return;
+        };
+        let db = state.db.clone();
+
+        let user_id = uuid::Uuid::new_v4().to_string();
+        let username = format!("pw{}", &user_id[..8]);
+        let hash = crate::auth::AuthManager::hash_password("secret1").unwrap();
+        sqlx::query!(
+            "INSERT INTO users (id, username, password_hash, role, created_at) VALUES ($1, $2, $3, 'user', NOW())",
+            user_id,
+            username,
+            hash
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let old = crate::auth::generate_token(&user_id, &username, "user", 0).unwrap();
+        let result = execute(
+            &state,
+            "passwd --current-password secret1 --new-password secret2",
+            &old,
+            "",
+            None,
+            &ClientInfo::default(),
+        )
+        .await;
+        let new = result.effects.iter().find_map(|effect| match effect {
+            SideEffect::SetToken(token) => Some(token.clone()),
+            _ => None,
+        });
+
+        // 吊销记录在数据库中，重新创建的应用状态（如服务重启后）同样拒绝旧 token
+        let restarted = crate::testing::state().await.unwrap();
+        let old_current = is_token_current(&restarted.db, &validate_token(&old).unwrap()).await;
+        let new_current = match &new {
+            Some(token) => is_token_current(&restarted.db, &validate_token(token).unwrap()).await,
+            None => false,
+        };
+
+        for query in [
+            "DELETE FROM command_history WHERE user_id = $1",
+            "DELETE FROM users WHERE id = $1",
+        ] {
+            sqlx::query(query)
+                .bind(&user_id)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+
+        assert!(result.success(), "{}", result.stderr);
+        assert!(!old_current);
+        assert!(new_current);
+    }
 }
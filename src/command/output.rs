@@ -0,0 +1,368 @@
+//! 机器可读的输出格式
+//!
+//! 命令把结果描述为类型化的记录（实现 `Record` 的结构体），规格中用 `output()` 声明支持的
+//! 全局选项 `--output json|yaml|csv|table` 由 `execute` 统一处理，按所选格式渲染记录并替换 stdout。
+//! 没有提供记录的命令在 json 和 yaml 格式下输出结构化数据 `data`。
+
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::{Map, Value};
+
+use super::args::display_width;
+use super::{CommandResult, ErrorKind};
+
+/// 命令结果中的一条记录
+///
+/// `COLUMNS` 为输出的字段及顺序，必须是序列化后的字段名。
+pub trait Record: Serialize {
+    const COLUMNS: &'static [&'static str];
+}
+
+/// 类型化的结果集
+#[derive(Debug, Default)]
+pub struct Records {
+    columns: &'static [&'static str],
+    rows: Vec<Map<String, Value>>,
+}
+
+impl Records {
+    pub fn new<R: Record>(records: &[R]) -> Self {
+        let rows = records
+            .iter()
+            .map(|record| match serde_json::to_value(record) {
+                Ok(Value::Object(map)) => map,
+                _ => Map::new(),
+            })
+            .collect();
+        Records {
+            columns: R::COLUMNS,
+            rows,
+        }
+    }
+
+    fn value<'a>(&'a self, row: &'a Map<String, Value>, column: &str) -> &'a Value {
+        row.get(column).unwrap_or(&Value::Null)
+    }
+}
+
+// 按列顺序序列化一行
+struct Row<'a> {
+    records: &'a Records,
+    row: &'a Map<String, Value>,
+}
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.records.columns.len()))?;
+        for column in self.records.columns {
+            map.serialize_entry(column, self.records.value(self.row, column))?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for Records {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.rows.len()))?;
+        for row in &self.rows {
+            seq.serialize_element(&Row { records: self, row })?;
+        }
+        seq.end()
+    }
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+    Csv,
+    Table,
+}
+
+impl OutputFormat {
+    const NAMES: &'static [&'static str] = &["json", "yaml", "csv", "table"];
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(OutputFormat::Json),
+            "yaml" => Some(OutputFormat::Yaml),
+            "csv" => Some(OutputFormat::Csv),
+            "table" => Some(OutputFormat::Table),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Table => "table",
+        }
+    }
+}
+
+/// 从参数中取出全局的 `--output <格式>` 或 `--output=<格式>` 选项，只识别 `end` 之前的参数
+pub fn take_output_option(
+    words: &mut Vec<String>,
+    mut end: usize,
+) -> Result<Option<OutputFormat>, String> {
+    let mut format = None;
+    let mut index = 1;
+    while index < end {
+        let word = &words[index];
+        let (name, consumed) = if word == "--output" {
+            match words.get(index + 1) {
+                Some(name) => (name.clone(), 2),
+                None => return Err("选项 --output 缺少值 <格式>".to_string()),
+            }
+        } else if let Some(name) = word.strip_prefix("--output=") {
+            (name.to_string(), 1)
+        } else {
+            index += 1;
+            continue;
+        };
+        format = Some(OutputFormat::parse(&name).ok_or_else(|| {
+            format!(
+                "--output 的值 {} 无效，可选值: {}",
+                name,
+                OutputFormat::NAMES.join(", ")
+            )
+        })?);
+        words.drain(index..index + consumed);
+        end = end.saturating_sub(consumed);
+    }
+    Ok(format)
+}
+
+// csv 和 table 中单元格的文本，字符串不加引号，空值为空
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn render_table(records: &Records) -> String {
+    let header: Vec<String> = records.columns.iter().map(|c| c.to_uppercase()).collect();
+    let rows: Vec<Vec<String>> = records
+        .rows
+        .iter()
+        .map(|row| {
+            records
+                .columns
+                .iter()
+                .map(|column| cell(records.value(row, column)))
+                .collect()
+        })
+        .collect();
+    align(&std::iter::once(header).chain(rows).collect::<Vec<_>>())
+}
+
+/// 按列对齐文本表格，列之间用两个空格分隔
+pub fn align(rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        for (i, text) in row.iter().enumerate() {
+            match widths.get_mut(i) {
+                Some(width) => *width = (*width).max(display_width(text)),
+                None => widths.push(display_width(text)),
+            }
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(text, width)| {
+                    format!("{}{}", text, " ".repeat(width - display_width(text)))
+                })
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_csv(records: &Records) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let error = |e: csv::Error| format!("生成 CSV 失败: {}", e);
+    writer.write_record(records.columns).map_err(error)?;
+    for row in &records.rows {
+        writer
+            .write_record(
+                records
+                    .columns
+                    .iter()
+                    .map(|column| cell(records.value(row, column))),
+            )
+            .map_err(error)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    let text = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+    Ok(text.trim_end().to_string())
+}
+
+fn render_value<T: Serialize>(format: OutputFormat, value: &T) -> Result<String, String> {
+    match format {
+        OutputFormat::Yaml => serde_yaml::to_string(value)
+            .map(|text| text.trim_end().to_string())
+            .map_err(|e| format!("生成 YAML 失败: {}", e)),
+        _ => serde_json::to_string_pretty(value).map_err(|e| format!("生成 JSON 失败: {}", e)),
+    }
+}
+
+/// 按所选格式渲染命令结果，失败的命令保持原样
+pub fn apply(format: OutputFormat, command: &str, mut result: CommandResult) -> CommandResult {
+    if !result.success() {
+        return result;
+    }
+    let rendered = match (&result.records, format) {
+        (Some(records), OutputFormat::Table) => Ok(render_table(records)),
+        (Some(records), OutputFormat::Csv) => render_csv(records),
+        (Some(records), _) => render_value(format, records),
+        (None, OutputFormat::Json | OutputFormat::Yaml) => match &result.data {
+            Some(data) => render_value(format, data),
+            None => Err(format!("{}: 没有可输出的结构化结果", command)),
+        },
+        (None, _) => Err(format!("{}: 不支持 --output {}", command, format.name())),
+    };
+    match rendered {
+        Ok(text) => {
+            result.stdout = text;
+            result
+        }
+        Err(e) => CommandResult::error(ErrorKind::InvalidInput, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct File {
+        name: String,
+        size: u64,
+        owner: Option<String>,
+    }
+
+    impl Record for File {
+        const COLUMNS: &'static [&'static str] = &["name", "size", "owner"];
+    }
+
+    fn files() -> Vec<File> {
+        vec![
+            File {
+                name: "hello.md".to_string(),
+                size: 12,
+                owner: Some("alice".to_string()),
+            },
+            File {
+                name: "草稿, 第一篇".to_string(),
+                size: 3,
+                owner: None,
+            },
+        ]
+    }
+
+    fn words(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn take_output_option_removes_option() {
+        let mut args = words("ls -a --output csv Documents");
+        let end = args.len();
+        assert_eq!(
+            take_output_option(&mut args, end),
+            Ok(Some(OutputFormat::Csv))
+        );
+        assert_eq!(args, words("ls -a Documents"));
+
+        let mut args = words("id --output=yaml");
+        assert_eq!(
+            take_output_option(&mut args, 2),
+            Ok(Some(OutputFormat::Yaml))
+        );
+        assert_eq!(args, words("id"));
+    }
+
+    #[test]
+    fn take_output_option_stops_at_end() {
+        let mut args = words("ls -- --output json");
+        assert_eq!(take_output_option(&mut args, 1), Ok(None));
+        assert_eq!(args, words("ls -- --output json"));
+    }
+
+    #[test]
+    fn take_output_option_rejects_invalid_values() {
+        let mut args = words("ls --output xml");
+        let end = args.len();
+        assert!(take_output_option(&mut args, end).is_err());
+        let mut args = words("ls --output");
+        let end = args.len();
+        assert!(take_output_option(&mut args, end).is_err());
+    }
+
+    #[test]
+    fn table_aligns_columns_by_display_width() {
+        let records = Records::new(&files());
+        assert_eq!(
+            render_table(&records),
+            "NAME          SIZE  OWNER\nhello.md      12    alice\n草稿, 第一篇  3"
+        );
+    }
+
+    #[test]
+    fn csv_quotes_cells_when_needed() {
+        let records = Records::new(&files());
+        assert_eq!(
+            render_csv(&records).unwrap(),
+            "name,size,owner\nhello.md,12,alice\n\"草稿, 第一篇\",3,"
+        );
+    }
+
+    #[test]
+    fn json_keeps_column_order() {
+        let records = Records::new(&files());
+        let text = render_value(OutputFormat::Json, &records).unwrap();
+        let name = text.find("\"name\"").unwrap();
+        let size = text.find("\"size\"").unwrap();
+        let owner = text.find("\"owner\"").unwrap();
+        assert!(name < size && size < owner);
+        assert!(text.contains("\"owner\": null"));
+    }
+
+    #[test]
+    fn apply_renders_records_and_data() {
+        let result = CommandResult::ok("hello.md").with_records(&files());
+        let result = apply(OutputFormat::Yaml, "ls", result);
+        assert!(result
+            .stdout
+            .starts_with("- name: hello.md\n  size: 12\n  owner: alice"));
+
+        let result = CommandResult::ok("/home/alice").with_data(json!({ "path": "/home/alice" }));
+        let result = apply(OutputFormat::Json, "pwd", result);
+        assert_eq!(result.stdout, "{\n  \"path\": \"/home/alice\"\n}");
+    }
+
+    #[test]
+    fn apply_rejects_unsupported_formats() {
+        let result = CommandResult::ok("/home/alice").with_data(json!({ "path": "/home/alice" }));
+        let result = apply(OutputFormat::Csv, "pwd", result);
+        assert_eq!(result.stderr, "pwd: 不支持 --output csv");
+
+        let result = apply(OutputFormat::Json, "clear", CommandResult::ok(""));
+        assert_eq!(result.stderr, "clear: 没有可输出的结构化结果");
+
+        let failed = CommandResult::invalid("ls: 没有那个目录");
+        let result = apply(OutputFormat::Json, "ls", failed);
+        assert_eq!(result.stderr, "ls: 没有那个目录");
+    }
+}
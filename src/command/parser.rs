@@ -0,0 +1,354 @@
+use crate::session::is_valid_var_name;
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::Chars;
+
+/// 将命令行拆分为参数列表
+///
+/// 支持单引号（原样保留）、双引号（允许变量展开）和反斜杠转义。
+/// 传入 `env` 时展开 `$VAR`、`${VAR}` 与 `${VAR:-default}`，未定义的变量展开为空字符串。
+/// 位置参数 `$1`…`$9` 与特殊参数 `$#`、`$@`、`$?` 同样从 `env` 中读取。
+/// 引号外的展开结果按空白拆分为多个参数，双引号内的展开保持为一个参数。
+pub fn split_command(
+    line: &str,
+    env: Option<&HashMap<String, String>>,
+) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    // 当前参数是否包含字面内容（引号或普通字符），用于区分空展开与空字符串参数
+    let mut in_word = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word || !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => current.push(ch),
+                        None => return Err("单引号未闭合".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.peek() {
+                            Some(&next) if matches!(next, '"' | '\\' | '$') => {
+                                current.push(next);
+                                chars.next();
+                            }
+                            _ => current.push('\\'),
+                        },
+                        Some('$') => match env {
+                            Some(env) => current.push_str(&expand_var(&mut chars, env)?),
+                            None => current.push('$'),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err("双引号未闭合".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            '$' => match env {
+                Some(env) => {
+                    // 引号外的展开结果按空白拆分
+                    for ch in expand_var(&mut chars, env)?.chars() {
+                        if ch.is_whitespace() {
+                            if in_word || !current.is_empty() {
+                                words.push(std::mem::take(&mut current));
+                                in_word = false;
+                            }
+                        } else {
+                            current.push(ch);
+                        }
+                    }
+                }
+                None => {
+                    in_word = true;
+                    current.push('$');
+                }
+            },
+            _ => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_word || !current.is_empty() {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+/// 按 `split_command` 的规则拆分参数但不展开变量，同时返回每个参数在原文中的字节范围
+///
+/// 用于在原始命令行中定位参数，如隐去密码。引号未闭合时不报错，最后一个参数延伸到行尾。
+pub fn split_spans(line: &str) -> Vec<(Range<usize>, String)> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut start = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c.is_whitespace() {
+            if let Some(start) = start.take() {
+                words.push((start..i, std::mem::take(&mut current)));
+            }
+            continue;
+        }
+        start.get_or_insert(i);
+        match c {
+            '\'' => {
+                for (_, ch) in chars.by_ref() {
+                    if ch == '\'' {
+                        break;
+                    }
+                    current.push(ch);
+                }
+            }
+            '"' => loop {
+                match chars.next() {
+                    Some((_, '"')) | None => break,
+                    Some((_, '\\')) => match chars.peek() {
+                        Some(&(_, next)) if matches!(next, '"' | '\\' | '$') => {
+                            current.push(next);
+                            chars.next();
+                        }
+                        _ => current.push('\\'),
+                    },
+                    Some((_, ch)) => current.push(ch),
+                }
+            },
+            '\\' => {
+                if let Some((_, next)) = chars.next() {
+                    current.push(next);
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+
+    if let Some(start) = start {
+        words.push((start..line.len(), current));
+    }
+    words
+}
+
+/// 展开字符串中的变量引用（不做分词和引号处理）
+pub fn expand_vars(text: &str, env: &HashMap<String, String>) -> Result<String, String> {
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' {
+            result.push_str(&expand_var(&mut chars, env)?);
+        } else {
+            result.push(c);
+        }
+    }
+    Ok(result)
+}
+
+// 解析 `$` 之后的变量引用，返回展开后的值
+fn expand_var(
+    chars: &mut Peekable<Chars<'_>>,
+    env: &HashMap<String, String>,
+) -> Result<String, String> {
+    match chars.peek() {
+        Some('{') => {
+            chars.next();
+            let mut body = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(ch) => body.push(ch),
+                    None => return Err("变量引用缺少 '}'".to_string()),
+                }
+            }
+
+            let (name, default) = match body.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (body.as_str(), None),
+            };
+            if !is_valid_var_name(name) && !is_special_param(name) {
+                return Err(format!("错误的变量替换: ${{{}}}", body));
+            }
+
+            match (env.get(name), default) {
+                (Some(value), _) if !value.is_empty() => Ok(value.clone()),
+                (_, Some(default)) => expand_vars(default, env),
+                (Some(value), None) => Ok(value.clone()),
+                (None, None) => Ok(String::new()),
+            }
+        }
+        Some(&c) if c.is_ascii_alphabetic() || c == '_' => {
+            let mut name = String::new();
+            while let Some(&ch) = chars.peek() {
+                if ch.is_ascii_alphanumeric() || ch == '_' {
+                    name.push(ch);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            Ok(env.get(&name).cloned().unwrap_or_default())
+        }
+        Some(&c) if c.is_ascii_digit() || matches!(c, '#' | '@' | '?') => {
+            chars.next();
+            Ok(env.get(c.to_string().as_str()).cloned().unwrap_or_default())
+        }
+        // 不是变量引用，按字面量处理
+        _ => Ok("$".to_string()),
+    }
+}
+
+/// 位置参数（纯数字）与特殊参数 `#`、`@`、`?`
+pub fn is_special_param(name: &str) -> bool {
+    matches!(name, "#" | "@" | "?")
+        || (!name.is_empty() && name.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// 去掉行尾表示后台运行的 `&`，返回剩余的命令；`&&` 和转义的 `\&` 不算
+pub fn strip_background(line: &str) -> Option<&str> {
+    let line = line.trim_end();
+    let rest = line.strip_suffix('&')?;
+    if rest.ends_with('&') || rest.ends_with('\\') {
+        return None;
+    }
+    Some(rest.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn split_command_handles_quotes_and_escapes() {
+        let words = split_command(r#"echo 'a  b' "c \"d\"" e\ f '' "#, None).unwrap();
+        assert_eq!(words, ["echo", "a  b", "c \"d\"", "e f", ""]);
+        assert_eq!(
+            split_command("echo 'open", None).unwrap_err(),
+            "单引号未闭合"
+        );
+        assert_eq!(
+            split_command("echo \"open", None).unwrap_err(),
+            "双引号未闭合"
+        );
+    }
+
+    #[test]
+    fn split_command_expands_variables() {
+        let env = env(&[("NAME", "alice"), ("LIST", "a b"), ("EMPTY", "")]);
+        let split = |line: &str| split_command(line, Some(&env)).unwrap();
+        assert_eq!(split("echo $NAME ${NAME}s"), ["echo", "alice", "alices"]);
+        // 引号外的展开按空白拆分，双引号内保持为一个参数，单引号内不展开
+        assert_eq!(split("echo $LIST"), ["echo", "a", "b"]);
+        assert_eq!(split("echo \"$LIST\" '$LIST'"), ["echo", "a b", "$LIST"]);
+        // 空展开不产生参数，空字符串参数保留
+        assert_eq!(split("echo $MISSING $EMPTY"), ["echo"]);
+        assert_eq!(split("echo \"$MISSING\""), ["echo", ""]);
+        assert_eq!(split("echo $ \\$NAME"), ["echo", "$", "$NAME"]);
+    }
+
+    #[test]
+    fn split_command_without_env_keeps_dollar() {
+        let words = split_command("echo $NAME \"${NAME}\"", None).unwrap();
+        assert_eq!(words, ["echo", "$NAME", "${NAME}"]);
+    }
+
+    #[test]
+    fn default_value_applies_to_unset_and_empty_variables() {
+        let env = env(&[("NAME", "alice"), ("EMPTY", ""), ("DIR", "/tmp")]);
+        let expand = |text: &str| expand_vars(text, &env).unwrap();
+        assert_eq!(expand("${NAME:-bob}"), "alice");
+        assert_eq!(expand("${EMPTY:-bob}"), "bob");
+        assert_eq!(expand("${MISSING:-bob}"), "bob");
+        assert_eq!(expand("${MISSING:-$DIR/x}"), "/tmp/x");
+        assert_eq!(expand("${EMPTY}"), "");
+    }
+
+    #[test]
+    fn expand_vars_reports_bad_references() {
+        let env = env(&[]);
+        assert_eq!(expand_vars("${NAME", &env).unwrap_err(), "变量引用缺少 '}'");
+        assert_eq!(
+            expand_vars("${A-B}", &env).unwrap_err(),
+            "错误的变量替换: ${A-B}"
+        );
+        assert_eq!(expand_vars("cost: 5$", &env).unwrap(), "cost: 5$");
+    }
+
+    #[test]
+    fn positional_and_special_params_expand() {
+        let env = env(&[
+            ("0", "deploy.sh"),
+            ("1", "first"),
+            ("2", "second"),
+            ("10", "tenth"),
+            ("#", "2"),
+            ("@", "first second"),
+            ("?", "1"),
+        ]);
+        let expand = |text: &str| expand_vars(text, &env).unwrap();
+        assert_eq!(expand("$0 $1 $2 $3"), "deploy.sh first second ");
+        // `$10` 是 `$1` 后跟 0，多位数的位置参数需要花括号
+        assert_eq!(expand("$10 ${10}"), "first0 tenth");
+        assert_eq!(expand("$# [$@] $?"), "2 [first second] 1");
+        assert_eq!(expand("${3:-none} ${#}"), "none 2");
+        assert!(is_special_param("#") && is_special_param("12"));
+        assert!(!is_special_param("") && !is_special_param("1a") && !is_special_param("*"));
+    }
+
+    #[test]
+    fn strip_background_removes_trailing_ampersand() {
+        assert_eq!(strip_background("sleep 5 &"), Some("sleep 5"));
+        assert_eq!(strip_background("ls -R /&  "), Some("ls -R /"));
+        assert_eq!(strip_background("&"), Some(""));
+        assert_eq!(strip_background("a && b"), None);
+        assert_eq!(strip_background("a &&"), None);
+        assert_eq!(strip_background("echo \\&"), None);
+        assert_eq!(strip_background("ls"), None);
+    }
+
+    #[test]
+    fn split_spans_keeps_byte_ranges_of_quoted_words() {
+        let line = "login  '张 三' \"a\\\"b\" c\\ d $X";
+        let spans = split_spans(line);
+        let words: Vec<&str> = spans.iter().map(|(_, word)| word.as_str()).collect();
+        assert_eq!(words, ["login", "张 三", "a\"b", "c d", "$X"]);
+        let sources: Vec<&str> = spans
+            .iter()
+            .map(|(range, _)| &line[range.clone()])
+            .collect();
+        assert_eq!(sources, ["login", "'张 三'", "\"a\\\"b\"", "c\\ d", "$X"]);
+
+        // 引号未闭合时最后一个参数延伸到行尾
+        let line = "login alice 'open  ";
+        let (range, word) = split_spans(line).pop().unwrap();
+        assert_eq!((range, word.as_str()), (12..line.len(), "open  "));
+        assert!(split_spans("   ").is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+use super::account::{home_dir, system_user};
+use super::CommandContext;
+use crate::vfs::front_matter::MAX_SLUG_LEN;
+use crate::vfs::model::VfsError;
+use crate::vfs::path_normalizer::PathNormalizer;
+use chrono::{DateTime, Utc};
+use log::debug;
+use serde::Serialize;
+use sqlx::PgPool;
+
+/// 草稿所在的目录，相对家目录
+pub const DRAFTS_DIR: &str = "Documents/drafts";
+
+/// 已发布文章所在的目录，相对家目录
+pub const PUBLISHED_DIR: &str = "Documents/published";
+
+// 文件名中没有可用字符时使用的 slug
+const DEFAULT_SLUG: &str = "post";
+
+/// `posts` 表中的一篇文章
+#[derive(Debug, Serialize)]
+pub struct Post {
+    pub id: i64,
+    pub author_id: String,
+    /// 公开链接中使用的标识，首次发布时生成，之后保持不变
+    pub slug: String,
+    pub title: String,
+    /// 文章文件当前的路径
+    pub path: String,
+    /// 发布时间，撤回发布后为 None
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 把 `/home/<用户>/<dir>/<相对路径>` 拆分为家目录和相对路径，不在该目录下时返回 None
+pub fn split(path: &str, dir: &str) -> Option<(String, String)> {
+    let (user, rest) = path.strip_prefix("/home/")?.split_once('/')?;
+    let relative = rest.strip_prefix(dir)?.strip_prefix('/')?;
+    if relative.is_empty() {
+        return None;
+    }
+    Some((home_dir(user), relative.to_string()))
+}
+
+/// 文章的默认标题：去掉扩展名的文件名
+pub fn title_from(path: &str) -> String {
+    let name = PathNormalizer::basename(path).unwrap_or_default();
+    match name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+        _ => name,
+    }
+}
+
+/// 由标题生成 slug：保留小写字母和数字，其余字符替换为 `-`
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        DEFAULT_SLUG.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// 按当前路径查找文章
+pub async fn find_by_path(db: &PgPool, path: &str) -> Result<Option<Post>, sqlx::Error> {
+    sqlx::query_as!(Post, "SELECT * FROM posts WHERE path = $1", path)
+        .fetch_optional(db)
+        .await
+}
+
+/// 按 slug 查找文章
+pub async fn find_by_slug(db: &PgPool, slug: &str) -> Result<Option<Post>, sqlx::Error> {
+    sqlx::query_as!(Post, "SELECT * FROM posts WHERE slug = $1", slug)
+        .fetch_optional(db)
+        .await
+}
+
+/// slug 是否已被 `id` 之外的文章使用
+pub async fn slug_taken(db: &PgPool, slug: &str, id: Option<i64>) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM posts WHERE slug = $1 AND id IS DISTINCT FROM $2) AS "taken!""#,
+        slug,
+        id
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// 以 `base` 为基础生成未被使用的 slug，重复时依次加上 `-2`、`-3` 等后缀
+pub async fn unique_slug(db: &PgPool, base: &str) -> Result<String, sqlx::Error> {
+    let mut slug = base.to_string();
+    let mut n = 1;
+    while slug_taken(db, &slug, None).await? {
+        n += 1;
+        let suffix = format!("-{}", n);
+        let mut prefix = base.to_string();
+        prefix.truncate(MAX_SLUG_LEN - suffix.len());
+        slug = format!("{}{}", prefix.trim_end_matches('-'), suffix);
+    }
+    Ok(slug)
+}
+
+/// 记录发布：`id` 为 None 时新建文章，否则更新原有记录的 slug、路径和发布时间
+pub async fn save_published(
+    db: &PgPool,
+    id: Option<i64>,
+    author_id: &str,
+    slug: &str,
+    title: &str,
+    path: &str,
+    published_at: DateTime<Utc>,
+) -> Result<Post, sqlx::Error> {
+    debug!("记录发布的文章: {} -> {}", slug, path);
+    match id {
+        Some(id) => {
+            sqlx::query_as!(
+                Post,
+                r#"
+                UPDATE posts
+                SET slug = $2, title = $3, path = $4, published_at = $5, updated_at = NOW()
+                WHERE id = $1
+                RETURNING *
+                "#,
+                id,
+                slug,
+                title,
+                path,
+                published_at
+            )
+            .fetch_one(db)
+            .await
+        }
+        None => {
+            sqlx::query_as!(
+                Post,
+                r#"
+                INSERT INTO posts (author_id, slug, title, path, published_at)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING *
+                "#,
+                author_id,
+                slug,
+                title,
+                path,
+                published_at
+            )
+            .fetch_one(db)
+            .await
+        }
+    }
+}
+
+/// 记录撤回发布：文章移回草稿目录，保留 slug 以便再次发布
+pub async fn save_unpublished(db: &PgPool, id: i64, path: &str) -> Result<Post, sqlx::Error> {
+    debug!("撤回发布的文章: {} -> {}", id, path);
+    sqlx::query_as!(
+        Post,
+        r#"
+        UPDATE posts SET path = $2, published_at = NULL, updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+        id,
+        path
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// 确保目录及其上级目录存在，新建的目录属于 `owner_id`
+pub async fn ensure_dir(
+    ctx: &CommandContext<'_>,
+    owner_id: &str,
+    path: &str,
+) -> Result<(), VfsError> {
+    let owner = system_user(owner_id);
+    let mut current = String::new();
+    for part in path.split('/').filter(|part| !part.is_empty()) {
+        current.push('/');
+        current.push_str(part);
+        match ctx.state.vfs_manager.stat(&current, "/").await {
+            Ok(node) if node.is_dir => {}
+            Ok(_) => return Err(VfsError::InvalidOperation(format!("不是目录: {}", current))),
+            Err(VfsError::NodeNotFound(_)) => {
+                ctx.state
+                    .vfs_manager
+                    .create_dir(&owner, &current, "/")
+                    .await?;
+                debug!("创建目录: {}", current);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_separates_home_and_relative_path() {
+        assert_eq!(
+            split("/home/alice/Documents/drafts/a/b.md", DRAFTS_DIR),
+            Some(("/home/alice".to_string(), "a/b.md".to_string()))
+        );
+        assert_eq!(split("/home/alice/Documents/drafts", DRAFTS_DIR), None);
+        assert_eq!(split("/home/alice/Documents/drafts/", DRAFTS_DIR), None);
+        assert_eq!(
+            split("/home/alice/Documents/draftsx/a.md", DRAFTS_DIR),
+            None
+        );
+        assert_eq!(
+            split("/home/alice/Documents/published/a.md", DRAFTS_DIR),
+            None
+        );
+        assert_eq!(split("/tmp/Documents/drafts/a.md", DRAFTS_DIR), None);
+    }
+
+    #[test]
+    fn title_from_drops_extension() {
+        assert_eq!(title_from("/home/alice/Documents/drafts/hello.md"), "hello");
+        assert_eq!(title_from("x/archive.tar.gz"), "archive.tar");
+        assert_eq!(title_from("x/README"), "README");
+        assert_eq!(title_from("x/.hidden"), ".hidden");
+    }
+
+    #[test]
+    fn slugify_keeps_lowercase_ascii_words() {
+        assert_eq!(slugify("Hello World!"), "hello-world");
+        assert_eq!(slugify("  Rust -- 2024 edition  "), "rust-2024-edition");
+        assert_eq!(slugify("你好 Rust"), "rust");
+        assert_eq!(slugify("你好，世界"), DEFAULT_SLUG);
+        let long = slugify(&"a".repeat(MAX_SLUG_LEN + 10));
+        assert_eq!(long.len(), MAX_SLUG_LEN);
+        // 截断后不以 `-` 结尾
+        let title = format!("{} b", "a".repeat(MAX_SLUG_LEN - 1));
+        assert_eq!(slugify(&title), "a".repeat(MAX_SLUG_LEN - 1));
+    }
+}
@@ -0,0 +1,757 @@
+use super::parser::split_command;
+use super::{cmd_cd, history, ClientInfo, CommandContext, CommandResult, OutputSink};
+use crate::session::is_valid_var_name;
+use crate::vfs::model::{User as VfsUser, VfsError};
+use futures::future::BoxFuture;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// 单次执行最多运行的命令数（包括嵌套调用的脚本）
+pub const MAX_STEPS: usize = 1000;
+
+// 单次执行的最长时间
+pub const MAX_DURATION: Duration = Duration::from_secs(10);
+
+// 后台任务不阻塞请求，允许运行更久
+pub const MAX_JOB_STEPS: usize = 10_000;
+pub const MAX_JOB_DURATION: Duration = Duration::from_secs(600);
+
+// 脚本嵌套调用的最大深度
+const MAX_DEPTH: usize = 8;
+
+// 脚本中不会被当作普通命令执行的关键字
+const KEYWORDS: [&str; 8] = ["if", "then", "elif", "else", "fi", "for", "do", "done"];
+
+// 按 `;` 和换行拆分出的一条语句
+#[derive(Debug)]
+struct Segment {
+    text: String,
+    line: usize,
+}
+
+// 语法树节点
+#[derive(Debug)]
+enum Node {
+    Command {
+        text: String,
+        line: usize,
+    },
+    If {
+        branches: Vec<(Vec<Node>, Vec<Node>)>,
+        otherwise: Option<Vec<Node>>,
+    },
+    For {
+        var: String,
+        words: String,
+        body: Vec<Node>,
+    },
+}
+
+// 语句执行后的控制流
+enum Flow {
+    Normal,
+    Exit(i32),
+}
+
+/// 脚本执行结果
+#[derive(Debug)]
+pub struct ScriptOutcome {
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+    pub exit_status: i32,
+    pub steps: usize,
+    pub error: Option<String>,
+}
+
+// 执行上下文，嵌套调用的脚本共享步数限制和输出
+struct Context<'a> {
+    state: &'a crate::AppState,
+    token: &'a str,
+    session_id: &'a str,
+    user: &'a VfsUser,
+    cwd: String,
+    locals: HashMap<String, String>,
+    stdout: Vec<String>,
+    stderr: Vec<String>,
+    steps: usize,
+    last_status: i32,
+    depth: usize,
+    job: Option<u64>,
+    output: Option<&'a OutputSink>,
+    color: bool,
+    elevated: bool,
+    must_change_password: bool,
+    client: &'a ClientInfo,
+}
+
+impl Context<'_> {
+    // 以脚本当前的工作目录执行命令的上下文
+    fn command(&self) -> CommandContext<'_> {
+        CommandContext {
+            state: self.state,
+            token: self.token,
+            session_id: self.session_id,
+            cwd: &self.cwd,
+            job: self.job,
+            // 命令的输出由脚本统一发送
+            output: None,
+            color: self.color,
+            elevated: self.elevated,
+            must_change_password: self.must_change_password,
+            client: self.client,
+        }
+    }
+
+    // 收集命令的输出，stdout 同时发送到输出通道，返回退出状态
+    fn record(&mut self, result: CommandResult) -> i32 {
+        if !result.stdout.is_empty() {
+            if let Some(output) = self.output {
+                let _ = output.send(result.stdout.clone());
+            }
+            self.stdout.push(result.stdout);
+        }
+        if !result.stderr.is_empty() {
+            self.stderr.push(result.stderr);
+        }
+        match result.error {
+            Some(_) if result.exit_code == history::EXIT_SUCCESS => history::EXIT_FAILURE,
+            _ => result.exit_code,
+        }
+    }
+
+    fn max_steps(&self) -> usize {
+        match self.job {
+            Some(_) => MAX_JOB_STEPS,
+            None => MAX_STEPS,
+        }
+    }
+}
+
+/// 执行 VFS 中的脚本
+///
+/// `require_exec` 为 `true` 时（`./script.sh`）需要文件具有执行权限，否则（`sh script.sh`）只需要读权限。
+pub async fn run(
+    command: &CommandContext<'_>,
+    user: &VfsUser,
+    args: &[String],
+    require_exec: bool,
+) -> ScriptOutcome {
+    let mut ctx = Context {
+        state: command.state,
+        token: command.token,
+        session_id: command.session_id,
+        user,
+        cwd: command.cwd.to_string(),
+        locals: HashMap::new(),
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+        steps: 0,
+        last_status: history::EXIT_SUCCESS,
+        depth: 0,
+        job: command.job,
+        output: command.output,
+        color: command.color,
+        elevated: command.elevated,
+        must_change_password: command.must_change_password,
+        client: command.client,
+    };
+
+    let max_duration = match command.job {
+        Some(_) => MAX_JOB_DURATION,
+        None => MAX_DURATION,
+    };
+    let result = tokio::time::timeout(max_duration, run_file(&mut ctx, args, require_exec)).await;
+    let (exit_status, error) = match result {
+        Ok(Ok(status)) => (status, None),
+        Ok(Err(e)) => (history::EXIT_FAILURE, Some(e)),
+        Err(_) => {
+            warn!("脚本执行超时");
+            (
+                history::EXIT_FAILURE,
+                Some(format!("执行超时（超过 {} 秒）", max_duration.as_secs())),
+            )
+        }
+    };
+
+    ScriptOutcome {
+        stdout: ctx.stdout,
+        stderr: ctx.stderr,
+        exit_status,
+        steps: ctx.steps,
+        error,
+    }
+}
+
+// 读取、解析并执行脚本文件，返回退出状态
+fn run_file<'a>(
+    ctx: &'a mut Context<'_>,
+    args: &'a [String],
+    require_exec: bool,
+) -> BoxFuture<'a, Result<i32, String>> {
+    Box::pin(async move {
+        let path = args.first().ok_or_else(|| "缺少脚本路径".to_string())?;
+        if ctx.depth >= MAX_DEPTH {
+            return Err(format!("{}: 脚本嵌套层数过多", path));
+        }
+        info!("执行脚本: {} (深度: {})", path, ctx.depth);
+
+        let source = if require_exec {
+            ctx.state
+                .vfs_manager
+                .read_executable(ctx.user, path, &ctx.cwd)
+                .await
+        } else {
+            ctx.state
+                .vfs_manager
+                .read_file(ctx.user, path, &ctx.cwd)
+                .await
+        }
+        .map_err(|e| match e {
+            VfsError::NodeNotFound(_) => format!("{}: 没有那个文件", path),
+            e => format!("{}: {}", path, e),
+        })?;
+        let nodes = parse(&source).map_err(|e| format!("{}: {}", path, e))?;
+
+        // 位置参数只在当前脚本内可见
+        let mut locals = HashMap::new();
+        for (i, arg) in args.iter().enumerate() {
+            locals.insert(i.to_string(), arg.clone());
+        }
+        locals.insert("#".to_string(), (args.len() - 1).to_string());
+        locals.insert("@".to_string(), args[1..].join(" "));
+        let saved = std::mem::replace(&mut ctx.locals, locals);
+
+        ctx.depth += 1;
+        let result = exec_block(ctx, &nodes).await;
+        ctx.depth -= 1;
+        ctx.locals = saved;
+
+        match result? {
+            Flow::Normal => Ok(ctx.last_status),
+            Flow::Exit(status) => Ok(status),
+        }
+    })
+}
+
+// 将脚本拆分为语句，去掉注释并处理续行
+fn split_segments(source: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut chars = source.chars().peekable();
+
+    let mut flush = |current: &mut String, start_line: usize| {
+        let text = current.trim();
+        if !text.is_empty() {
+            segments.push(Segment {
+                text: text.to_string(),
+                line: start_line,
+            });
+        }
+        current.clear();
+    };
+
+    while let Some(c) = chars.next() {
+        if current.trim().is_empty() {
+            start_line = line;
+        }
+        match c {
+            '\n' => {
+                line += 1;
+                if in_single || in_double {
+                    current.push(c);
+                } else {
+                    flush(&mut current, start_line);
+                }
+            }
+            '\'' if !in_double => {
+                in_single = !in_single;
+                current.push(c);
+            }
+            '"' if !in_single => {
+                in_double = !in_double;
+                current.push(c);
+            }
+            '\\' if !in_single => match chars.next() {
+                // 反斜杠加换行表示续行
+                Some('\n') => line += 1,
+                Some(next) => {
+                    current.push(c);
+                    current.push(next);
+                }
+                None => current.push(c),
+            },
+            ';' if !in_single && !in_double => flush(&mut current, start_line),
+            // 单词开头的 `#` 表示注释
+            '#' if !in_single
+                && !in_double
+                && current.chars().last().is_none_or(char::is_whitespace) =>
+            {
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+
+    if in_single || in_double {
+        return Err(format!("第 {} 行: 引号未闭合", start_line));
+    }
+    flush(&mut current, start_line);
+
+    // `then`、`do`、`else` 后可以直接跟命令，拆成单独的语句
+    let mut result = Vec::new();
+    for segment in segments {
+        let mut text = segment.text;
+        loop {
+            let (first, rest) = split_first_word(&text);
+            if matches!(first, "then" | "do" | "else" | "fi" | "done") && !rest.is_empty() {
+                let rest = rest.to_string();
+                result.push(Segment {
+                    text: first.to_string(),
+                    line: segment.line,
+                });
+                text = rest;
+            } else {
+                result.push(Segment {
+                    text,
+                    line: segment.line,
+                });
+                break;
+            }
+        }
+    }
+    Ok(result)
+}
+
+// 拆出语句的首个单词和剩余部分
+fn split_first_word(text: &str) -> (&str, &str) {
+    let text = text.trim();
+    match text.find(char::is_whitespace) {
+        Some(i) => (&text[..i], text[i..].trim()),
+        None => (text, ""),
+    }
+}
+
+// 将脚本解析为语法树
+fn parse(source: &str) -> Result<Vec<Node>, String> {
+    let segments = split_segments(source)?;
+    let mut parser = Parser { segments, pos: 0 };
+    let (nodes, end) = parser.parse_block(&[])?;
+    match end {
+        None => Ok(nodes),
+        Some(keyword) => Err(format!(
+            "第 {} 行: 语法错误: 意外的 {}",
+            parser.line(),
+            keyword
+        )),
+    }
+}
+
+struct Parser {
+    segments: Vec<Segment>,
+    pos: usize,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        self.segments
+            .get(self.pos)
+            .or_else(|| self.segments.last())
+            .map_or(1, |s| s.line)
+    }
+
+    // 解析语句直到遇到 `terminators` 中的关键字，返回遇到的关键字（不消费）
+    fn parse_block(&mut self, terminators: &[&str]) -> Result<(Vec<Node>, Option<String>), String> {
+        let mut nodes = Vec::new();
+        while let Some(segment) = self.segments.get(self.pos) {
+            let line = segment.line;
+            let (first, rest) = split_first_word(&segment.text);
+            let (first, rest) = (first.to_string(), rest.to_string());
+
+            if terminators.contains(&first.as_str()) {
+                return Ok((nodes, Some(first)));
+            }
+            match first.as_str() {
+                "if" => nodes.push(self.parse_if(rest)?),
+                "for" => nodes.push(self.parse_for(&rest, line)?),
+                keyword if KEYWORDS.contains(&keyword) => {
+                    return Err(format!("第 {} 行: 语法错误: 意外的 {}", line, keyword));
+                }
+                _ => {
+                    nodes.push(Node::Command {
+                        text: segment.text.clone(),
+                        line,
+                    });
+                    self.pos += 1;
+                }
+            }
+        }
+        Ok((nodes, None))
+    }
+
+    // 解析 if/elif/else/fi
+    fn parse_if(&mut self, mut condition: String) -> Result<Node, String> {
+        let start = self.line();
+        let mut branches = Vec::new();
+        loop {
+            let line = self.line();
+            self.pos += 1;
+            let (mut cond, end) = self.parse_block(&["then"])?;
+            if !condition.is_empty() {
+                cond.insert(
+                    0,
+                    Node::Command {
+                        text: condition.clone(),
+                        line,
+                    },
+                );
+            }
+            if end.is_none() || cond.is_empty() {
+                return Err(format!("第 {} 行: 语法错误: if 缺少条件或 then", start));
+            }
+            self.pos += 1;
+
+            let (body, end) = self.parse_block(&["elif", "else", "fi"])?;
+            branches.push((cond, body));
+            match end.as_deref() {
+                Some("elif") => {
+                    let segment = &self.segments[self.pos];
+                    condition = split_first_word(&segment.text).1.to_string();
+                }
+                Some("else") => {
+                    self.pos += 1;
+                    let (otherwise, end) = self.parse_block(&["fi"])?;
+                    if end.is_none() {
+                        return Err(format!("第 {} 行: 语法错误: if 缺少 fi", start));
+                    }
+                    self.pos += 1;
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: Some(otherwise),
+                    });
+                }
+                Some(_) => {
+                    self.pos += 1;
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: None,
+                    });
+                }
+                None => return Err(format!("第 {} 行: 语法错误: if 缺少 fi", start)),
+            }
+        }
+    }
+
+    // 解析 for VAR in WORDS; do ...; done
+    fn parse_for(&mut self, header: &str, line: usize) -> Result<Node, String> {
+        let (var, rest) = split_first_word(header);
+        if !is_valid_var_name(var) {
+            return Err(format!("第 {} 行: 语法错误: 无效的循环变量 {}", line, var));
+        }
+        // 省略 `in` 时遍历所有位置参数
+        let words = match split_first_word(rest) {
+            ("in", words) => words.to_string(),
+            ("", _) => "$@".to_string(),
+            _ => return Err(format!("第 {} 行: 语法错误: for 缺少 in", line)),
+        };
+
+        self.pos += 1;
+        let (nodes, end) = self.parse_block(&["do"])?;
+        if end.is_none() || !nodes.is_empty() {
+            return Err(format!("第 {} 行: 语法错误: for 缺少 do", line));
+        }
+        self.pos += 1;
+        let (body, end) = self.parse_block(&["done"])?;
+        if end.is_none() {
+            return Err(format!("第 {} 行: 语法错误: for 缺少 done", line));
+        }
+        self.pos += 1;
+
+        Ok(Node::For {
+            var: var.to_string(),
+            words,
+            body,
+        })
+    }
+}
+
+// 执行一组语句
+fn exec_block<'a>(
+    ctx: &'a mut Context<'_>,
+    nodes: &'a [Node],
+) -> BoxFuture<'a, Result<Flow, String>> {
+    Box::pin(async move {
+        for node in nodes {
+            let flow = match node {
+                Node::Command { text, line } => exec_command(ctx, text, *line).await?,
+                Node::If {
+                    branches,
+                    otherwise,
+                } => {
+                    let mut flow = Flow::Normal;
+                    let mut matched = false;
+                    for (cond, body) in branches {
+                        if let Flow::Exit(status) = exec_block(ctx, cond).await? {
+                            return Ok(Flow::Exit(status));
+                        }
+                        if ctx.last_status == history::EXIT_SUCCESS {
+                            flow = exec_block(ctx, body).await?;
+                            matched = true;
+                            break;
+                        }
+                    }
+                    match otherwise {
+                        Some(body) if !matched => exec_block(ctx, body).await?,
+                        _ => {
+                            if !matched {
+                                ctx.last_status = history::EXIT_SUCCESS;
+                            }
+                            flow
+                        }
+                    }
+                }
+                Node::For { var, words, body } => {
+                    let env = expansion_env(ctx);
+                    let items = split_command(words, Some(&env))?;
+                    let mut flow = Flow::Normal;
+                    for item in items {
+                        ctx.locals.insert(var.clone(), item);
+                        flow = exec_block(ctx, body).await?;
+                        if let Flow::Exit(_) = flow {
+                            break;
+                        }
+                    }
+                    flow
+                }
+            };
+            if let Flow::Exit(status) = flow {
+                return Ok(Flow::Exit(status));
+            }
+        }
+        Ok(Flow::Normal)
+    })
+}
+
+// 会话环境变量叠加脚本局部变量，用于展开
+fn expansion_env(ctx: &Context<'_>) -> HashMap<String, String> {
+    let mut env = match ctx.state.session_manager.get(ctx.token) {
+        Some(session) => session.env,
+        None => HashMap::new(),
+    };
+    env.extend(ctx.locals.clone());
+    env.insert("?".to_string(), ctx.last_status.to_string());
+    env
+}
+
+// 执行单条命令
+async fn exec_command(ctx: &mut Context<'_>, text: &str, line: usize) -> Result<Flow, String> {
+    ctx.steps += 1;
+    if ctx.steps > ctx.max_steps() {
+        return Err(format!(
+            "第 {} 行: 超过最大执行步数 {}",
+            line,
+            ctx.max_steps()
+        ));
+    }
+    // 后台任务以执行步数作为进度
+    let command = ctx.command();
+    command.progress(ctx.steps as u64, None, format!("第 {} 行", line));
+
+    let env = expansion_env(ctx);
+    let words = split_command(text, Some(&env)).map_err(|e| format!("第 {} 行: {}", line, e))?;
+    let command = match words.first() {
+        Some(command) => command,
+        None => return Ok(Flow::Normal),
+    };
+    debug!("脚本第 {} 行: {:?}", line, words);
+
+    // 变量赋值只在脚本内有效
+    if words.len() == 1 {
+        if let Some((name, value)) = command.split_once('=') {
+            if is_valid_var_name(name) {
+                ctx.locals.insert(name.to_string(), value.to_string());
+                ctx.last_status = history::EXIT_SUCCESS;
+                return Ok(Flow::Normal);
+            }
+        }
+    }
+
+    let status = match command.as_str() {
+        "exit" => {
+            let status = match words.get(1) {
+                Some(code) => code
+                    .parse::<i32>()
+                    .map_err(|_| format!("第 {} 行: exit: 需要数字参数", line))?,
+                None => ctx.last_status,
+            };
+            return Ok(Flow::Exit(status));
+        }
+        "true" | ":" => history::EXIT_SUCCESS,
+        "false" => history::EXIT_FAILURE,
+        // 切换目录只改变脚本自己的工作目录，不写回会话
+        "cd" if words.get(1).map(String::as_str) != Some("--help") => {
+            let target = match words.get(1).map(String::as_str) {
+                None => env.get("HOME").cloned().unwrap_or_default(),
+                Some("-") => env.get("OLDPWD").cloned().unwrap_or_default(),
+                Some(path) => path.to_string(),
+            };
+            match cmd_cd::resolve_dir(&ctx.command(), &target).await {
+                Ok(path) => {
+                    let old = std::mem::replace(&mut ctx.cwd, path.clone());
+                    ctx.locals.insert("OLDPWD".to_string(), old);
+                    ctx.locals.insert("PWD".to_string(), path);
+                    history::EXIT_SUCCESS
+                }
+                Err(result) => ctx.record(result),
+            }
+        }
+        // 目录栈属于会话，脚本中只能用 cd
+        "pushd" | "popd" => {
+            return Err(format!(
+                "第 {} 行: {}: 不能在脚本中执行，请使用 cd",
+                line, command
+            ));
+        }
+        // 嵌套调用脚本
+        "sh" if words.get(1).map(String::as_str) != Some("--help") => match words.get(1) {
+            Some(_) => run_file(ctx, &words[1..], false).await?,
+            None => return Err(format!("第 {} 行: sh: 缺少脚本路径", line)),
+        },
+        path if path.contains('/') => run_file(ctx, &words, true).await?,
+        name => match ctx.state.command_registry.get_handler(name) {
+            // 修改会话状态的命令会影响前台，不能在后台任务中执行
+            Some(handler) if ctx.job.is_some() && handler.spec().foreground => {
+                return Err(format!("第 {} 行: {}: 不能在后台任务中执行", line, name));
+            }
+            // 与直接执行的命令经过相同的分发流程，支持 --help 和全局选项
+            Some(_) => {
+                let result = super::run_words(words.clone(), &ctx.command()).await;
+                ctx.record(result)
+            }
+            None => {
+                ctx.stderr
+                    .push(format!("第 {} 行: 未知命令: {}", line, name));
+                history::EXIT_NOT_FOUND
+            }
+        },
+    };
+
+    ctx.last_status = status;
+    Ok(Flow::Normal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(source: &str) -> Vec<(String, usize)> {
+        split_segments(source)
+            .unwrap()
+            .into_iter()
+            .map(|segment| (segment.text, segment.line))
+            .collect()
+    }
+
+    // 以紧凑的文本表示语法树，命令前带行号
+    fn outline(nodes: &[Node]) -> String {
+        let block = |nodes: &[Node]| format!("{{{}}}", outline(nodes));
+        nodes
+            .iter()
+            .map(|node| match node {
+                Node::Command { text, line } => format!("{}:{}", line, text),
+                Node::If {
+                    branches,
+                    otherwise,
+                } => {
+                    let parts: Vec<String> = branches
+                        .iter()
+                        .map(|(condition, body)| format!("{} {}", block(condition), block(body)))
+                        .collect();
+                    let mut text = format!("if {}", parts.join(" elif "));
+                    if let Some(otherwise) = otherwise {
+                        text.push_str(&format!(" else {}", block(otherwise)));
+                    }
+                    text
+                }
+                Node::For { var, words, body } => {
+                    format!("for {} in {} {}", var, words, block(body))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    #[test]
+    fn split_segments_handles_comments_quotes_and_continuations() {
+        let source = "echo a; echo b#c # 注释\n# 整行注释\necho 'x;y\nz' \\\n  more\nif true; then echo t; fi\n";
+        let expected = [
+            ("echo a", 1),
+            ("echo b#c", 1),
+            ("echo 'x;y\nz'   more", 3),
+            ("if true", 6),
+            ("then", 6),
+            ("echo t", 6),
+            ("fi", 6),
+        ];
+        let expected: Vec<(String, usize)> = expected
+            .iter()
+            .map(|(text, line)| (text.to_string(), *line))
+            .collect();
+        assert_eq!(segments(source), expected);
+        assert_eq!(
+            split_segments("echo ok\necho 'oops\n").unwrap_err(),
+            "第 2 行: 引号未闭合"
+        );
+    }
+
+    #[test]
+    fn parse_builds_if_and_for_blocks() {
+        let source = "\
+if test $1 = a
+then
+  echo A
+elif [ $1 = b ]; then echo B
+else
+  for x in 1 2; do echo $x; done
+fi
+for f; do cat $f; done
+";
+        assert_eq!(
+            outline(&parse(source).unwrap()),
+            "if {1:test $1 = a} {3:echo A} elif {4:[ $1 = b ]} {4:echo B} \
+             else {for x in 1 2 {6:echo $x}}; for f in $@ {8:cat $f}"
+        );
+    }
+
+    #[test]
+    fn parse_reports_syntax_errors_with_lines() {
+        let cases = [
+            ("echo a\nfi", "第 2 行: 语法错误: 意外的 fi"),
+            ("if true; then echo", "第 1 行: 语法错误: if 缺少 fi"),
+            ("if; then echo; fi", "第 1 行: 语法错误: if 缺少条件或 then"),
+            ("if true; then done; fi", "第 1 行: 语法错误: 意外的 done"),
+            ("for x in a b\necho $x", "第 1 行: 语法错误: for 缺少 do"),
+            ("for x in a; do echo", "第 1 行: 语法错误: for 缺少 done"),
+            (
+                "for 1x in a; do echo; done",
+                "第 1 行: 语法错误: 无效的循环变量 1x",
+            ),
+            (
+                "for x of a; do echo; done",
+                "第 1 行: 语法错误: for 缺少 in",
+            ),
+        ];
+        for (source, error) in cases {
+            assert_eq!(parse(source).unwrap_err(), error, "{}", source);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::output::{align, Record};
+use crate::vfs::model::{Role, User as VfsUser};
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+
+/// 一个活动的登录会话
+#[derive(Debug, Serialize)]
+pub struct ActiveSession {
+    pub user: String,
+    /// 客户端 IP，未知时为 `-`
+    pub from: String,
+    pub login_at: DateTime<Utc>,
+    /// 空闲时间，格式与 `w` 一致
+    pub idle: String,
+    pub idle_seconds: i64,
+}
+
+impl Record for ActiveSession {
+    const COLUMNS: &'static [&'static str] = &["user", "from", "login_at", "idle"];
+}
+
+/// `viewer` 能看到的会话：管理员可以看到所有会话，其他用户只能看到自己的
+pub fn visible_to(viewer: &VfsUser, user_id: &str) -> bool {
+    viewer.roles.contains(&Role::Admin) || user_id == viewer.id
+}
+
+/// 当前未过期的会话，按登录时间排序，只包含 `viewer` 能看到的会话
+pub fn active_sessions(state: &crate::AppState, viewer: &VfsUser) -> Vec<ActiveSession> {
+    let now = Utc::now();
+    let mut sessions: Vec<ActiveSession> = state
+        .session_manager
+        .list()
+        .into_iter()
+        .filter(|session| visible_to(viewer, &session.user_id))
+        .map(|session| {
+            let idle_seconds = (now - session.last_active).num_seconds().max(0);
+            ActiveSession {
+                user: session.env.get("USER").cloned().unwrap_or_default(),
+                from: session.from.unwrap_or_else(|| "-".to_string()),
+                login_at: session.login_at,
+                idle: format_idle(idle_seconds),
+                idle_seconds,
+            }
+        })
+        .collect();
+    sessions.sort_by_key(|session| session.login_at);
+    sessions
+}
+
+/// 以对齐的表格显示会话，`header` 为真时带表头
+pub fn render(sessions: &[ActiveSession], header: bool) -> String {
+    let mut rows = Vec::new();
+    if header {
+        rows.push(vec![
+            "USER".to_string(),
+            "FROM".to_string(),
+            "LOGIN@".to_string(),
+            "IDLE".to_string(),
+        ]);
+    }
+    rows.extend(sessions.iter().map(|session| {
+        vec![
+            session.user.clone(),
+            session.from.clone(),
+            session
+                .login_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            session.idle.clone(),
+        ]
+    }));
+    align(&rows)
+}
+
+/// 空闲时间：不到一分钟显示秒数，不到一小时显示 `分:秒`，不到一天显示 `时:分m`，否则显示天数
+pub fn format_idle(seconds: i64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}:{:02}", s / 60, s % 60),
+        s if s < 86400 => format!("{}:{:02}m", s / 3600, s % 3600 / 60),
+        s => format!("{}days", s / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: &str, roles: Vec<Role>) -> VfsUser {
+        VfsUser {
+            id: id.to_string(),
+            username: id.to_string(),
+            roles,
+            session: None,
+        }
+    }
+
+    #[test]
+    fn non_admins_only_see_their_own_sessions() {
+        let admin = user("admin", vec![Role::Admin]);
+        let author = user("bob", vec![Role::Author]);
+        assert!(visible_to(&admin, "bob") && visible_to(&admin, "admin"));
+        assert!(visible_to(&author, "bob"));
+        assert!(!visible_to(&author, "admin"));
+    }
+
+    #[test]
+    fn format_idle_picks_unit_by_length() {
+        assert_eq!(format_idle(0), "0s");
+        assert_eq!(format_idle(59), "59s");
+        assert_eq!(format_idle(60), "1:00");
+        assert_eq!(format_idle(3599), "59:59");
+        assert_eq!(format_idle(3600), "1:00m");
+        assert_eq!(format_idle(86399), "23:59m");
+        assert_eq!(format_idle(3 * 86400 + 5), "3days");
+    }
+}
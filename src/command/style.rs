@@ -0,0 +1,254 @@
+//! 输出中的语义样式
+//!
+//! 命令只标记文本的含义（目录、可执行文件、错误等），由各前端决定如何显示：HTTP 前端
+//! 渲染为 CSS 类，SSH 和 WebSocket 终端渲染为 ANSI 转义序列。标记使用 SO/SI 控制字符，
+//! 不会出现在正常的文本中；`--color=never` 时命令不输出标记。
+
+use serde::Serialize;
+
+// 标记的开始和结束，开始符后紧跟一个表示样式的字符
+const START: char = '\u{0e}';
+const END: char = '\u{0f}';
+
+const RESET: &str = "\x1b[0m";
+
+/// 语义样式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Directory,
+    Executable,
+    Error,
+    Highlight,
+    Link,
+}
+
+impl Style {
+    const ALL: [Style; 5] = [
+        Style::Directory,
+        Style::Executable,
+        Style::Error,
+        Style::Highlight,
+        Style::Link,
+    ];
+
+    fn code(self) -> char {
+        match self {
+            Style::Directory => 'd',
+            Style::Executable => 'x',
+            Style::Error => 'e',
+            Style::Highlight => 'h',
+            Style::Link => 'l',
+        }
+    }
+
+    fn from_code(code: char) -> Option<Style> {
+        Style::ALL.into_iter().find(|s| s.code() == code)
+    }
+
+    /// 前端使用的样式名，对应 CSS 类 `style-<name>`
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::Directory => "directory",
+            Style::Executable => "executable",
+            Style::Error => "error",
+            Style::Highlight => "highlight",
+            Style::Link => "link",
+        }
+    }
+
+    fn ansi(self) -> &'static str {
+        match self {
+            Style::Directory => "\x1b[1;34m",
+            Style::Executable => "\x1b[1;32m",
+            Style::Error => "\x1b[31m",
+            Style::Highlight => "\x1b[1;33m",
+            Style::Link => "\x1b[4;36m",
+        }
+    }
+}
+
+/// 为文本加上样式标记
+pub fn paint(style: Style, text: &str) -> String {
+    format!("{}{}{}{}", START, style.code(), text, END)
+}
+
+/// 一段带样式的文本
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Span {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<&'static str>,
+}
+
+// 按标记拆分文本，无法识别的标记按普通文本处理
+fn parse(text: &str) -> Vec<(Option<Style>, &str)> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(START) {
+        let marked = &rest[start + START.len_utf8()..];
+        let style = marked.chars().next().and_then(Style::from_code);
+        let end = marked.find(END);
+        match (style, end) {
+            (Some(style), Some(end)) => {
+                if start > 0 {
+                    parts.push((None, &rest[..start]));
+                }
+                parts.push((Some(style), &marked[1..end]));
+                rest = &marked[end + END.len_utf8()..];
+            }
+            _ => {
+                parts.push((None, &rest[..start + START.len_utf8()]));
+                rest = marked;
+            }
+        }
+    }
+    if !rest.is_empty() {
+        parts.push((None, rest));
+    }
+    parts
+}
+
+/// 文本是否包含样式标记
+pub fn has_markup(text: &str) -> bool {
+    text.contains(START)
+}
+
+/// 去掉样式标记，得到纯文本
+pub fn strip(text: &str) -> String {
+    if !has_markup(text) {
+        return text.to_string();
+    }
+    parse(text).into_iter().map(|(_, text)| text).collect()
+}
+
+/// 将样式标记转换为 ANSI 转义序列
+pub fn to_ansi(text: &str) -> String {
+    if !has_markup(text) {
+        return text.to_string();
+    }
+    parse(text)
+        .into_iter()
+        .map(|(style, text)| match style {
+            Some(style) => format!("{}{}{}", style.ansi(), text, RESET),
+            None => text.to_string(),
+        })
+        .collect()
+}
+
+/// 将样式标记转换为带样式名的片段，供 HTTP 前端渲染
+pub fn spans(text: &str) -> Vec<Span> {
+    parse(text)
+        .into_iter()
+        .map(|(style, text)| Span {
+            text: text.to_string(),
+            style: style.map(Style::name),
+        })
+        .collect()
+}
+
+/// 从参数中取出全局的 `--color[=auto|always|never]` 选项，返回是否输出样式标记
+///
+/// 只识别 `end` 之前的参数。样式标记最终由前端按终端能力渲染，`auto` 与 `always` 在服务端没有区别。
+pub fn take_color_option(words: &mut Vec<String>, mut end: usize) -> Result<bool, String> {
+    let mut color = true;
+    let mut index = 1;
+    while index < end {
+        let word = &words[index];
+        let when = match word.strip_prefix("--color") {
+            Some("") => "always",
+            Some(value) => match value.strip_prefix('=') {
+                Some(when) => when,
+                None => {
+                    index += 1;
+                    continue;
+                }
+            },
+            None => {
+                index += 1;
+                continue;
+            }
+        };
+        color = match when {
+            "auto" | "always" => true,
+            "never" => false,
+            _ => {
+                return Err(format!(
+                    "--color 的值 {} 无效，可选值: auto, always, never",
+                    when
+                ))
+            }
+        };
+        words.remove(index);
+        end -= 1;
+    }
+    Ok(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn markup_renders_as_plain_ansi_and_spans() {
+        let text = format!(
+            "{} -> {}!",
+            paint(Style::Directory, "docs"),
+            paint(Style::Link, "a")
+        );
+        assert!(has_markup(&text));
+        assert_eq!(strip(&text), "docs -> a!");
+        assert_eq!(
+            to_ansi(&text),
+            "\x1b[1;34mdocs\x1b[0m -> \x1b[4;36ma\x1b[0m!"
+        );
+        let span = |text: &str, style| Span {
+            text: text.to_string(),
+            style,
+        };
+        assert_eq!(
+            spans(&text),
+            [
+                span("docs", Some("directory")),
+                span(" -> ", None),
+                span("a", Some("link")),
+                span("!", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_or_unclosed_markup_is_plain_text() {
+        let unknown = format!("a{}zb{}", START, END);
+        assert_eq!(strip(&unknown), unknown);
+        let unclosed = format!("{}dtail", START);
+        assert_eq!(strip(&unclosed), unclosed);
+        assert_eq!(to_ansi("plain"), "plain");
+        assert_eq!(spans(""), []);
+    }
+
+    #[test]
+    fn take_color_option_removes_global_option() {
+        let mut args = words("ls --color=never -l");
+        assert_eq!(take_color_option(&mut args, 3), Ok(false));
+        assert_eq!(args, ["ls", "-l"]);
+
+        let mut args = words("ls --color --colorful");
+        assert_eq!(take_color_option(&mut args, 3), Ok(true));
+        assert_eq!(args, ["ls", "--colorful"]);
+
+        // 最后一次出现的值生效，`end` 之后的参数原样保留
+        let mut args = words("ls --color=never --color=auto -- --color=never");
+        assert_eq!(take_color_option(&mut args, 3), Ok(true));
+        assert_eq!(args, ["ls", "--", "--color=never"]);
+
+        let mut args = words("ls --color=sometimes");
+        assert_eq!(
+            take_color_option(&mut args, 2),
+            Err("--color 的值 sometimes 无效，可选值: auto, always, never".to_string())
+        );
+    }
+}
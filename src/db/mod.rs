@@ -1,7 +1,29 @@
-use crate::vfs::model::VfsError;
+use crate::vfs::model::{VfsError, DEFAULT_DIR_PERM};
 use log::{error, info};
 use sqlx::PgPool;
 
+// 初始化时创建的系统目录及其父目录，/usr/share/man 存放运维人员编写的手册页，
+// /var/archive 存放被删除用户归档的家目录
+const SYSTEM_DIRS: &[(&str, &str)] = &[
+    ("/usr", "/"),
+    ("/usr/share", "/usr"),
+    ("/usr/share/man", "/usr/share"),
+    ("/etc", "/"),
+    ("/var", "/"),
+    ("/var/archive", "/var"),
+    // `/proc` 的挂载点，其下的内容由 procfs 生成
+    ("/proc", "/"),
+];
+
+/// `sudo` 的策略文件，每行一个允许提权的用户名，`%角色` 表示该角色的所有用户
+pub const SUDOERS_PATH: &str = "/etc/sudoers";
+
+// 默认只允许管理员提权
+const DEFAULT_SUDOERS: &str = "# 允许使用 sudo 提权的用户，每行一个用户名，%admin 表示所有管理员\n%admin\n";
+
+// 策略文件只有所有者和管理员可读
+const SUDOERS_PERM: i16 = 0o440;
+
 pub struct DbInitializer {
     pool: PgPool,
 }
@@ -78,6 +100,38 @@ impl DbInitializer {
                 .map_err(|e| VfsError::StorageError(format!("添加role列失败: {}", e)))?;
         }
 
+        // 管理员重置密码后，用户下次登录必须修改密码
+        if !existing_columns.contains(&"must_change_password".to_string()) {
+            sqlx::query("ALTER TABLE users ADD COLUMN must_change_password BOOLEAN NOT NULL DEFAULT FALSE")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| VfsError::StorageError(format!("添加must_change_password列失败: {}", e)))?;
+        }
+
+        // 被锁定的账号不能登录
+        if !existing_columns.contains(&"locked".to_string()) {
+            sqlx::query("ALTER TABLE users ADD COLUMN locked BOOLEAN NOT NULL DEFAULT FALSE")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| VfsError::StorageError(format!("添加locked列失败: {}", e)))?;
+        }
+
+        // token 版本，修改密码等操作递增后此前签发的 token 全部失效
+        if !existing_columns.contains(&"token_version".to_string()) {
+            sqlx::query("ALTER TABLE users ADD COLUMN token_version INTEGER NOT NULL DEFAULT 0")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| VfsError::StorageError(format!("添加token_version列失败: {}", e)))?;
+        }
+
+        // 用 mesg n 关闭后不再接收其他用户的消息
+        if !existing_columns.contains(&"accept_messages".to_string()) {
+            sqlx::query("ALTER TABLE users ADD COLUMN accept_messages BOOLEAN NOT NULL DEFAULT TRUE")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| VfsError::StorageError(format!("添加accept_messages列失败: {}", e)))?;
+        }
+
         Ok(())
     }
 
@@ -103,6 +157,12 @@ impl DbInitializer {
         .await
         .map_err(|e| VfsError::StorageError(format!("创建表失败: {}", e)))?;
 
+        // 文件内容列
+        sqlx::query("ALTER TABLE vfs_nodes ADD COLUMN IF NOT EXISTS content TEXT")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| VfsError::StorageError(format!("添加content列失败: {}", e)))?;
+
         // 创建索引
         sqlx::query(
             r#"
@@ -126,9 +186,10 @@ impl DbInitializer {
             sqlx::query(
                 r#"
                 INSERT INTO vfs_nodes (parent_id, name, is_dir, owner_id, permissions)
-                VALUES (NULL, '/', true, 'system', 755)
+                VALUES (NULL, '/', true, 'system', $1)
             "#,
             )
+            .bind(DEFAULT_DIR_PERM)
             .execute(&self.pool)
             .await
             .map_err(|e| VfsError::StorageError(format!("创建根目录失败: {}", e)))?;
@@ -146,16 +207,62 @@ impl DbInitializer {
             sqlx::query(
                 r#"
             INSERT INTO vfs_nodes (parent_id, name, is_dir, owner_id, permissions)
-                SELECT id, 'home', true, 'system', 755
+                SELECT id, '/home', true, 'system', $1
             FROM vfs_nodes
                 WHERE parent_id IS NULL AND name = '/'
                 "#,
             )
+            .bind(DEFAULT_DIR_PERM)
             .execute(&self.pool)
             .await
             .map_err(|e| VfsError::StorageError(format!("创建home目录失败: {}", e)))?;
         }
 
+        // 早期版本以十进制 755 写入了根目录和 home 目录的权限，修正为 0o755
+        sqlx::query(
+            "UPDATE vfs_nodes SET permissions = $1 WHERE name IN ('/', '/home') AND permissions = 755",
+        )
+        .bind(DEFAULT_DIR_PERM)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("修正目录权限失败: {}", e)))?;
+
+        // 创建系统目录，父目录在前
+        for (path, parent) in SYSTEM_DIRS {
+            sqlx::query(
+                r#"
+                INSERT INTO vfs_nodes (parent_id, name, is_dir, owner_id, permissions)
+                SELECT id, $1, true, 'system', $3
+                FROM vfs_nodes
+                WHERE name = $2
+                    AND NOT EXISTS (SELECT 1 FROM vfs_nodes WHERE name = $1)
+                "#,
+            )
+            .bind(path)
+            .bind(parent)
+            .bind(DEFAULT_DIR_PERM)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| VfsError::StorageError(format!("创建目录 {} 失败: {}", path, e)))?;
+        }
+
+        // 创建默认的 sudo 策略文件
+        sqlx::query(
+            r#"
+            INSERT INTO vfs_nodes (parent_id, name, is_dir, owner_id, permissions, content)
+            SELECT id, $1, false, 'system', $2, $3
+            FROM vfs_nodes
+            WHERE name = '/etc'
+                AND NOT EXISTS (SELECT 1 FROM vfs_nodes WHERE name = $1)
+            "#,
+        )
+        .bind(SUDOERS_PATH)
+        .bind(SUDOERS_PERM)
+        .bind(DEFAULT_SUDOERS)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("创建 {} 失败: {}", SUDOERS_PATH, e)))?;
+
         Ok(())
     }
 
@@ -190,6 +297,14 @@ impl DbInitializer {
         .await
         .map_err(|e| VfsError::StorageError(format!("创建用户-组关系表失败: {}", e)))?;
 
+        // 组管理员可以管理组成员
+        sqlx::query(
+            "ALTER TABLE user_group_members ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("添加is_admin列失败: {}", e)))?;
+
         // 创建权限审计日志表
         sqlx::query(
             r#"
@@ -209,6 +324,134 @@ impl DbInitializer {
 
         Ok(())
     }
+
+    /// 初始化命令历史表
+    pub async fn init_history_tables(&self) -> Result<(), VfsError> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS command_history (
+                id BIGSERIAL PRIMARY KEY,
+                user_id VARCHAR NOT NULL,
+                command TEXT NOT NULL,
+                cwd TEXT NOT NULL,
+                exit_status INTEGER NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("创建命令历史表失败: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_command_history_user ON command_history(user_id, id)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("创建命令历史索引失败: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// 初始化登录历史表，记录每次成功和失败的登录
+    pub async fn init_login_tables(&self) -> Result<(), VfsError> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS login_history (
+                id BIGSERIAL PRIMARY KEY,
+                user_id VARCHAR,
+                username VARCHAR NOT NULL,
+                success BOOLEAN NOT NULL,
+                method VARCHAR NOT NULL,
+                via VARCHAR NOT NULL,
+                ip VARCHAR,
+                user_agent TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("创建登录历史表失败: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_login_history_username ON login_history(username, id)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("创建登录历史索引失败: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// 初始化文章表，记录文章的 slug、当前路径和发布时间，是公开博客的数据来源
+    ///
+    /// 撤回发布的文章保留原记录，`published_at` 置空，再次发布时沿用原来的 slug
+    pub async fn init_post_tables(&self) -> Result<(), VfsError> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS posts (
+                id BIGSERIAL PRIMARY KEY,
+                author_id VARCHAR NOT NULL,
+                slug VARCHAR NOT NULL UNIQUE,
+                title TEXT NOT NULL,
+                path TEXT NOT NULL UNIQUE,
+                published_at TIMESTAMPTZ,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("创建文章表失败: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_posts_published ON posts(published_at DESC)
+            WHERE published_at IS NOT NULL
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("创建文章索引失败: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// 初始化用户消息表，保存发给离线用户、尚未送达的消息
+    pub async fn init_message_tables(&self) -> Result<(), VfsError> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS messages (
+                id BIGSERIAL PRIMARY KEY,
+                recipient_id VARCHAR NOT NULL,
+                sender VARCHAR NOT NULL,
+                body TEXT NOT NULL,
+                broadcast BOOLEAN NOT NULL DEFAULT FALSE,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("创建消息表失败: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_messages_recipient ON messages(recipient_id, id)
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("创建消息索引失败: {}", e)))?;
+
+        Ok(())
+    }
 }
 
 /// 初始化所有数据库表
@@ -231,6 +474,26 @@ pub async fn initialize_db(pool: PgPool) -> Result<(), VfsError> {
         e
     })?;
 
+    initializer.init_history_tables().await.map_err(|e| {
+        error!("命令历史表初始化失败: {:?}", e);
+        e
+    })?;
+
+    initializer.init_login_tables().await.map_err(|e| {
+        error!("登录历史表初始化失败: {:?}", e);
+        e
+    })?;
+
+    initializer.init_message_tables().await.map_err(|e| {
+        error!("消息表初始化失败: {:?}", e);
+        e
+    })?;
+
+    initializer.init_post_tables().await.map_err(|e| {
+        error!("文章表初始化失败: {:?}", e);
+        e
+    })?;
+
     info!("数据库初始化完成");
     Ok(())
 }
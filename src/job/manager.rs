@@ -0,0 +1,218 @@
+use chrono::Utc;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::AbortHandle;
+
+use super::types::{Job, JobOutput, JobStatus, Progress, MAX_FINISHED_JOBS, MAX_RUNNING_JOBS};
+
+// 任务及其取消句柄
+struct Entry {
+    job: Job,
+    abort: Option<AbortHandle>,
+}
+
+// 一个会话的任务表
+#[derive(Default)]
+struct SessionJobs {
+    next_id: u64,
+    entries: Vec<Entry>,
+}
+
+impl SessionJobs {
+    fn get_mut(&mut self, id: u64) -> Option<&mut Entry> {
+        self.entries.iter_mut().find(|e| e.job.id == id)
+    }
+
+    // 只保留最近的若干个已结束任务
+    fn trim_finished(&mut self) {
+        let finished = self
+            .entries
+            .iter()
+            .filter(|e| e.job.status != JobStatus::Running)
+            .count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        self.entries.retain(|e| {
+            if excess > 0 && e.job.status != JobStatus::Running {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+}
+
+// 后台任务管理器，按 token 保存每个会话的任务
+pub struct JobManager {
+    sessions: Mutex<HashMap<String, SessionJobs>>,
+    // 有任务结束、被终止或被清理时唤醒等待者
+    changed: Notify,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        info!("初始化后台任务管理器");
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            changed: Notify::new(),
+        }
+    }
+
+    /// 登记新任务，返回任务编号
+    pub fn start(&self, token: &str, command: &str) -> Result<u64, String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let jobs = sessions.entry(token.to_string()).or_default();
+        let running = jobs
+            .entries
+            .iter()
+            .filter(|e| e.job.status == JobStatus::Running)
+            .count();
+        if running >= MAX_RUNNING_JOBS {
+            warn!("后台任务数量已达上限");
+            return Err(format!("最多同时运行 {} 个后台任务", MAX_RUNNING_JOBS));
+        }
+        jobs.next_id += 1;
+        let id = jobs.next_id;
+        jobs.entries.push(Entry {
+            job: Job::new(id, command),
+            abort: None,
+        });
+        info!("启动后台任务 [{}]: {}", id, command);
+        Ok(id)
+    }
+
+    /// 保存任务的取消句柄
+    pub fn attach(&self, token: &str, id: u64, abort: AbortHandle) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(entry) = sessions.get_mut(token).and_then(|jobs| jobs.get_mut(id)) {
+            if entry.job.status == JobStatus::Running {
+                entry.abort = Some(abort);
+            }
+        }
+    }
+
+    /// 更新任务进度
+    pub fn set_progress(&self, token: &str, id: u64, progress: Progress) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(entry) = sessions.get_mut(token).and_then(|jobs| jobs.get_mut(id)) {
+            entry.job.progress = progress;
+        }
+    }
+
+    /// 记录任务结束，已被终止的任务不再更新
+    pub fn finish(&self, token: &str, id: u64, output: JobOutput) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let jobs = match sessions.get_mut(token) {
+            Some(jobs) => jobs,
+            None => return,
+        };
+        if let Some(entry) = jobs.get_mut(id) {
+            if entry.job.status != JobStatus::Running {
+                return;
+            }
+            entry.job.status = if output.exit_code == 0 {
+                JobStatus::Done
+            } else {
+                JobStatus::Failed
+            };
+            debug!("后台任务 [{}] 结束，状态: {}", id, output.exit_code);
+            entry.job.finished_at = Some(Utc::now());
+            entry.job.output = Some(output);
+            entry.abort = None;
+        }
+        jobs.trim_finished();
+        self.changed.notify_waiters();
+    }
+
+    /// 会话的所有任务，按编号排序
+    pub fn list(&self, token: &str) -> Vec<Job> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(token)
+            .map(|jobs| jobs.entries.iter().map(|e| e.job.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 获取任务
+    pub fn get(&self, token: &str, id: u64) -> Option<Job> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(token)
+            .and_then(|jobs| jobs.entries.iter().find(|e| e.job.id == id))
+            .map(|e| e.job.clone())
+    }
+
+    /// 等待任务结束，最多等待 `timeout`
+    ///
+    /// 返回结束时的任务；超时时返回仍在运行的任务，任务不存在时返回 `None`。
+    pub async fn wait(&self, token: &str, id: u64, timeout: Duration) -> Option<Job> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // 先登记等待再检查状态，检查之后结束的任务也能唤醒
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            let job = self.get(token, id)?;
+            if job.status != JobStatus::Running {
+                return Some(job);
+            }
+            if tokio::time::timeout_at(deadline, changed).await.is_err() {
+                return self.get(token, id);
+            }
+        }
+    }
+
+    /// 最近启动的任务编号
+    pub fn latest(&self, token: &str) -> Option<u64> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(token)
+            .and_then(|jobs| jobs.entries.last())
+            .map(|e| e.job.id)
+    }
+
+    /// 终止运行中的任务
+    pub fn kill(&self, token: &str, id: u64) -> Result<Job, String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let entry = sessions
+            .get_mut(token)
+            .and_then(|jobs| jobs.get_mut(id))
+            .ok_or_else(|| format!("任务 {} 不存在", id))?;
+        if entry.job.status != JobStatus::Running {
+            return Err(format!("任务 {} {}", id, entry.job.status.label()));
+        }
+        if let Some(abort) = entry.abort.take() {
+            abort.abort();
+        }
+        entry.job.status = JobStatus::Cancelled;
+        entry.job.finished_at = Some(Utc::now());
+        info!("终止后台任务 [{}]: {}", id, entry.job.command);
+        let job = entry.job.clone();
+        drop(sessions);
+        self.changed.notify_waiters();
+        Ok(job)
+    }
+
+    /// 终止并移除会话的所有任务，用于登出
+    pub fn remove_session(&self, token: &str) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(jobs) = sessions.remove(token) {
+            for entry in jobs.entries {
+                if let Some(abort) = entry.abort {
+                    abort.abort();
+                }
+            }
+            info!("已清理会话的后台任务");
+        }
+        drop(sessions);
+        self.changed.notify_waiters();
+    }
+}
+
+/// 解析任务编号，支持 `1` 和 `%1` 两种写法
+pub fn parse_job_id(spec: &str) -> Option<u64> {
+    spec.strip_prefix('%').unwrap_or(spec).parse().ok()
+}
@@ -0,0 +1,5 @@
+mod manager;
+mod types;
+
+pub use manager::{parse_job_id, JobManager};
+pub use types::{Job, JobOutput, JobStatus, Progress};
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+// 每个会话同时运行的后台任务上限
+pub const MAX_RUNNING_JOBS: usize = 8;
+
+// 每个会话保留的已结束任务数量，超出时丢弃最早的
+pub const MAX_FINISHED_JOBS: usize = 20;
+
+/// 后台任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobStatus::Running => "运行中",
+            JobStatus::Done => "已完成",
+            JobStatus::Failed => "失败",
+            JobStatus::Cancelled => "已终止",
+        }
+    }
+}
+
+/// 任务进度，`total` 未知时只显示已完成的数量
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Progress {
+    pub current: u64,
+    pub total: Option<u64>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.total {
+            Some(total) if total > 0 => write!(
+                f,
+                "{}/{} ({}%)",
+                self.current,
+                total,
+                self.current.min(total) * 100 / total
+            )?,
+            _ => write!(f, "{}", self.current)?,
+        }
+        if !self.message.is_empty() {
+            write!(f, " {}", self.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// 任务结束后保存的输出
+#[derive(Debug, Clone, Serialize)]
+pub struct JobOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+    pub data: Option<Value>,
+}
+
+/// 后台任务，编号在会话内从 1 开始递增
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: u64,
+    pub command: String,
+    pub status: JobStatus,
+    pub progress: Progress,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    pub output: Option<JobOutput>,
+}
+
+impl Job {
+    pub fn new(id: u64, command: &str) -> Self {
+        Self {
+            id,
+            command: command.to_string(),
+            status: JobStatus::Running,
+            progress: Progress::default(),
+            started_at: Utc::now(),
+            finished_at: None,
+            output: None,
+        }
+    }
+}
@@ -2,8 +2,15 @@ mod auth;
 mod captcha;
 mod command;
 mod db;
+mod job;
 mod logger;
+mod procfs;
+mod session;
+mod ssh;
+#[cfg(test)]
+mod testing;
 mod vfs;
+mod ws;
 
 use actix_cors::Cors;
 use actix_web::{web, App, HttpServer};
@@ -11,12 +18,21 @@ use dotenv::dotenv;
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use std::fs;
 use std::io;
+use std::sync::Arc;
+use std::time::Duration;
 
 use auth::AuthManager;
 use captcha::{get_captcha, CaptchaManager};
-use command::{handle_command, CommandRegistry};
-use log::info;
+use command::{complete::handle_complete, handle_command, CommandRegistry};
+use job::JobManager;
+use log::{error, info};
+use procfs::ProcFs;
+use session::SessionManager;
 use vfs::{PostgresBackend, VfsManager};
+use ws::{handle_ws, ConnectionManager};
+
+// 过期数据的清理间隔
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
 
 // 应用状态
 struct AppState {
@@ -24,6 +40,9 @@ struct AppState {
     auth_manager: AuthManager,
     captcha_manager: CaptchaManager,
     command_registry: CommandRegistry,
+    session_manager: SessionManager,
+    job_manager: JobManager,
+    connection_manager: ConnectionManager,
     vfs_manager: VfsManager<PostgresBackend>,
 }
 
@@ -68,20 +87,42 @@ async fn main() -> std::io::Result<()> {
         auth_manager: AuthManager::new(),
         captcha_manager: CaptchaManager::new(),
         command_registry: CommandRegistry::new(),
+        session_manager: SessionManager::new(),
+        job_manager: JobManager::new(),
+        connection_manager: ConnectionManager::new(),
         vfs_manager,
     });
 
-    // 启动验证码清理任务
+    // 挂载 /proc，内容由应用状态实时生成
+    let proc_fs = ProcFs::new(Arc::downgrade(&app_state.clone().into_inner()));
+    app_state
+        .vfs_manager
+        .mount(procfs::MOUNT_POINT, Arc::new(proc_fs));
+
+    // 启动清理任务：定期清理过期的验证码、token 黑名单和会话
     let cleanup_state = app_state.clone();
     tokio::spawn(async move {
-        cleanup_state.captcha_manager.cleanup_expired_captchas();
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            cleanup_state.captcha_manager.cleanup_expired_captchas();
+            cleanup_state.auth_manager.cleanup_expired_tokens();
+            // 过期会话的后台任务一并清理
+            for token in cleanup_state.session_manager.cleanup_expired_sessions() {
+                cleanup_state.job_manager.remove_session(&token);
+            }
+        }
     });
 
-    // 启动 token 黑名单清理任务
-    let token_cleanup_state = app_state.clone();
-    tokio::spawn(async move {
-        token_cleanup_state.auth_manager.cleanup_expired_tokens();
-    });
+    // 设置了 SSH_ADDR 时启动 SSH 服务
+    if let Ok(addr) = std::env::var("SSH_ADDR") {
+        let ssh_state = app_state.clone().into_inner();
+        tokio::spawn(async move {
+            if let Err(e) = ssh::serve(ssh_state, &addr).await {
+                error!("SSH 服务启动失败: {}", e);
+            }
+        });
+    }
 
     println!("服务器启动在 http://0.0.0.0:8080");
 
@@ -96,6 +137,8 @@ async fn main() -> std::io::Result<()> {
                     .max_age(3600),
             )
             .route("/api/command", web::post().to(handle_command))
+            .route("/api/complete", web::post().to(handle_complete))
+            .route("/api/ws", web::get().to(handle_ws))
             .route("/api/captcha", web::get().to(get_captcha))
     })
     .bind(("0.0.0.0", 8080))?
@@ -0,0 +1,229 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use std::cmp::Reverse;
+use std::fmt::Write;
+use std::sync::Weak;
+
+use crate::command::sessions::visible_to;
+use crate::vfs::model::{User, VfsError, VfsNode};
+use crate::vfs::MountProvider;
+
+/// `/proc` 的挂载点
+pub const MOUNT_POINT: &str = "/proc";
+
+// 目录和文件的权限：所有人只读
+const DIR_PERM: i16 = 0o555;
+const FILE_PERM: i16 = 0o444;
+
+// 时间的显示格式
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// 全部节点，名称相对挂载点，挂载点本身为空字符串
+const ENTRIES: &[(&str, bool)] = &[
+    ("", true),
+    ("self", true),
+    ("self/status", false),
+    ("sessions", false),
+    ("stats", false),
+    ("uptime", false),
+    ("version", false),
+];
+
+/// 系统信息的只读视图，内容在读取时根据当前状态生成
+///
+/// - `uptime`：服务运行的秒数
+/// - `version`：服务的名称和版本
+/// - `sessions`：当前的登录会话，管理员之外的用户只能看到自己的会话
+/// - `stats`：用户、文件和文章的数量
+/// - `self/status`：当前用户、角色和登录的过期时间
+pub struct ProcFs {
+    state: Weak<crate::AppState>,
+    started: DateTime<Utc>,
+}
+
+impl ProcFs {
+    /// 应用状态中的 VFS 管理器持有挂载的 `ProcFs`，这里只保留弱引用
+    pub fn new(state: Weak<crate::AppState>) -> Self {
+        Self {
+            state,
+            started: Utc::now(),
+        }
+    }
+
+    fn node(&self, index: usize) -> VfsNode {
+        let (name, is_dir) = ENTRIES[index];
+        VfsNode {
+            id: -(index as i64) - 1,
+            parent_id: None,
+            name: name.to_string(),
+            is_dir,
+            owner_id: "system".to_string(),
+            permissions: if is_dir { DIR_PERM } else { FILE_PERM },
+            size: 0,
+            created_at: self.started,
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn find(&self, path: &str) -> Result<usize, VfsError> {
+        ENTRIES
+            .iter()
+            .position(|(name, _)| *name == path)
+            .ok_or_else(|| VfsError::NodeNotFound(absolute(path)))
+    }
+
+    fn uptime(&self) -> String {
+        let elapsed = Utc::now() - self.started;
+        format!("{:.2}\n", elapsed.num_milliseconds() as f64 / 1000.0)
+    }
+
+    // 管理员之外的用户只能看到自己的会话
+    fn sessions(&self, state: &crate::AppState, user: &User) -> String {
+        let mut sessions = state.session_manager.list();
+        sessions.retain(|session| visible_to(user, &session.user_id));
+        sessions.sort_by_key(|session| Reverse(session.last_active));
+        let mut text = String::from("USER\tLAST_ACTIVE\tEXPIRES\n");
+        for session in sessions {
+            let user = session.env.get("USER").map_or("?", String::as_str);
+            let _ = writeln!(
+                text,
+                "{}\t{}\t{}",
+                user,
+                session.last_active.format(TIME_FORMAT),
+                session.expires_at.format(TIME_FORMAT)
+            );
+        }
+        text
+    }
+
+    async fn stats(&self, state: &crate::AppState) -> Result<String, VfsError> {
+        let nodes = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE is_dir) AS "directories!",
+                COUNT(*) FILTER (WHERE NOT is_dir) AS "files!",
+                COALESCE(SUM(octet_length(content)), 0)::BIGINT AS "bytes!",
+                COUNT(*) FILTER (WHERE NOT is_dir AND name LIKE '/home/%/Documents/drafts/%') AS "drafts!",
+                (SELECT COUNT(*) FROM posts WHERE published_at IS NOT NULL) AS "published!"
+            FROM vfs_nodes
+            "#
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| {
+            error!("统计节点失败: {}", e);
+            VfsError::StorageError(format!("统计节点失败: {}", e))
+        })?;
+        let users = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM users"#)
+            .fetch_one(&state.db)
+            .await
+            .map_err(|e| {
+                error!("统计用户失败: {}", e);
+                VfsError::StorageError(format!("统计用户失败: {}", e))
+            })?;
+
+        Ok(format!(
+            "users:\t{}\nsessions:\t{}\ndirectories:\t{}\nfiles:\t{}\nbytes:\t{}\ndrafts:\t{}\npublished:\t{}\n",
+            users,
+            state.session_manager.list().len(),
+            nodes.directories,
+            nodes.files,
+            nodes.bytes,
+            nodes.drafts,
+            nodes.published
+        ))
+    }
+
+    fn status(&self, state: &crate::AppState, user: &User) -> String {
+        let roles: Vec<String> = user
+            .roles
+            .iter()
+            .map(|role| format!("{:?}", role))
+            .collect();
+        let sessions = state
+            .session_manager
+            .list()
+            .into_iter()
+            .filter(|session| session.user_id == user.id)
+            .count();
+        // 过期时间取自调用者自己的会话
+        let expires = user
+            .session
+            .as_deref()
+            .and_then(|token| state.session_manager.get(token))
+            .map_or("-".to_string(), |session| {
+                session.expires_at.format(TIME_FORMAT).to_string()
+            });
+        format!(
+            "Name:\t{}\nUid:\t{}\nRoles:\t{}\nSessions:\t{}\nTokenExpires:\t{}\n",
+            user.username,
+            user.id,
+            roles.join(" "),
+            sessions,
+            expires
+        )
+    }
+}
+
+#[async_trait]
+impl MountProvider for ProcFs {
+    async fn get_node(&self, path: &str) -> Result<VfsNode, VfsError> {
+        let index = self.find(path)?;
+        Ok(self.node(index))
+    }
+
+    async fn list(&self, path: &str) -> Result<Vec<VfsNode>, VfsError> {
+        let index = self.find(path)?;
+        if !ENTRIES[index].1 {
+            return Err(VfsError::InvalidOperation(format!(
+                "不是目录: {}",
+                absolute(path)
+            )));
+        }
+        Ok((0..ENTRIES.len())
+            .filter(|&i| i != index && parent(ENTRIES[i].0) == path)
+            .map(|i| self.node(i))
+            .collect())
+    }
+
+    async fn read_file(&self, user: &User, path: &str) -> Result<String, VfsError> {
+        debug!("读取 {}，用户: {}", absolute(path), user.username);
+        let state = self
+            .state
+            .upgrade()
+            .ok_or_else(|| VfsError::StorageError("服务正在关闭".to_string()))?;
+        match path {
+            "uptime" => Ok(self.uptime()),
+            "version" => Ok(format!(
+                "{} version {}\n",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            )),
+            "sessions" => Ok(self.sessions(&state, user)),
+            "stats" => self.stats(&state).await,
+            "self/status" => Ok(self.status(&state, user)),
+            _ => {
+                let index = self.find(path)?;
+                Err(VfsError::InvalidOperation(if ENTRIES[index].1 {
+                    format!("是一个目录: {}", absolute(path))
+                } else {
+                    format!("无法读取: {}", absolute(path))
+                }))
+            }
+        }
+    }
+}
+
+// 相对挂载点的父目录
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn absolute(path: &str) -> String {
+    if path.is_empty() {
+        MOUNT_POINT.to_string()
+    } else {
+        format!("{}/{}", MOUNT_POINT, path)
+    }
+}
@@ -0,0 +1,281 @@
+use chrono::{DateTime, TimeZone, Utc};
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::types::{Session, MAX_DIR_STACK, MAX_ENV_VARS, MAX_ENV_VAR_SIZE, READONLY_VARS};
+use crate::auth::Claims;
+
+// 会话管理器，按 token 保存每个登录会话的 shell 状态
+pub struct SessionManager {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        info!("初始化会话管理器");
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 校验并获取 token 对应的会话，不存在时根据 claims 创建，`from` 为客户端 IP
+    ///
+    /// 会话与创建时的用户绑定，token 对应的用户不一致时拒绝访问。
+    pub fn validate(
+        &self,
+        token: &str,
+        claims: &Claims,
+        from: Option<&str>,
+    ) -> Result<Session, String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.entry(token.to_string()).or_insert_with(|| {
+            info!("为用户 {} 创建新会话", claims.username);
+            let expires_at = Utc
+                .timestamp_opt(claims.exp as i64, 0)
+                .single()
+                .unwrap_or_else(Utc::now);
+            Session::new(&claims.sub, &claims.username, expires_at, from)
+        });
+
+        if session.user_id != claims.sub {
+            warn!("会话用户不匹配: {} != {}", session.user_id, claims.sub);
+            return Err("会话与当前用户不匹配".to_string());
+        }
+        if session.expires_at <= Utc::now() {
+            warn!("会话已过期");
+            sessions.remove(token);
+            return Err("会话已过期，请重新登录".to_string());
+        }
+
+        // 会话可能由不带客户端信息的请求（如补全）创建
+        if session.from.is_none() {
+            session.from = from.map(|from| from.to_string());
+        }
+        session.last_active = Utc::now();
+        Ok(session.clone())
+    }
+
+    /// 获取 token 对应的会话
+    pub fn get(&self, token: &str) -> Option<Session> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions.get(token).cloned()
+    }
+
+    /// 所有未过期的会话
+    pub fn list(&self) -> Vec<Session> {
+        let sessions = self.sessions.lock().unwrap();
+        let now = Utc::now();
+        sessions
+            .values()
+            .filter(|session| session.expires_at > now)
+            .cloned()
+            .collect()
+    }
+
+    /// 设置环境变量，变量的数量和长度有上限
+    pub fn set_var(&self, token: &str, name: &str, value: &str) -> Result<(), String> {
+        // 值可能包含敏感信息，日志中只记录变量名
+        debug!("设置环境变量: {}", name);
+        if !is_valid_var_name(name) {
+            return Err(format!("无效的变量名: {}", name));
+        }
+        if READONLY_VARS.contains(&name) {
+            warn!("尝试修改只读变量: {}", name);
+            return Err(format!("{}: 只读变量", name));
+        }
+        if name.len() + value.len() > MAX_ENV_VAR_SIZE {
+            warn!("环境变量过长: {}", name);
+            return Err(format!(
+                "{}: 变量过长（最多 {} 字节）",
+                name, MAX_ENV_VAR_SIZE
+            ));
+        }
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(token)
+            .ok_or_else(|| "会话不存在".to_string())?;
+        if !session.env.contains_key(name) && session.env.len() >= MAX_ENV_VARS {
+            warn!("环境变量数量已达上限");
+            return Err(format!("环境变量过多（最多 {} 个）", MAX_ENV_VARS));
+        }
+        session.env.insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    /// 删除环境变量
+    pub fn unset_var(&self, token: &str, name: &str) -> Result<(), String> {
+        debug!("删除环境变量: {}", name);
+        if READONLY_VARS.contains(&name) {
+            warn!("尝试删除只读变量: {}", name);
+            return Err(format!("{}: 只读变量", name));
+        }
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(token)
+            .ok_or_else(|| "会话不存在".to_string())?;
+        session.env.remove(name);
+        Ok(())
+    }
+
+    /// 切换工作目录
+    pub fn change_dir(&self, token: &str, path: &str) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(token)
+            .ok_or_else(|| "会话不存在".to_string())?;
+        debug!("会话目录切换: {} -> {}", session.cwd, path);
+        session.set_cwd(path);
+        Ok(())
+    }
+
+    /// 将当前目录压入目录栈并切换到新目录，返回切换后的目录栈
+    pub fn push_dir(&self, token: &str, path: &str) -> Result<Vec<String>, String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(token)
+            .ok_or_else(|| "会话不存在".to_string())?;
+        if session.dir_stack.len() >= MAX_DIR_STACK {
+            return Err("目录栈已满".to_string());
+        }
+        let old = session.cwd.clone();
+        session.dir_stack.push(old);
+        session.set_cwd(path);
+        debug!("压入目录栈: {}", path);
+        Ok(session.dir_stack.clone())
+    }
+
+    /// 交换当前目录与栈顶目录，返回切换后的目录栈
+    pub fn swap_dir(&self, token: &str) -> Result<Vec<String>, String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(token)
+            .ok_or_else(|| "会话不存在".to_string())?;
+        let top = session
+            .dir_stack
+            .pop()
+            .ok_or_else(|| "目录栈为空".to_string())?;
+        let old = session.cwd.clone();
+        session.dir_stack.push(old);
+        session.set_cwd(&top);
+        Ok(session.dir_stack.clone())
+    }
+
+    /// 弹出栈顶目录并切换过去，返回新的工作目录
+    pub fn pop_dir(&self, token: &str) -> Result<String, String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(token)
+            .ok_or_else(|| "会话不存在".to_string())?;
+        let top = session
+            .dir_stack
+            .pop()
+            .ok_or_else(|| "目录栈为空".to_string())?;
+        debug!("弹出目录栈: {}", top);
+        session.set_cwd(&top);
+        Ok(top)
+    }
+
+    /// 清空目录栈
+    pub fn clear_dirs(&self, token: &str) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(token)
+            .ok_or_else(|| "会话不存在".to_string())?;
+        session.dir_stack.clear();
+        Ok(())
+    }
+
+    /// 记录 `sudo` 验证通过，在 `until` 之前无需再次输入密码
+    pub fn elevate(&self, token: &str, until: DateTime<Utc>) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(token)
+            .ok_or_else(|| "会话不存在".to_string())?;
+        session.elevated_until = Some(until);
+        Ok(())
+    }
+
+    /// `sudo` 的密码验证是否仍然有效
+    pub fn is_elevated(&self, token: &str) -> bool {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(token)
+            .and_then(|session| session.elevated_until)
+            .is_some_and(|until| until > Utc::now())
+    }
+
+    /// 清除 `sudo` 的密码验证缓存
+    pub fn drop_elevation(&self, token: &str) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(session) = sessions.get_mut(token) {
+            session.elevated_until = None;
+        }
+    }
+
+    /// 移除会话
+    pub fn remove(&self, token: &str) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.remove(token).is_some() {
+            info!("会话已移除");
+        }
+    }
+
+    /// 把会话转移到新签发的 token，保留 shell 状态，过期时间改为新 token 的
+    pub fn rename(&self, token: &str, new_token: &str, claims: &Claims) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(mut session) = sessions.remove(token) {
+            if let Some(expires_at) = Utc.timestamp_opt(claims.exp as i64, 0).single() {
+                session.expires_at = expires_at;
+            }
+            sessions.insert(new_token.to_string(), session);
+            debug!("会话已转移到新的 token");
+        }
+    }
+
+    /// 移除用户除 `keep` 之外的所有会话，返回被移除会话的 token
+    pub fn remove_user(&self, user_id: &str, keep: Option<&str>) -> Vec<String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let tokens: Vec<String> = sessions
+            .iter()
+            .filter(|(token, session)| {
+                session.user_id == user_id && keep != Some(token.as_str())
+            })
+            .map(|(token, _)| token.clone())
+            .collect();
+        for token in &tokens {
+            sessions.remove(token);
+        }
+        info!("移除了用户 {} 的 {} 个会话", user_id, tokens.len());
+        tokens
+    }
+
+    /// 清理过期的会话，返回被清理会话的 token
+    pub fn cleanup_expired_sessions(&self) -> Vec<String> {
+        debug!("清理过期的会话");
+        let mut sessions = self.sessions.lock().unwrap();
+        let now = Utc::now();
+        let tokens: Vec<String> = sessions
+            .iter()
+            .filter(|(_, session)| session.expires_at <= now)
+            .map(|(token, _)| token.clone())
+            .collect();
+        for token in &tokens {
+            sessions.remove(token);
+        }
+        if !tokens.is_empty() {
+            info!("清理了 {} 个过期的会话", tokens.len());
+        }
+        tokens
+    }
+}
+
+/// 变量名只能由字母、数字和下划线组成，且不能以数字开头
+pub fn is_valid_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,5 @@
+mod manager;
+mod types;
+
+pub use manager::{is_valid_var_name, SessionManager};
+pub use types::Session;
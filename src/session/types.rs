@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+// 由系统维护的环境变量，不允许用户通过 export/unset 修改
+pub const READONLY_VARS: [&str; 4] = ["HOME", "USER", "PWD", "OLDPWD"];
+
+// 目录栈的最大深度
+pub const MAX_DIR_STACK: usize = 32;
+
+// 每个会话环境变量的最大数量（包括只读变量）
+pub const MAX_ENV_VARS: usize = 64;
+
+// 单个环境变量名和值的最大总长度（字节）
+pub const MAX_ENV_VAR_SIZE: usize = 4096;
+
+// 会话结构体，保存服务端维护的 shell 状态
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub user_id: String,
+    pub cwd: String,
+    pub env: HashMap<String, String>,
+    pub dir_stack: Vec<String>,
+    /// 会话建立（登录后第一次执行命令）的时间
+    pub login_at: DateTime<Utc>,
+    pub last_active: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// 客户端 IP
+    pub from: Option<String>,
+    /// `sudo` 验证密码后在此之前无需再次输入密码
+    pub elevated_until: Option<DateTime<Utc>>,
+}
+
+impl Session {
+    pub fn new(
+        user_id: &str,
+        username: &str,
+        expires_at: DateTime<Utc>,
+        from: Option<&str>,
+    ) -> Self {
+        let home = format!("/home/{}", username);
+        Self {
+            user_id: user_id.to_string(),
+            env: Self::default_env(username, &home),
+            cwd: home,
+            dir_stack: Vec::new(),
+            login_at: Utc::now(),
+            last_active: Utc::now(),
+            expires_at,
+            from: from.map(|from| from.to_string()),
+            elevated_until: None,
+        }
+    }
+
+    /// 访客会话，不保存在服务端
+    pub fn guest() -> Self {
+        let home = "/home/guest".to_string();
+        Self {
+            user_id: "guest".to_string(),
+            env: Self::default_env("guest", &home),
+            cwd: home,
+            dir_stack: Vec::new(),
+            login_at: Utc::now(),
+            last_active: Utc::now(),
+            expires_at: Utc::now(),
+            from: None,
+            elevated_until: None,
+        }
+    }
+
+    /// 生成默认环境变量
+    pub fn default_env(username: &str, cwd: &str) -> HashMap<String, String> {
+        let home = format!("/home/{}", username);
+        let mut env = HashMap::new();
+        env.insert("HOME".to_string(), home);
+        env.insert("USER".to_string(), username.to_string());
+        env.insert("PWD".to_string(), cwd.to_string());
+        env.insert("OLDPWD".to_string(), cwd.to_string());
+        env
+    }
+
+    /// 切换工作目录，同时更新 PWD 与 OLDPWD
+    pub fn set_cwd(&mut self, path: &str) {
+        let old = std::mem::replace(&mut self.cwd, path.to_string());
+        self.env.insert("OLDPWD".to_string(), old);
+        self.env.insert("PWD".to_string(), path.to_string());
+    }
+}
@@ -0,0 +1,384 @@
+use log::{debug, info, warn};
+use russh::server::Msg;
+use russh::{Channel, ChannelMsg};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use super::connection::Account;
+use super::editor::{EditorEvent, LineEditor};
+use crate::auth::validate_token;
+use crate::command::{self, complete, history, message, style, ClientInfo, SideEffect};
+use crate::ws::ServerMessage;
+
+// 编辑器预载的历史条数
+const HISTORY_SIZE: i64 = 500;
+
+// 扩展数据类型：标准错误输出（RFC 4254 第 5.2 节）
+const EXTENDED_DATA_STDERR: u32 = 1;
+
+type ChannelResult<T> = Result<T, russh::Error>;
+
+/// 会话通道：交互式 shell 或单条命令，在独立的任务中运行
+///
+/// 命令执行期间 russh 继续处理连接上的其他消息，输出经通道发送，流量控制由 russh 负责。
+pub struct ShellChannel {
+    state: Arc<crate::AppState>,
+    account: Account,
+    client: ClientInfo,
+    token: String,
+    // 连接签发的所有 token，`login` 切换的 token 也登记在这里，连接结束时一并作废
+    tokens: Arc<Mutex<Vec<String>>>,
+    channel: Channel<Msg>,
+    pty: bool,
+    // 需要输入密码后重新执行的命令（如 sudo）及提示文字
+    password_prompt: Option<(String, String)>,
+    // 在连接管理器中登记的编号及推送的事件，交互式 shell 在显示提示符前输出其中的消息
+    events: Option<(u64, mpsc::UnboundedReceiver<ServerMessage>)>,
+}
+
+impl ShellChannel {
+    pub fn new(
+        state: Arc<crate::AppState>,
+        account: Account,
+        client: ClientInfo,
+        token: String,
+        tokens: Arc<Mutex<Vec<String>>>,
+        channel: Channel<Msg>,
+    ) -> Self {
+        ShellChannel {
+            state,
+            account,
+            client,
+            token,
+            tokens,
+            channel,
+            pty: false,
+            password_prompt: None,
+            events: None,
+        }
+    }
+
+    /// 处理通道上的请求和输入，直到通道关闭
+    pub async fn run(mut self) {
+        if let Err(e) = self.serve().await {
+            debug!("SSH 通道异常结束: {}", e);
+        }
+        if let Some((id, _)) = self.events.take() {
+            self.state.connection_manager.unregister(id);
+        }
+    }
+
+    async fn serve(&mut self) -> ChannelResult<()> {
+        let mut shell: Option<LineEditor> = None;
+        while let Some(message) = self.channel.wait().await {
+            match message {
+                ChannelMsg::RequestPty { .. } => self.pty = true,
+                ChannelMsg::RequestShell { .. } if shell.is_none() => {
+                    shell = Some(self.start_shell().await?);
+                }
+                ChannelMsg::Exec { command, .. } if shell.is_none() => {
+                    let command = String::from_utf8_lossy(&command).into_owned();
+                    let (exit_code, _) = self.run_line(&command).await?;
+                    return self.finish(exit_code).await;
+                }
+                ChannelMsg::Data { data } => {
+                    if let Some(editor) = &mut shell {
+                        if !self.shell_input(editor, &data).await? {
+                            return self.finish(0).await;
+                        }
+                    }
+                }
+                ChannelMsg::Eof if shell.is_some() => return self.finish(0).await,
+                ChannelMsg::Close => return Ok(()),
+                message => debug!("忽略通道消息: {:?}", message),
+            }
+        }
+        Ok(())
+    }
+
+    // 发送退出状态并关闭通道
+    async fn finish(&mut self, exit_code: i32) -> ChannelResult<()> {
+        self.channel.exit_status(exit_code as u32).await?;
+        self.channel.eof().await?;
+        self.channel.close().await
+    }
+
+    async fn send(&self, data: &[u8], stderr: bool) -> ChannelResult<()> {
+        if stderr {
+            self.channel.extended_data(EXTENDED_DATA_STDERR, data).await
+        } else {
+            self.channel.data(data).await
+        }
+    }
+
+    // 发送文本，在伪终端上把换行转换为回车换行并以 ANSI 转义显示样式，否则去掉样式标记
+    async fn write_text(&self, text: &str, stderr: bool) -> ChannelResult<()> {
+        if self.pty {
+            let text = style::to_ansi(text).replace('\n', "\r\n");
+            self.send(text.as_bytes(), false).await
+        } else {
+            self.send(style::strip(text).as_bytes(), stderr).await
+        }
+    }
+
+    fn prompt(&self) -> String {
+        let (username, home) = match validate_token(&self.token) {
+            Ok(claims) => {
+                let home = format!("/home/{}", claims.username);
+                (claims.username, home)
+            }
+            Err(_) => ("guest".to_string(), "/".to_string()),
+        };
+        let cwd = self
+            .state
+            .session_manager
+            .get(&self.token)
+            .map(|s| s.cwd)
+            .unwrap_or_else(|| home.clone());
+        let cwd = match cwd.strip_prefix(&home) {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("~{}", rest),
+            _ => cwd,
+        };
+        format!(
+            "\x1b[1;32m{}@blog\x1b[0m:\x1b[1;34m{}\x1b[0m$ ",
+            username, cwd
+        )
+    }
+
+    // 执行一行命令，stdout 流式发送；返回退出状态和会话是否继续
+    async fn run_line(&mut self, line: &str) -> ChannelResult<(i32, bool)> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok((0, true));
+        }
+        if line == "exit" {
+            return Ok((0, false));
+        }
+        info!(
+            "收到 SSH 命令: {}",
+            self.state.command_registry.redact(line)
+        );
+
+        let (sink, mut chunks) = mpsc::unbounded_channel::<String>();
+        let state = self.state.clone();
+        let command = line.to_string();
+        let token = self.token.clone();
+        let client = self.client.clone();
+        let execute = async move {
+            let result = command::execute(&state, &command, &token, "", Some(&sink), &client).await;
+            drop(sink);
+            result
+        };
+        let forward = async {
+            let mut streamed = false;
+            while let Some(text) = chunks.recv().await {
+                streamed = true;
+                self.write_text(&format!("{}\n", text.trim_end_matches('\n')), false)
+                    .await?;
+            }
+            Ok::<_, russh::Error>(streamed)
+        };
+        let (result, streamed) = tokio::join!(execute, forward);
+        let streamed = streamed?;
+
+        if !streamed && !result.stdout.is_empty() {
+            self.write_text(&format!("{}\n", result.stdout), false)
+                .await?;
+        }
+        // 在伪终端上由行编辑器提示输入密码，不显示错误
+        let password_prompt = result.effects.iter().find_map(|effect| match effect {
+            SideEffect::PromptPassword(prompt) if self.pty && line.starts_with("sudo ") => {
+                Some(prompt.clone())
+            }
+            _ => None,
+        });
+        if !result.stderr.is_empty() && password_prompt.is_none() {
+            self.write_text(&format!("{}\n", result.stderr), true)
+                .await?;
+        }
+        if let Some(prompt) = password_prompt {
+            self.password_prompt = Some((prompt, line.to_string()));
+        }
+
+        let mut keep_going = true;
+        for effect in &result.effects {
+            match effect {
+                SideEffect::ClearScreen => self.write_text("\x1b[H\x1b[2J", false).await?,
+                SideEffect::SetToken(token) => {
+                    self.token = token.clone();
+                    self.tokens.lock().unwrap().push(token.clone());
+                    if let Some((id, _)) = &self.events {
+                        self.state.connection_manager.set_token(*id, token);
+                    }
+                }
+                // 登出即结束 SSH 会话
+                SideEffect::ClearToken => keep_going = false,
+                SideEffect::ChangeDir(_) | SideEffect::PromptPassword(_) => {}
+            }
+        }
+        Ok((result.exit_code, keep_going))
+    }
+
+    // 处理 Tab 补全：唯一候选直接替换，多个候选先补全公共前缀，否则列出候选项
+    async fn complete(&self, editor: &mut LineEditor) -> ChannelResult<()> {
+        let (line, cursor) = editor.line();
+        let completion = complete::complete(&self.state, &line, cursor, &self.token).await;
+        let candidates = &completion.candidates;
+        let word_len = completion.end - completion.start;
+
+        let output = match candidates.len() {
+            0 => "\x07".to_string(),
+            1 => {
+                let mut value = candidates[0].value.clone();
+                if candidates[0].kind != "directory" {
+                    value.push(' ');
+                }
+                editor.replace(completion.start, completion.end, &value)
+            }
+            _ => {
+                let first = &candidates[0].value;
+                let common = candidates.iter().skip(1).fold(first.len(), |len, c| {
+                    first[..len]
+                        .char_indices()
+                        .zip(c.value.chars())
+                        .find(|((_, a), b)| a != b)
+                        .map(|((i, _), _)| i)
+                        .unwrap_or(len.min(c.value.len()))
+                });
+                let prefix = &first[..common];
+                if prefix.chars().count() > word_len {
+                    editor.replace(completion.start, completion.end, prefix)
+                } else {
+                    let values: Vec<&str> = candidates.iter().map(|c| c.value.as_str()).collect();
+                    format!("\r\n{}{}", values.join("  "), editor.redisplay())
+                }
+            }
+        };
+        self.send(output.as_bytes(), false).await
+    }
+
+    // 处理交互式 shell 的输入，返回会话是否继续
+    async fn shell_input(&mut self, editor: &mut LineEditor, data: &[u8]) -> ChannelResult<bool> {
+        let mut events = editor.feed(data);
+        loop {
+            // 提交、补全等事件处理完后继续处理暂存的输入
+            let mut resume = false;
+            for event in events {
+                match event {
+                    // 没有伪终端时客户端自己回显，不需要重绘
+                    EditorEvent::Output(text) if self.pty => {
+                        self.send(text.as_bytes(), false).await?
+                    }
+                    EditorEvent::Output(_) => {}
+                    EditorEvent::Submit(line) => {
+                        // 输入的是密码时带上密码重新执行原来的命令
+                        let line = match self.password_prompt.take() {
+                            Some((_, command)) => with_password(&command, &line),
+                            None => line,
+                        };
+                        let (_, keep_going) = self.run_line(&line).await?;
+                        if !keep_going {
+                            return Ok(false);
+                        }
+                        self.start_line(editor).await?;
+                        resume = true;
+                    }
+                    EditorEvent::Cancel => {
+                        self.password_prompt = None;
+                        self.start_line(editor).await?;
+                        resume = true;
+                    }
+                    EditorEvent::Exit => return Ok(false),
+                    EditorEvent::Complete => {
+                        self.complete(editor).await?;
+                        resume = true;
+                    }
+                    EditorEvent::ClearScreen => {
+                        let output = format!("\x1b[H\x1b[2J{}", editor.render());
+                        self.send(output.as_bytes(), false).await?;
+                    }
+                }
+            }
+            if !resume {
+                return Ok(true);
+            }
+            events = editor.feed(&[]);
+        }
+    }
+
+    // 开始新的一行并显示提示符，等待密码时显示密码提示
+    async fn start_line(&mut self, editor: &mut LineEditor) -> ChannelResult<()> {
+        self.show_messages().await?;
+        let prompt = match &self.password_prompt {
+            Some((prompt, _)) => editor.start_secret(prompt.clone()),
+            None => editor.start(self.prompt()),
+        };
+        if self.pty {
+            self.send(prompt.as_bytes(), false).await?;
+        }
+        Ok(())
+    }
+
+    // 输出其他用户发来的消息；输入过程中到达的消息在下一次显示提示符前输出
+    async fn show_messages(&mut self) -> ChannelResult<()> {
+        let mut lines = Vec::new();
+        if let Some((_, events)) = &mut self.events {
+            while let Ok(received) = events.try_recv() {
+                if let ServerMessage::Event(event) = received {
+                    lines.extend(message::render(&event));
+                }
+            }
+        }
+        for line in lines {
+            self.write_text(&format!("{}\n", line), false).await?;
+        }
+        Ok(())
+    }
+
+    // 启动交互式 shell：显示欢迎信息和离线消息，返回行编辑器
+    async fn start_shell(&mut self) -> ChannelResult<LineEditor> {
+        let account = &self.account;
+        let history = match history::recent(&self.state.db, &account.id, Some(HISTORY_SIZE)).await {
+            Ok(entries) => entries.into_iter().map(|e| e.command).collect(),
+            Err(e) => {
+                warn!("读取命令历史失败: {}", e);
+                Vec::new()
+            }
+        };
+        let mut editor = LineEditor::new(history);
+        let welcome = format!(
+            "欢迎来到终端博客，{}！输入 help 查看可用命令，exit 退出。\n",
+            account.username
+        );
+        self.write_text(&welcome, false).await?;
+
+        // 登记到连接管理器以接收 write、wall 推送的消息，再显示离线期间的消息
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let id = self.state.connection_manager.register(&self.token, sender);
+        self.events = Some((id, receiver));
+        match message::take_pending(&self.state.db, &self.account.id).await {
+            Ok(pending) if !pending.is_empty() => {
+                let mut text = format!("你有 {} 条离线消息:\n", pending.len());
+                for line in pending.iter().filter_map(message::render) {
+                    text.push_str(&line);
+                    text.push('\n');
+                }
+                self.write_text(&text, false).await?;
+            }
+            Ok(_) => {}
+            Err(e) => warn!("读取离线消息失败: {}", e),
+        }
+        self.start_line(&mut editor).await?;
+        Ok(editor)
+    }
+}
+
+// 在 `sudo` 之后插入 `--password`，密码用单引号括起
+fn with_password(command: &str, password: &str) -> String {
+    let rest = command.strip_prefix("sudo").unwrap_or(command);
+    format!(
+        "sudo --password '{}'{}",
+        password.replace('\'', "'\\''"),
+        rest
+    )
+}
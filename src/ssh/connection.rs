@@ -0,0 +1,311 @@
+use log::{debug, error, info, warn};
+use russh::keys::PublicKey;
+use russh::server::{Auth, Handler, Msg, Session};
+use russh::{Channel, ChannelId};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use super::channel::ShellChannel;
+use super::types::{SshError, SshResult};
+use crate::auth::{generate_token, AuthManager};
+use crate::command::{login_history, ClientInfo};
+use crate::vfs::model::{Role, User as VfsUser};
+
+/// 通过认证的博客用户
+#[derive(Clone)]
+pub struct Account {
+    pub id: String,
+    pub username: String,
+    pub role: String,
+    pub token_version: i32,
+}
+
+/// 一个 SSH 连接：用博客账号认证，为会话通道运行 shell 或执行命令
+///
+/// 协议本身由 russh 处理，这里只负责认证和把会话通道交给 [`ShellChannel`]。
+pub struct Connection {
+    state: Arc<crate::AppState>,
+    peer: SocketAddr,
+    // 记入登录历史的客户端信息
+    client: ClientInfo,
+    // 认证通过的用户及认证方式，登录成功在 `auth_succeeded` 中记录
+    account: Option<(Account, &'static str)>,
+    // 本次连接签发及之后切换到的 token，连接结束时全部作废
+    tokens: Arc<Mutex<Vec<String>>>,
+    session_opened: bool,
+}
+
+impl Connection {
+    pub fn new(state: Arc<crate::AppState>, peer: SocketAddr) -> Self {
+        let client = ClientInfo {
+            via: "ssh",
+            ip: Some(peer.ip().to_string()),
+            user_agent: None,
+        };
+        Connection {
+            state,
+            peer,
+            client,
+            account: None,
+            tokens: Arc::new(Mutex::new(Vec::new())),
+            session_opened: false,
+        }
+    }
+
+    // 被锁定的账号按不存在处理
+    async fn find_account(&self, username: &str) -> Option<(Account, String)> {
+        match sqlx::query!(
+            "SELECT id, username, password_hash, role, token_version FROM users WHERE username = $1 AND NOT locked",
+            username
+        )
+        .fetch_optional(&self.state.db)
+        .await
+        {
+            Ok(user) => user.map(|user| {
+                (
+                    Account {
+                        id: user.id,
+                        username: user.username,
+                        role: user.role,
+                        token_version: user.token_version,
+                    },
+                    user.password_hash,
+                )
+            }),
+            Err(e) => {
+                error!("数据库查询错误: {}", e);
+                None
+            }
+        }
+    }
+
+    // 检查公钥是否在用户的 ~/.ssh/authorized_keys 中
+    async fn is_authorized_key(&self, account: &Account, key: &PublicKey) -> bool {
+        let user = VfsUser {
+            id: account.id.clone(),
+            username: account.username.clone(),
+            roles: vec![Role::from_account(&account.role, false)],
+            session: None,
+        };
+        let path = format!("/home/{}/.ssh/authorized_keys", account.username);
+        let content = match self.state.vfs_manager.read_file(&user, &path, "/").await {
+            Ok(content) => content,
+            Err(e) => {
+                debug!("读取 {} 失败: {}", path, e);
+                return false;
+            }
+        };
+        // 无法解析的行跳过，不影响其他公钥
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| match PublicKey::from_openssh(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    debug!("{} 中有无效的公钥: {}", path, e);
+                    None
+                }
+            })
+            .any(|entry| entry.key_data() == key.key_data())
+    }
+
+    // 公钥属于未锁定的账号时返回该账号
+    async fn key_owner(&self, username: &str, key: &PublicKey) -> Option<Account> {
+        let (account, _) = self.find_account(username).await?;
+        self.is_authorized_key(&account, key)
+            .await
+            .then_some(account)
+    }
+
+    // 记入登录历史
+    async fn record_login(
+        &self,
+        user_id: Option<&str>,
+        username: &str,
+        method: &str,
+        success: bool,
+    ) {
+        login_history::record(
+            &self.state.db,
+            user_id,
+            username,
+            method,
+            &self.client,
+            success,
+        )
+        .await;
+    }
+}
+
+impl Handler for Connection {
+    type Error = SshError;
+
+    async fn authentication_banner(&mut self) -> SshResult<Option<String>> {
+        Ok(Some(
+            "终端博客 SSH 服务，请使用博客账号登录\r\n".to_string(),
+        ))
+    }
+
+    async fn auth_password(&mut self, user: &str, password: &str) -> SshResult<Auth> {
+        if let Err(e) = self.state.auth_manager.check_login_attempts(user) {
+            warn!("SSH 登录尝试次数过多: {}", e);
+            self.record_login(None, user, "password", false).await;
+            return Ok(Auth::reject());
+        }
+        match self.find_account(user).await {
+            Some((account, hash))
+                if AuthManager::verify_password(password, &hash).unwrap_or(false) =>
+            {
+                self.account = Some((account, "password"));
+                Ok(Auth::Accept)
+            }
+            account => {
+                self.state.auth_manager.record_failed_attempt(user);
+                let user_id = account.map(|(account, _)| account.id);
+                self.record_login(user_id.as_deref(), user, "password", false)
+                    .await;
+                warn!("SSH 用户 {} 密码错误", user);
+                Ok(Auth::reject())
+            }
+        }
+    }
+
+    // 客户端询问公钥是否可用，签名由 russh 校验后再调用 `auth_publickey`
+    async fn auth_publickey_offered(&mut self, user: &str, key: &PublicKey) -> SshResult<Auth> {
+        Ok(match self.key_owner(user, key).await {
+            Some(_) => Auth::Accept,
+            None => Auth::reject(),
+        })
+    }
+
+    async fn auth_publickey(&mut self, user: &str, key: &PublicKey) -> SshResult<Auth> {
+        match self.key_owner(user, key).await {
+            Some(account) => {
+                self.account = Some((account, "publickey"));
+                Ok(Auth::Accept)
+            }
+            None => Ok(Auth::reject()),
+        }
+    }
+
+    async fn auth_succeeded(&mut self, session: &mut Session) -> SshResult<()> {
+        let Some((account, method)) = self.account.clone() else {
+            return Ok(());
+        };
+        self.client.user_agent = Some(String::from_utf8_lossy(session.remote_sshid()).into_owned());
+        self.state
+            .auth_manager
+            .reset_login_attempts(&account.username);
+        self.record_login(Some(&account.id), &account.username, method, true)
+            .await;
+        let token = generate_token(
+            &account.id,
+            &account.username,
+            &account.role,
+            account.token_version,
+        )?;
+        self.tokens.lock().unwrap().push(token);
+        info!("SSH 用户 {} 从 {} 登录成功", account.username, self.peer);
+        Ok(())
+    }
+
+    async fn channel_open_session(
+        &mut self,
+        channel: Channel<Msg>,
+        _session: &mut Session,
+    ) -> SshResult<bool> {
+        let (Some((account, _)), false) = (&self.account, self.session_opened) else {
+            debug!("拒绝打开会话通道：每个连接只支持一个会话");
+            return Ok(false);
+        };
+        let Some(token) = self.tokens.lock().unwrap().first().cloned() else {
+            return Ok(false);
+        };
+        self.session_opened = true;
+        let shell = ShellChannel::new(
+            self.state.clone(),
+            account.clone(),
+            self.client.clone(),
+            token,
+            self.tokens.clone(),
+            channel,
+        );
+        tokio::spawn(shell.run());
+        Ok(true)
+    }
+
+    async fn pty_request(
+        &mut self,
+        channel: ChannelId,
+        _term: &str,
+        _col_width: u32,
+        _row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _modes: &[(russh::Pty, u32)],
+        session: &mut Session,
+    ) -> SshResult<()> {
+        Ok(session.channel_success(channel)?)
+    }
+
+    async fn shell_request(&mut self, channel: ChannelId, session: &mut Session) -> SshResult<()> {
+        Ok(session.channel_success(channel)?)
+    }
+
+    async fn exec_request(
+        &mut self,
+        channel: ChannelId,
+        _data: &[u8],
+        session: &mut Session,
+    ) -> SshResult<()> {
+        Ok(session.channel_success(channel)?)
+    }
+
+    // 不支持环境变量、子系统（如 sftp）和 X11 转发
+    async fn env_request(
+        &mut self,
+        channel: ChannelId,
+        _variable_name: &str,
+        _variable_value: &str,
+        session: &mut Session,
+    ) -> SshResult<()> {
+        Ok(session.channel_failure(channel)?)
+    }
+
+    async fn subsystem_request(
+        &mut self,
+        channel: ChannelId,
+        name: &str,
+        session: &mut Session,
+    ) -> SshResult<()> {
+        debug!("拒绝子系统请求: {}", name);
+        Ok(session.channel_failure(channel)?)
+    }
+
+    async fn x11_request(
+        &mut self,
+        channel: ChannelId,
+        _single_connection: bool,
+        _x11_auth_protocol: &str,
+        _x11_auth_cookie: &str,
+        _x11_screen_number: u32,
+        session: &mut Session,
+    ) -> SshResult<()> {
+        Ok(session.channel_failure(channel)?)
+    }
+}
+
+// 连接结束即登出：作废 token 并清理会话状态
+impl Drop for Connection {
+    fn drop(&mut self) {
+        for token in self.tokens.lock().unwrap().iter() {
+            self.state.auth_manager.blacklist_token(token);
+            self.state.session_manager.remove(token);
+            self.state.job_manager.remove_session(token);
+        }
+        if let Some((account, _)) = &self.account {
+            info!("SSH 用户 {} 断开连接", account.username);
+        }
+    }
+}
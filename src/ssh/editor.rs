@@ -0,0 +1,325 @@
+use crate::command::args::display_width;
+
+// 编辑器历史记录的最大条数
+const MAX_HISTORY: usize = 500;
+
+/// 编辑器产生的事件，由 shell 负责执行
+#[derive(Debug, PartialEq)]
+pub enum EditorEvent {
+    /// 需要写回终端的内容（回显、重绘）
+    Output(String),
+    /// 用户按下回车，提交一行命令
+    Submit(String),
+    /// 按下 Tab，请求补全光标处的单词
+    Complete,
+    /// Ctrl-C 放弃当前输入
+    Cancel,
+    /// 空行上按 Ctrl-D
+    Exit,
+    /// Ctrl-L 清屏
+    ClearScreen,
+}
+
+// 解码后的按键
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Tab,
+    Interrupt,
+    Eof,
+    KillLine,
+    ClearScreen,
+}
+
+/// 行编辑器：把终端发来的字节流解码为按键，维护当前行、光标和历史
+///
+/// 每次修改后整行重绘，不依赖终端的插入、删除字符功能。
+#[derive(Default)]
+pub struct LineEditor {
+    prompt: String,
+    buffer: Vec<char>,
+    cursor: usize,
+    history: Vec<String>,
+    // 正在浏览的历史位置及浏览前的输入
+    browsing: Option<usize>,
+    draft: String,
+    // 未完整收到的转义序列或 UTF-8 字符
+    pending: Vec<u8>,
+    // 输入密码：不回显，不记入历史，不补全
+    secret: bool,
+}
+
+impl LineEditor {
+    pub fn new(history: Vec<String>) -> Self {
+        LineEditor {
+            history,
+            ..Default::default()
+        }
+    }
+
+    /// 开始新的一行，返回需要显示的提示符
+    pub fn start(&mut self, prompt: String) -> String {
+        self.prompt = prompt;
+        self.buffer.clear();
+        self.cursor = 0;
+        self.browsing = None;
+        self.secret = false;
+        self.prompt.clone()
+    }
+
+    /// 开始输入密码，输入内容不回显
+    pub fn start_secret(&mut self, prompt: String) -> String {
+        let output = self.start(prompt);
+        self.secret = true;
+        output
+    }
+
+    /// 当前输入和光标位置（按字符计）
+    pub fn line(&self) -> (String, usize) {
+        (self.buffer.iter().collect(), self.cursor)
+    }
+
+    /// 用补全结果替换第 `start` 到 `end` 个字符，返回重绘内容
+    pub fn replace(&mut self, start: usize, end: usize, text: &str) -> String {
+        let end = end.min(self.buffer.len());
+        let start = start.min(end);
+        self.buffer.splice(start..end, text.chars());
+        self.cursor = start + text.chars().count();
+        self.render()
+    }
+
+    /// 在下一行重新显示提示符和当前输入，用于显示补全候选项之后
+    pub fn redisplay(&self) -> String {
+        format!("\r\n{}", self.render())
+    }
+
+    /// 在当前行重绘提示符和输入
+    pub fn render(&self) -> String {
+        if self.secret {
+            return format!("\r{}\x1b[K", self.prompt);
+        }
+        let line: String = self.buffer.iter().collect();
+        let mut output = format!("\r{}{}\x1b[K", self.prompt, line);
+        let tail: String = self.buffer[self.cursor..].iter().collect();
+        let width = display_width(&tail);
+        if width > 0 {
+            output.push_str(&format!("\x1b[{}D", width));
+        }
+        output
+    }
+
+    fn push_history(&mut self, line: &str) {
+        if line.trim().is_empty() || self.history.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+    }
+
+    fn show_history(&mut self, index: Option<usize>) -> String {
+        let line = match index {
+            Some(i) => self.history[i].clone(),
+            None => std::mem::take(&mut self.draft),
+        };
+        if self.browsing.is_none() {
+            self.draft = self.buffer.iter().collect();
+        }
+        self.browsing = index;
+        self.buffer = line.chars().collect();
+        self.cursor = self.buffer.len();
+        self.render()
+    }
+
+    /// 处理终端发来的数据
+    ///
+    /// 遇到提交、补全、取消或退出时停止，之后的输入暂存：调用方处理完该事件（提交后
+    /// 先调用 `start`）再以空数据调用 `feed` 继续处理。
+    pub fn feed(&mut self, data: &[u8]) -> Vec<EditorEvent> {
+        self.pending.extend_from_slice(data);
+        let mut events = Vec::new();
+        while let Some(key) = self.next_key() {
+            let event = match key {
+                Key::Char(c) => {
+                    self.buffer.insert(self.cursor, c);
+                    self.cursor += 1;
+                    EditorEvent::Output(self.render())
+                }
+                Key::Enter => {
+                    let line: String = self.buffer.iter().collect();
+                    if !self.secret {
+                        self.push_history(&line);
+                    }
+                    events.push(EditorEvent::Output("\r\n".to_string()));
+                    EditorEvent::Submit(line)
+                }
+                Key::Backspace if self.cursor > 0 => {
+                    self.cursor -= 1;
+                    self.buffer.remove(self.cursor);
+                    EditorEvent::Output(self.render())
+                }
+                Key::Delete if self.cursor < self.buffer.len() => {
+                    self.buffer.remove(self.cursor);
+                    EditorEvent::Output(self.render())
+                }
+                Key::Left if self.cursor > 0 => {
+                    self.cursor -= 1;
+                    EditorEvent::Output(self.render())
+                }
+                Key::Right if self.cursor < self.buffer.len() => {
+                    self.cursor += 1;
+                    EditorEvent::Output(self.render())
+                }
+                Key::Home => {
+                    self.cursor = 0;
+                    EditorEvent::Output(self.render())
+                }
+                Key::End => {
+                    self.cursor = self.buffer.len();
+                    EditorEvent::Output(self.render())
+                }
+                Key::Up | Key::Down | Key::Tab if self.secret => {
+                    EditorEvent::Output("\x07".to_string())
+                }
+                Key::Up if !self.history.is_empty() && self.browsing != Some(0) => {
+                    let index = match self.browsing {
+                        Some(i) => i - 1,
+                        None => self.history.len() - 1,
+                    };
+                    EditorEvent::Output(self.show_history(Some(index)))
+                }
+                Key::Down if self.browsing.is_some() => {
+                    let index = self
+                        .browsing
+                        .map(|i| i + 1)
+                        .filter(|i| *i < self.history.len());
+                    EditorEvent::Output(self.show_history(index))
+                }
+                Key::Tab => EditorEvent::Complete,
+                Key::Interrupt => {
+                    events.push(EditorEvent::Output("^C\r\n".to_string()));
+                    EditorEvent::Cancel
+                }
+                Key::Eof if self.buffer.is_empty() => EditorEvent::Exit,
+                Key::Eof if self.cursor < self.buffer.len() => {
+                    self.buffer.remove(self.cursor);
+                    EditorEvent::Output(self.render())
+                }
+                Key::KillLine => {
+                    self.buffer.drain(..self.cursor);
+                    self.cursor = 0;
+                    EditorEvent::Output(self.render())
+                }
+                Key::ClearScreen => EditorEvent::ClearScreen,
+                // 无法执行的编辑（如行首退格）响铃提示
+                _ => EditorEvent::Output("\x07".to_string()),
+            };
+            events.push(event);
+            if matches!(
+                events.last(),
+                Some(
+                    EditorEvent::Submit(_)
+                        | EditorEvent::Cancel
+                        | EditorEvent::Exit
+                        | EditorEvent::Complete
+                )
+            ) {
+                break;
+            }
+        }
+        events
+    }
+
+    // 从缓冲的输入中解码下一个按键，不完整的转义序列和 UTF-8 字符留到下次
+    fn next_key(&mut self) -> Option<Key> {
+        loop {
+            let bytes = &self.pending;
+            let first = *bytes.first()?;
+            let (key, used) = match first {
+                b'\r' | b'\n' => {
+                    // 回车后紧跟的换行或空字符一并跳过
+                    let used = match bytes.get(1) {
+                        Some(b'\n' | 0) if first == b'\r' => 2,
+                        _ => 1,
+                    };
+                    (Some(Key::Enter), used)
+                }
+                0x7f | 0x08 => (Some(Key::Backspace), 1),
+                b'\t' => (Some(Key::Tab), 1),
+                0x01 => (Some(Key::Home), 1),
+                0x03 => (Some(Key::Interrupt), 1),
+                0x04 => (Some(Key::Eof), 1),
+                0x05 => (Some(Key::End), 1),
+                0x0c => (Some(Key::ClearScreen), 1),
+                0x15 => (Some(Key::KillLine), 1),
+                0x1b => escape_sequence(bytes)?,
+                b if b < 0x20 => (None, 1),
+                b => {
+                    let len = match b {
+                        0xc0..=0xdf => 2,
+                        0xe0..=0xef => 3,
+                        0xf0..=0xf7 => 4,
+                        _ => 1,
+                    };
+                    if bytes.len() < len {
+                        return None;
+                    }
+                    let key = std::str::from_utf8(&bytes[..len])
+                        .ok()
+                        .and_then(|s| s.chars().next())
+                        .map(Key::Char);
+                    (key, len)
+                }
+            };
+            self.pending.drain(..used);
+            if key.is_some() {
+                return key;
+            }
+        }
+    }
+}
+
+// 解析 ESC 开头的转义序列，返回按键和占用的字节数；序列不完整时返回 None
+fn escape_sequence(bytes: &[u8]) -> Option<(Option<Key>, usize)> {
+    match bytes.get(1) {
+        None => None,
+        // CSI 序列：ESC [ 参数 终止字节
+        Some(b'[') => {
+            let end = bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b))? + 2;
+            let key = match (&bytes[2..end], bytes[end]) {
+                (_, b'A') => Some(Key::Up),
+                (_, b'B') => Some(Key::Down),
+                (_, b'C') => Some(Key::Right),
+                (_, b'D') => Some(Key::Left),
+                (_, b'H') | (b"1" | b"7", b'~') => Some(Key::Home),
+                (_, b'F') | (b"4" | b"8", b'~') => Some(Key::End),
+                (b"3", b'~') => Some(Key::Delete),
+                _ => None,
+            };
+            Some((key, end + 1))
+        }
+        // SS3 序列：应用模式下的方向键
+        Some(b'O') => {
+            let key = match bytes.get(2)? {
+                b'A' => Some(Key::Up),
+                b'B' => Some(Key::Down),
+                b'C' => Some(Key::Right),
+                b'D' => Some(Key::Left),
+                b'H' => Some(Key::Home),
+                b'F' => Some(Key::End),
+                _ => None,
+            };
+            Some((key, 3))
+        }
+        Some(_) => Some((None, 1)),
+    }
+}
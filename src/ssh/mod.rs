@@ -0,0 +1,313 @@
+mod channel;
+mod connection;
+mod editor;
+mod types;
+
+use log::{error, info, warn};
+use russh::keys::{Algorithm, HashAlg, PrivateKey};
+use russh::server::Config;
+use russh::{Limits, MethodKind, MethodSet, SshId};
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+use connection::Connection;
+
+// 认证失败的最大次数，超过后断开连接
+const MAX_AUTH_ATTEMPTS: usize = 6;
+
+// 认证失败后的等待时间，客户端查询认证方式（none）时不等待
+const AUTH_REJECTION_TIME: Duration = Duration::from_secs(1);
+
+// 客户端无响应时发送保活消息的间隔
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(60);
+
+// 读取 OpenSSH 格式的 ed25519 主机密钥
+fn load_host_key(path: &Path) -> io::Result<PrivateKey> {
+    let invalid = |message: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "主机密钥 {} 无效: {}，可以用 ssh-keygen -t ed25519 -N '' -f {} 生成",
+                path.display(),
+                message,
+                path.display()
+            ),
+        )
+    };
+    let key = russh::keys::load_secret_key(path, None).map_err(|e| invalid(e.to_string()))?;
+    if key.algorithm() != Algorithm::Ed25519 {
+        return Err(invalid(format!(
+            "只支持 ed25519 密钥，实际为 {}",
+            key.algorithm()
+        )));
+    }
+    Ok(key)
+}
+
+// 服务端配置：公钥和密码认证，传输 `limits` 规定的数据量或时间后重新交换密钥
+fn config(host_key: PrivateKey, limits: Limits) -> Arc<Config> {
+    Arc::new(Config {
+        server_id: SshId::Standard(format!(
+            "SSH-2.0-TerminalBlog_{}",
+            env!("CARGO_PKG_VERSION")
+        )),
+        methods: MethodSet::from(&[MethodKind::PublicKey, MethodKind::Password][..]),
+        auth_rejection_time: AUTH_REJECTION_TIME,
+        auth_rejection_time_initial: Some(Duration::ZERO),
+        max_auth_attempts: MAX_AUTH_ATTEMPTS,
+        keys: vec![host_key],
+        limits,
+        inactivity_timeout: None,
+        keepalive_interval: Some(KEEPALIVE_INTERVAL),
+        ..Default::default()
+    })
+}
+
+/// 启动 SSH 服务，监听 `addr`，每个连接在独立的任务中处理
+///
+/// 主机密钥从环境变量 `SSH_HOST_KEY` 指定的 OpenSSH 格式私钥文件读取，必须设置。
+pub async fn serve(state: Arc<crate::AppState>, addr: &str) -> io::Result<()> {
+    let key_path = std::env::var("SSH_HOST_KEY").map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "启用 SSH 服务需要用 SSH_HOST_KEY 指定主机密钥文件",
+        )
+    })?;
+    let host_key = load_host_key(Path::new(&key_path))?;
+    info!(
+        "SSH 主机密钥指纹: {}",
+        host_key.public_key().fingerprint(HashAlg::Sha256)
+    );
+
+    let listener = TcpListener::bind(addr).await?;
+    println!("SSH 服务启动在 {}", addr);
+    accept(state, listener, config(host_key, Limits::default())).await
+}
+
+// 接受连接，每个连接由 russh 在后台任务中运行
+async fn accept(
+    state: Arc<crate::AppState>,
+    listener: TcpListener,
+    config: Arc<Config>,
+) -> io::Result<()> {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                error!("接受 SSH 连接失败: {}", e);
+                continue;
+            }
+        };
+        info!("收到 SSH 连接: {}", peer);
+        let connection = Connection::new(state.clone(), peer);
+        let config = config.clone();
+        tokio::spawn(async move {
+            let result = match russh::server::run_stream(config, stream, connection).await {
+                Ok(session) => session.await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!("SSH 连接 {} 异常结束: {}", peer, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::{Role, User};
+    use std::path::PathBuf;
+    use std::process::{Output, Stdio};
+    use tokio::io::AsyncWriteExt;
+    use tokio::process::Command;
+
+    // 服务端重新交换密钥的阈值，远小于一次测试传输的数据量
+    const SMALL_REKEY_LIMIT: usize = 4096;
+
+    // 在本机的随机端口上启动 SSH 服务，传输 `rekey_limit` 字节后重新交换密钥，返回端口号
+    async fn start(state: Arc<crate::AppState>, host_key: PrivateKey, rekey_limit: usize) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let limits = Limits::new(rekey_limit, rekey_limit, Duration::from_secs(3600));
+        tokio::spawn(accept(state, listener, config(host_key, limits)));
+        port
+    }
+
+    // 测试依赖 OpenSSH 客户端，没有安装时跳过
+    async fn has_openssh() -> bool {
+        for program in ["ssh", "ssh-keygen"] {
+            if Command::new(program).arg("-V").output().await.is_err() {
+                eprintln!("没有找到 {}，跳过 SSH 测试", program);
+                return false;
+            }
+        }
+        true
+    }
+
+    // 用 OpenSSH 客户端登录并在 shell 中依次输入 `input` 中的各段命令，只信任 known_hosts 中的主机密钥
+    async fn ssh(
+        dir: &Path,
+        port: u16,
+        username: &str,
+        options: &[&str],
+        input: &[String],
+    ) -> Output {
+        let mut child = Command::new("ssh")
+            .args(["-F", "/dev/null", "-v", "-T"])
+            .arg("-i")
+            .arg(dir.join("id_ed25519"))
+            .args(["-o", "IdentitiesOnly=yes", "-o", "BatchMode=yes"])
+            .args(["-o", "StrictHostKeyChecking=yes"])
+            .arg("-o")
+            .arg(format!(
+                "UserKnownHostsFile={}",
+                dir.join("known_hosts").display()
+            ))
+            .args(["-p", &port.to_string()])
+            .args(options)
+            .arg(format!("{}@127.0.0.1", username))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("无法运行 ssh");
+        // 分段输入，让双方在会话中途多次重新交换密钥
+        let mut stdin = child.stdin.take().unwrap();
+        for chunk in input {
+            stdin.write_all(chunk.as_bytes()).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        drop(stdin);
+        tokio::time::timeout(Duration::from_secs(60), child.wait_with_output())
+            .await
+            .expect("ssh 超时")
+            .unwrap()
+    }
+
+    // 客户端完成的密钥交换次数，包括第一次
+    fn key_exchanges(output: &Output) -> usize {
+        String::from_utf8_lossy(&output.stderr)
+            .matches("SSH2_MSG_NEWKEYS received")
+            .count()
+    }
+
+    #[test]
+    fn missing_host_key_is_an_error() {
+        let path = std::env::temp_dir().join(format!("blog-no-host-key-{}", std::process::id()));
+        let message = load_host_key(&path).unwrap_err().to_string();
+        assert!(message.contains("ssh-keygen"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn openssh_client_runs_commands_across_rekeying() {
+        if !has_openssh().await {
+            return;
+        }
+        let Some(state) = crate::testing::state().await else {
+            return;
+        };
+        let db = state.db.clone();
+
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("blog-ssh-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // 主机密钥和客户端密钥都由 ssh-keygen 生成
+        for name in ["ssh_host_ed25519_key", "id_ed25519"] {
+            let generated = Command::new("ssh-keygen")
+                .args(["-q", "-t", "ed25519", "-N", ""])
+                .arg("-f")
+                .arg(dir.join(name))
+                .status()
+                .await
+                .expect("无法运行 ssh-keygen");
+            assert!(generated.success());
+        }
+        let host_key = load_host_key(&dir.join("ssh_host_ed25519_key")).unwrap();
+        let host_public = host_key.public_key().to_openssh().unwrap();
+        let client_public = std::fs::read_to_string(dir.join("id_ed25519.pub")).unwrap();
+
+        // 临时账号，通过 ~/.ssh/authorized_keys 中的公钥登录
+        let username = format!("sshtest{}", std::process::id());
+        let user_id = uuid::Uuid::new_v4().to_string();
+        sqlx::query!(
+            "INSERT INTO users (id, username, password_hash, role, created_at) VALUES ($1, $2, '!', 'user', NOW())",
+            user_id,
+            username
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+        let home = format!("/home/{}", username);
+        let system = User {
+            id: user_id.clone(),
+            username: "system".to_string(),
+            roles: vec![Role::Admin],
+            session: None,
+        };
+        let user = User {
+            id: user_id.clone(),
+            username: username.clone(),
+            roles: vec![Role::Author],
+            session: None,
+        };
+        let vfs = &state.vfs_manager;
+        vfs.create_dir(&system, &home, "/").await.unwrap();
+        vfs.update_node_owner(&home, &user_id).await.unwrap();
+        vfs.create_dir(&user, ".ssh", &home).await.unwrap();
+        vfs.write_file(&user, ".ssh/authorized_keys", &home, &client_public)
+            .await
+            .unwrap();
+
+        let port = start(state.clone(), host_key.clone(), 1 << 30).await;
+        let small_port = start(state.clone(), host_key.clone(), SMALL_REKEY_LIMIT).await;
+        let known_hosts: String = [port, small_port]
+            .iter()
+            .map(|port| format!("[127.0.0.1]:{} {}\n", port, host_public))
+            .collect();
+        std::fs::write(dir.join("known_hosts"), known_hosts).unwrap();
+
+        // 收发各约 16 KB，客户端或服务端每传输几 KB 就重新交换一次密钥
+        let mut input: Vec<String> = (0..10)
+            .map(|chunk| {
+                (0..20)
+                    .map(|i| format!("echo line-{:03} {}\n", chunk * 20 + i, "x".repeat(64)))
+                    .collect()
+            })
+            .collect();
+        input.push("exit\n".to_string());
+        let client_rekey = ssh(&dir, port, &username, &["-o", "RekeyLimit=2K"], &input).await;
+        let server_rekey = ssh(&dir, small_port, &username, &[], &input).await;
+
+        vfs.delete(&system, &home, "/").await.unwrap();
+        for query in [
+            "DELETE FROM command_history WHERE user_id = $1",
+            "DELETE FROM login_history WHERE user_id = $1",
+            "DELETE FROM users WHERE id = $1",
+        ] {
+            sqlx::query(query)
+                .bind(&user_id)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for output in [&client_rekey, &server_rekey] {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                output.status.success(),
+                "ssh 退出状态 {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            assert!(stdout.contains("line-000"), "{}", stdout);
+            assert!(stdout.contains("line-199"), "{}", stdout);
+            assert!(key_exchanges(output) >= 3, "{}", key_exchanges(output));
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+/// SSH 服务错误
+#[derive(Debug, Error)]
+pub enum SshError {
+    #[error("SSH 协议错误: {0}")]
+    Protocol(#[from] russh::Error),
+
+    #[error("生成 token 失败: {0}")]
+    Token(#[from] jsonwebtoken::errors::Error),
+}
+
+pub type SshResult<T> = Result<T, SshError>;
@@ -0,0 +1,42 @@
+use crate::auth::AuthManager;
+use crate::captcha::CaptchaManager;
+use crate::command::CommandRegistry;
+use crate::job::JobManager;
+use crate::session::SessionManager;
+use crate::vfs::{PostgresBackend, VfsManager};
+use crate::ws::ConnectionManager;
+use crate::AppState;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+
+/// 连接 `DATABASE_URL` 指定的数据库并创建应用状态，供需要数据库的测试使用
+///
+/// 没有设置 `DATABASE_URL` 时返回 None，调用的测试直接跳过。没有设置 `JWT_SECRET` 时
+/// 使用测试专用的值，以便签发 token。
+pub async fn state() -> Option<Arc<AppState>> {
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("没有设置 DATABASE_URL，跳过需要数据库的测试");
+        return None;
+    };
+    if std::env::var("JWT_SECRET").is_err() {
+        std::env::set_var("JWT_SECRET", "test-secret");
+    }
+    let db = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("连接数据库失败");
+    crate::db::initialize_db(db.clone())
+        .await
+        .expect("初始化数据库失败");
+    Some(Arc::new(AppState {
+        db: db.clone(),
+        auth_manager: AuthManager::new(),
+        captcha_manager: CaptchaManager::new(),
+        command_registry: CommandRegistry::new(),
+        session_manager: SessionManager::new(),
+        job_manager: JobManager::new(),
+        connection_manager: ConnectionManager::new(),
+        vfs_manager: VfsManager::new(PostgresBackend::new(db)),
+    }))
+}
@@ -0,0 +1,500 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::LazyLock;
+
+/// 写入时校验 front matter 的目录，相对家目录
+pub const DOCUMENTS_DIR: &str = "Documents";
+
+/// slug 的最大长度
+pub const MAX_SLUG_LEN: usize = 80;
+
+// 各字段的长度限制（字符数）
+const MAX_TITLE_LEN: usize = 200;
+const MAX_SUMMARY_LEN: usize = 1000;
+const MAX_TAG_LEN: usize = 50;
+const MAX_TAGS: usize = 20;
+
+// 语言标签，如 zh、en-US、zh-Hans-CN
+static LANGUAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z]{2,3}(-[A-Za-z0-9]{2,8})*$").unwrap());
+
+/// front matter 的格式，由开头的分隔行决定：`---` 为 YAML，`+++` 为 TOML
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    fn fence(self) -> &'static str {
+        match self {
+            Format::Yaml => "---",
+            Format::Toml => "+++",
+        }
+    }
+}
+
+/// 文章开头的元数据
+#[derive(Debug, Serialize)]
+pub struct FrontMatter {
+    pub format: Format,
+    pub title: Option<String>,
+    pub date: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    pub summary: Option<String>,
+    pub cover: Option<String>,
+    /// 为真时不允许发布
+    pub draft: bool,
+    pub slug: Option<String>,
+    pub language: Option<String>,
+}
+
+/// front matter 的错误，行号和列号从 1 开始，相对整个文件
+#[derive(Debug)]
+pub struct FrontMatterError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FrontMatterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "第 {} 行第 {} 列: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+// 反序列化得到的原始字段，未知字段视为错误以便发现拼写错误
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Fields {
+    title: Option<String>,
+    date: Option<DateField>,
+    #[serde(default)]
+    tags: Vec<String>,
+    summary: Option<String>,
+    cover: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    slug: Option<String>,
+    #[serde(alias = "lang")]
+    language: Option<String>,
+}
+
+// YAML 中的日期是字符串，TOML 有原生的日期时间类型
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DateField {
+    Text(String),
+    Toml(toml::value::Datetime),
+}
+
+/// 路径是否位于某个用户家目录的 `Documents` 下
+pub fn applies_to(path: &str) -> bool {
+    path.strip_prefix("/home/")
+        .and_then(|rest| rest.split_once('/'))
+        .and_then(|(_, rest)| rest.strip_prefix(DOCUMENTS_DIR))
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// 解析并校验文件开头的 front matter，没有 front matter 时返回 None
+pub fn parse(content: &str) -> Result<Option<FrontMatter>, FrontMatterError> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut lines = content.split_inclusive('\n');
+    let first = lines.next().unwrap_or_default();
+    let format = match first.trim_end() {
+        "---" => Format::Yaml,
+        "+++" => Format::Toml,
+        _ => return Ok(None),
+    };
+
+    // 查找结束的分隔行，YAML 也可以用 `...` 结束
+    let start = first.len();
+    let mut end = None;
+    let mut offset = start;
+    for line in lines {
+        let line_text = line.trim_end();
+        if line_text == format.fence() || (format == Format::Yaml && line_text == "...") {
+            end = Some(offset);
+            break;
+        }
+        offset += line.len();
+    }
+    let text = match end {
+        Some(end) => &content[start..end],
+        None => {
+            return Err(FrontMatterError {
+                line: 1,
+                column: 1,
+                message: format!("front matter 缺少结束的 {}", format.fence()),
+            })
+        }
+    };
+
+    let fields: Fields = if text.trim().is_empty() {
+        Fields::default()
+    } else {
+        match format {
+            Format::Yaml => serde_yaml::from_str(text).map_err(|e| {
+                let index = e.location().map_or(0, |location| location.index());
+                error_at(text, index, yaml_message(&e))
+            })?,
+            Format::Toml => toml::from_str(text).map_err(|e| {
+                let index = e.span().map_or(0, |span| span.start);
+                error_at(text, index, e.message().trim_end().replace('\n', ", "))
+            })?,
+        }
+    };
+    validate(text, format, fields).map(Some)
+}
+
+/// slug 验证：小写字母、数字和单个 `-`，不以 `-` 开头或结尾
+pub fn validate_slug(slug: &str) -> Result<(), String> {
+    if slug.is_empty() || slug.len() > MAX_SLUG_LEN {
+        return Err(format!("slug 长度必须在 1 到 {} 之间", MAX_SLUG_LEN));
+    }
+    let valid = slug.split('-').all(|part| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    });
+    if !valid {
+        return Err(format!(
+            "无效的 slug: {}，只能包含小写字母、数字和 -，且不能以 - 开头或结尾",
+            slug
+        ));
+    }
+    Ok(())
+}
+
+// 校验各字段的取值，错误定位到字段所在的行
+fn validate(text: &str, format: Format, fields: Fields) -> Result<FrontMatter, FrontMatterError> {
+    let fail = |key: &str, message: String| Err(error_at(text, key_index(text, key), message));
+
+    if let Some(title) = &fields.title {
+        if title.trim().is_empty() {
+            return fail("title", "title 不能为空".to_string());
+        }
+        if title.chars().count() > MAX_TITLE_LEN {
+            return fail("title", format!("title 不能超过 {} 个字符", MAX_TITLE_LEN));
+        }
+    }
+
+    let date = match fields.date {
+        Some(field) => {
+            let value = match field {
+                DateField::Text(value) => value,
+                DateField::Toml(value) => value.to_string(),
+            };
+            match parse_date(value.trim()) {
+                Some(date) => Some(date),
+                None => {
+                    return fail(
+                        "date",
+                        format!(
+                            "无效的日期: {}，应为 YYYY-MM-DD、YYYY-MM-DD HH:MM:SS 或 RFC 3339 格式",
+                            value
+                        ),
+                    )
+                }
+            }
+        }
+        None => None,
+    };
+
+    if fields.tags.len() > MAX_TAGS {
+        return fail("tags", format!("tags 不能超过 {} 个", MAX_TAGS));
+    }
+    let mut seen = HashSet::new();
+    for tag in &fields.tags {
+        if tag.trim().is_empty() {
+            return fail("tags", "tags 中不能有空标签".to_string());
+        }
+        if tag.chars().count() > MAX_TAG_LEN {
+            return fail("tags", format!("标签 {} 超过 {} 个字符", tag, MAX_TAG_LEN));
+        }
+        if !seen.insert(tag.as_str()) {
+            return fail("tags", format!("标签 {} 重复", tag));
+        }
+    }
+
+    if let Some(summary) = &fields.summary {
+        if summary.chars().count() > MAX_SUMMARY_LEN {
+            return fail(
+                "summary",
+                format!("summary 不能超过 {} 个字符", MAX_SUMMARY_LEN),
+            );
+        }
+    }
+    if fields
+        .cover
+        .as_ref()
+        .is_some_and(|cover| cover.trim().is_empty())
+    {
+        return fail("cover", "cover 不能为空".to_string());
+    }
+    if let Some(slug) = &fields.slug {
+        if let Err(e) = validate_slug(slug) {
+            return fail("slug", e);
+        }
+    }
+    if let Some(language) = &fields.language {
+        if !LANGUAGE.is_match(language) {
+            return fail(
+                "language",
+                format!("无效的语言: {}，应为 zh、en-US 这样的语言标签", language),
+            );
+        }
+    }
+
+    Ok(FrontMatter {
+        format,
+        title: fields.title,
+        date,
+        tags: fields.tags,
+        summary: fields.summary,
+        cover: fields.cover,
+        draft: fields.draft,
+        slug: fields.slug,
+        language: fields.language,
+    })
+}
+
+// 日期：RFC 3339，或不带时区的日期、日期时间（按服务器时区）
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+// serde_yaml 的错误信息末尾带有位置，位置已单独给出
+fn yaml_message(error: &serde_yaml::Error) -> String {
+    static LOCATION: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r" at (line \d+ column \d+|position \d+)").unwrap());
+    LOCATION.replace(&error.to_string(), "").to_string()
+}
+
+// 字段名在 front matter 中的位置，找不到时为开头
+fn key_index(text: &str, key: &str) -> usize {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let aliases: &[&str] = if key == "language" {
+            &[key, "lang"]
+        } else {
+            &[key]
+        };
+        for alias in aliases {
+            if let Some(rest) = trimmed.strip_prefix(alias) {
+                if rest.trim_start().starts_with([':', '=']) {
+                    return offset + line.len() - trimmed.len();
+                }
+            }
+        }
+        offset += line.len();
+    }
+    0
+}
+
+// 把 front matter 中的字节位置换算为文件中的行号和列号，front matter 从第 2 行开始
+fn error_at(text: &str, index: usize, message: String) -> FrontMatterError {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    let before = &text[..index];
+    let line = before.matches('\n').count() + 2;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    FrontMatterError {
+        line,
+        column,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(content: &str) -> (usize, usize, String) {
+        let e = parse(content).unwrap_err();
+        (e.line, e.column, e.message)
+    }
+
+    fn local(date: &str) -> DateTime<Utc> {
+        let naive = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S").unwrap();
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_yaml_front_matter() {
+        let content = "\u{feff}---\ntitle: 你好\ndate: 2024-05-01\ntags: [rust, blog]\nlang: zh-CN\ndraft: true\n...\n正文\n";
+        let meta = parse(content).unwrap().unwrap();
+        assert_eq!(meta.format, Format::Yaml);
+        assert_eq!(meta.title.as_deref(), Some("你好"));
+        assert_eq!(meta.date, Some(local("2024-05-01 00:00:00")));
+        assert_eq!(meta.tags, ["rust", "blog"]);
+        assert_eq!(meta.language.as_deref(), Some("zh-CN"));
+        assert!(meta.draft);
+        assert_eq!(meta.slug, None);
+    }
+
+    #[test]
+    fn parses_toml_front_matter() {
+        let content =
+            "+++\ntitle = \"Hello\"\ndate = 2024-05-01T08:00:00Z\nslug = \"hello-world\"\n+++\n";
+        let meta = parse(content).unwrap().unwrap();
+        assert_eq!(meta.format, Format::Toml);
+        assert_eq!(
+            meta.date,
+            Some(
+                DateTime::parse_from_rfc3339("2024-05-01T08:00:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc)
+            )
+        );
+        assert_eq!(meta.slug.as_deref(), Some("hello-world"));
+        assert!(!meta.draft);
+
+        let empty = parse("---\n---\n").unwrap().unwrap();
+        assert_eq!(empty.title, None);
+        assert!(empty.tags.is_empty());
+    }
+
+    #[test]
+    fn content_without_front_matter_is_none() {
+        assert!(parse("").unwrap().is_none());
+        assert!(parse("# 标题\n---\n").unwrap().is_none());
+        assert!(parse("----\ntitle: x\n----\n").unwrap().is_none());
+    }
+
+    #[test]
+    fn errors_point_at_lines_in_the_file() {
+        assert_eq!(
+            error("---\ntitle: x\n"),
+            (1, 1, "front matter 缺少结束的 ---".to_string())
+        );
+        assert_eq!(
+            error("+++\ntitle = \"x\"\n---\n"),
+            (1, 1, "front matter 缺少结束的 +++".to_string())
+        );
+
+        // 语法错误和未知字段的位置来自解析器，换算为文件中的行号
+        let (line, _, message) = error("---\ntitle: x\nauthor: bob\n---\n");
+        assert_eq!(line, 3);
+        assert!(message.contains("unknown field `author`"), "{}", message);
+        assert!(!message.contains(" at line "), "{}", message);
+        let (line, _, _) = error("+++\ntitle = \"x\"\ndraft = maybe\n+++\n");
+        assert_eq!(line, 3);
+
+        // 取值错误定位到字段所在的行
+        assert_eq!(
+            error("---\ntitle: x\n  \nslug: Hello\n---\n"),
+            (
+                4,
+                1,
+                "无效的 slug: Hello，只能包含小写字母、数字和 -，且不能以 - 开头或结尾".to_string()
+            )
+        );
+        assert_eq!(
+            error("+++\ntags = [\"a\", \"a\"]\n+++\n"),
+            (2, 1, "标签 a 重复".to_string())
+        );
+        assert_eq!(
+            error("---\ntitle: x\ndate: soon\n---\n"),
+            (
+                3,
+                1,
+                "无效的日期: soon，应为 YYYY-MM-DD、YYYY-MM-DD HH:MM:SS 或 RFC 3339 格式"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            error("---\nlang: chinese!\n---\n").2,
+            "无效的语言: chinese!，应为 zh、en-US 这样的语言标签"
+        );
+        assert_eq!(error("---\ntitle: \"  \"\n---\n").2, "title 不能为空");
+    }
+
+    #[test]
+    fn error_display_includes_position() {
+        let e = parse("---\ntitle: x\n").unwrap_err();
+        assert_eq!(e.to_string(), "第 1 行第 1 列: front matter 缺少结束的 ---");
+    }
+
+    #[test]
+    fn validate_slug_accepts_lowercase_words() {
+        assert_eq!(validate_slug("hello-world-2"), Ok(()));
+        for slug in ["", "-a", "a-", "a--b", "Hello", "a_b", "你好"] {
+            assert!(validate_slug(slug).is_err(), "{}", slug);
+        }
+        assert!(validate_slug(&"a".repeat(MAX_SLUG_LEN)).is_ok());
+        assert_eq!(
+            validate_slug(&"a".repeat(MAX_SLUG_LEN + 1)),
+            Err(format!("slug 长度必须在 1 到 {} 之间", MAX_SLUG_LEN))
+        );
+    }
+
+    #[test]
+    fn parse_date_accepts_common_formats() {
+        assert_eq!(
+            parse_date("2024-05-01T08:00:00+08:00"),
+            Some(
+                DateTime::parse_from_rfc3339("2024-05-01T00:00:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc)
+            )
+        );
+        assert_eq!(parse_date("2024-05-01"), Some(local("2024-05-01 00:00:00")));
+        assert_eq!(
+            parse_date("2024-05-01 12:30:00"),
+            Some(local("2024-05-01 12:30:00"))
+        );
+        assert_eq!(
+            parse_date("2024-05-01T12:30:00"),
+            Some(local("2024-05-01 12:30:00"))
+        );
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("May 1, 2024"), None);
+    }
+
+    #[test]
+    fn applies_to_documents_in_home_directories() {
+        assert!(applies_to("/home/alice/Documents/a.md"));
+        assert!(applies_to("/home/alice/Documents/drafts/a.md"));
+        assert!(!applies_to("/home/alice/Documents"));
+        assert!(!applies_to("/home/alice/Documentsx/a.md"));
+        assert!(!applies_to("/home/alice/notes/a.md"));
+        assert!(!applies_to("/tmp/Documents/a.md"));
+    }
+}
@@ -1,18 +1,38 @@
 use crate::vfs::{
-    model::{User, VfsError, VfsNode, VfsOp},
+    front_matter,
+    model::{Role, User, VfsError, VfsNode, VfsOp},
+    mount::{MountProvider, MountTable},
     path_normalizer::PathNormalizer,
     permission::PermissionManager,
     storage::StorageBackend,
 };
 use log::{debug, info, warn};
+use std::sync::Arc;
 
 pub struct VfsManager<B: StorageBackend> {
     backend: B,
+    mounts: MountTable,
 }
 
 impl<B: StorageBackend> VfsManager<B> {
     pub fn new(backend: B) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            mounts: MountTable::new(),
+        }
+    }
+
+    /// 把只读的虚拟文件系统挂载到 `point`，其下的路径不再访问存储后端
+    pub fn mount(&self, point: &str, provider: Arc<dyn MountProvider>) {
+        self.mounts.mount(point, provider);
+    }
+
+    // 获取节点信息，挂载点下的路径由对应的提供者生成
+    async fn get_node(&self, path: &str) -> Result<VfsNode, VfsError> {
+        match self.mounts.resolve(path) {
+            Some(mounted) => mounted.get_node().await,
+            None => self.backend.get_node(path).await,
+        }
     }
 
     /// 列出目录内容
@@ -29,13 +49,16 @@ impl<B: StorageBackend> VfsManager<B> {
         debug!("规范化后的路径: {}", path);
 
         // 获取目录节点
-        let node = self.backend.get_node(&path).await?;
+        let node = self.get_node(&path).await?;
 
         // 检查权限
         PermissionManager::check(user, &node, &VfsOp::ReadDir)?;
 
         // 获取目录内容
-        let contents = self.backend.list(&path).await?;
+        let contents = match self.mounts.resolve(&path) {
+            Some(mounted) => mounted.list().await?,
+            None => self.backend.list(&path).await?,
+        };
         info!("成功获取目录 {} 的内容", path);
 
         Ok(contents)
@@ -58,6 +81,7 @@ impl<B: StorageBackend> VfsManager<B> {
         let parent_path = PathNormalizer::parent(&path)
             .ok_or_else(|| VfsError::PathError("无法获取父目录".to_string()))?;
 
+        self.mounts.check_writable(&path)?;
         let parent = self.backend.get_node(&parent_path).await?;
 
         // 检查权限
@@ -80,6 +104,105 @@ impl<B: StorageBackend> VfsManager<B> {
         Ok(node)
     }
 
+    /// 读取文件内容
+    pub async fn read_file(
+        &self,
+        user: &User,
+        raw_path: &str,
+        cwd: &str,
+    ) -> Result<String, VfsError> {
+        info!("读取文件: {}, 用户名: {}", raw_path, user.username);
+
+        // 规范化路径
+        let path = PathNormalizer::normalize(raw_path, cwd)?;
+        debug!("规范化后的路径: {}", path);
+
+        // 获取节点并检查权限
+        let node = self.get_node(&path).await?;
+        PermissionManager::check(user, &node, &VfsOp::ReadFile)?;
+
+        match self.mounts.resolve(&path) {
+            Some(mounted) => mounted.read_file(user).await,
+            None => self.backend.read_file(&path).await,
+        }
+    }
+
+    /// 读取可执行文件内容，需要同时具有读和执行权限
+    pub async fn read_executable(
+        &self,
+        user: &User,
+        raw_path: &str,
+        cwd: &str,
+    ) -> Result<String, VfsError> {
+        info!("执行文件: {}, 用户名: {}", raw_path, user.username);
+
+        // 规范化路径
+        let path = PathNormalizer::normalize(raw_path, cwd)?;
+        debug!("规范化后的路径: {}", path);
+
+        // 获取节点并检查权限
+        let node = self.get_node(&path).await?;
+        if node.is_dir {
+            return Err(VfsError::InvalidOperation(format!("是一个目录: {}", path)));
+        }
+        PermissionManager::check(user, &node, &VfsOp::ReadFile)?;
+        PermissionManager::check(user, &node, &VfsOp::Execute)?;
+
+        match self.mounts.resolve(&path) {
+            Some(mounted) => mounted.read_file(user).await,
+            None => self.backend.read_file(&path).await,
+        }
+    }
+
+    /// 写入文件内容，文件不存在时创建
+    pub async fn write_file(
+        &self,
+        user: &User,
+        raw_path: &str,
+        cwd: &str,
+        content: &str,
+    ) -> Result<VfsNode, VfsError> {
+        info!("写入文件: {}, 用户名: {}", raw_path, user.username);
+
+        // 规范化路径
+        let path = PathNormalizer::normalize(raw_path, cwd)?;
+        debug!("规范化后的路径: {}", path);
+
+        self.mounts.check_writable(&path)?;
+        match self.backend.get_node(&path).await {
+            // 已存在的文件按节点权限检查
+            Ok(node) => PermissionManager::check(user, &node, &VfsOp::WriteFile)?,
+            // 新文件检查父目录的写权限
+            Err(VfsError::NodeNotFound(_)) => {
+                let parent_path = PathNormalizer::parent(&path)
+                    .ok_or_else(|| VfsError::PathError("无法获取父目录".to_string()))?;
+                if !PermissionManager::can_write(user, &parent_path) {
+                    warn!(
+                        "权限检查失败 - 用户: {}, 路径: {}",
+                        user.username, parent_path
+                    );
+                    return Err(VfsError::PermissionError(
+                        "作者用户只能在自己的目录下执行写操作".to_string(),
+                    ));
+                }
+            }
+            Err(e) => return Err(e),
+        }
+
+        // 文章的 front matter 必须有效
+        if front_matter::applies_to(&path) {
+            front_matter::parse(content).map_err(|e| {
+                warn!("front matter 无效 - 路径: {}, {}", path, e);
+                VfsError::InvalidMetadata(format!("{}: {}", path, e))
+            })?;
+        }
+
+        let node = self.backend.write_file(&path, content, user.id.clone()).await?;
+        info!("成功写入文件: {}", path);
+
+        Ok(node)
+    }
+
     /// 删除节点
     pub async fn delete(&self, user: &User, raw_path: &str, cwd: &str) -> Result<(), VfsError> {
         info!("删除节点: {}, 用户名: {}", raw_path, user.username);
@@ -88,6 +211,9 @@ impl<B: StorageBackend> VfsManager<B> {
         let path = PathNormalizer::normalize(raw_path, cwd)?;
         debug!("规范化后的路径: {}", path);
 
+        // 虚拟文件系统只读
+        self.mounts.check_writable(&path)?;
+
         // 获取节点
         let node = self.backend.get_node(&path).await?;
 
@@ -119,6 +245,10 @@ impl<B: StorageBackend> VfsManager<B> {
         let new_path = PathNormalizer::normalize(new_path, cwd)?;
         debug!("规范化后的路径: {} -> {}", old_path, new_path);
 
+        // 虚拟文件系统只读
+        self.mounts.check_writable(&old_path)?;
+        self.mounts.check_writable(&new_path)?;
+
         // 获取节点
         let node = self.backend.get_node(&old_path).await?;
 
@@ -132,6 +262,68 @@ impl<B: StorageBackend> VfsManager<B> {
         Ok(())
     }
 
+    /// 修改节点权限，只有所有者和管理员可以修改
+    pub async fn chmod(
+        &self,
+        user: &User,
+        raw_path: &str,
+        cwd: &str,
+        permissions: i16,
+    ) -> Result<VfsNode, VfsError> {
+        info!(
+            "修改权限: {} -> {:o}, 用户名: {}",
+            raw_path, permissions, user.username
+        );
+
+        // 规范化路径
+        let path = PathNormalizer::normalize(raw_path, cwd)?;
+        debug!("规范化后的路径: {}", path);
+
+        // 虚拟文件系统只读
+        self.mounts.check_writable(&path)?;
+
+        // 获取节点并检查所有权
+        let mut node = self.backend.get_node(&path).await?;
+        if node.owner_id != user.id && !user.roles.contains(&Role::Admin) {
+            warn!("非所有者修改权限 - 用户: {}, 路径: {}", user.username, path);
+            return Err(VfsError::PermissionError(format!(
+                "只有所有者可以修改 {} 的权限",
+                path
+            )));
+        }
+
+        node.permissions = permissions & 0o777;
+        self.backend.update_node(&node).await?;
+        info!("成功修改权限: {} -> {:o}", path, node.permissions);
+
+        Ok(node)
+    }
+
+    /// 获取节点信息
+    pub async fn stat(&self, raw_path: &str, cwd: &str) -> Result<VfsNode, VfsError> {
+        let path = PathNormalizer::normalize(raw_path, cwd)?;
+        debug!("获取节点信息: {}", path);
+        self.get_node(&path).await
+    }
+
+    /// 以用户身份获取节点信息，需要具有列出父目录的权限
+    pub async fn stat_as(
+        &self,
+        user: &User,
+        raw_path: &str,
+        cwd: &str,
+    ) -> Result<VfsNode, VfsError> {
+        let path = PathNormalizer::normalize(raw_path, cwd)?;
+        debug!("获取节点信息: {}, 用户名: {}", path, user.username);
+
+        // 根目录没有父目录
+        if let Some(parent_path) = PathNormalizer::parent(&path) {
+            let parent = self.get_node(&parent_path).await?;
+            PermissionManager::check(user, &parent, &VfsOp::ReadDir)?;
+        }
+        self.get_node(&path).await
+    }
+
     /// 获取当前工作目录
     pub fn pwd(&self, cwd: &str) -> String {
         cwd.to_string()
@@ -144,6 +336,9 @@ impl<B: StorageBackend> VfsManager<B> {
             path, new_owner_id
         );
 
+        // 虚拟文件系统只读
+        self.mounts.check_writable(path)?;
+
         // 获取节点
         let mut node = self.backend.get_node(path).await?;
 
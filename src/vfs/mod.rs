@@ -1,11 +1,14 @@
+pub mod front_matter;
 pub mod manager;
 pub mod model;
+pub mod mount;
 pub mod path_normalizer;
 pub mod permission;
 pub mod storage;
 
 pub use manager::VfsManager;
 pub use model::{Role, User, VfsError, VfsNode, VfsOp};
+pub use mount::MountProvider;
 pub use path_normalizer::PathNormalizer;
 pub use permission::PermissionManager;
 pub use storage::{backend::PostgresBackend, StorageBackend};
@@ -14,6 +14,8 @@ pub struct VfsNode {
     pub is_dir: bool,
     pub owner_id: String,
     pub permissions: i16, // Unix 模式 bits
+    /// 文件内容的字节数，目录为 0
+    pub size: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -23,6 +25,9 @@ pub struct User {
     pub id: String,
     pub username: String,
     pub roles: Vec<Role>,
+    /// 发起操作的登录 token，`/proc/self` 据此找到调用者自己的会话
+    #[serde(skip)]
+    pub session: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -32,6 +37,19 @@ pub enum Role {
     Guest,
 }
 
+impl Role {
+    /// 账户角色（`users.role`）对应的 VFS 角色
+    ///
+    /// 管理员平时以作者身份操作，只有通过 `sudo` 提权（`elevated`）后才拥有 Admin 权限。
+    pub fn from_account(role: &str, elevated: bool) -> Role {
+        match role {
+            _ if elevated => Role::Admin,
+            "admin" | "user" => Role::Author,
+            _ => Role::Guest,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum VfsOp {
     ReadDir,
@@ -63,6 +81,9 @@ pub enum VfsError {
     #[error("无效操作: {0}")]
     InvalidOperation(String),
 
+    #[error("元数据无效: {0}")]
+    InvalidMetadata(String),
+
     #[error("I/O错误: {0}")]
     IoError(#[from] io::Error),
 
@@ -80,6 +101,7 @@ impl From<VfsError> for io::Error {
             VfsError::NodeNotFound(msg) => io::Error::new(io::ErrorKind::NotFound, msg),
             VfsError::NodeExists(msg) => io::Error::new(io::ErrorKind::AlreadyExists, msg),
             VfsError::InvalidOperation(msg) => io::Error::new(io::ErrorKind::InvalidInput, msg),
+            VfsError::InvalidMetadata(msg) => io::Error::new(io::ErrorKind::InvalidData, msg),
             VfsError::IoError(err) => err,
             VfsError::DbError(err) => {
                 io::Error::new(io::ErrorKind::Other, format!("数据库错误: {}", err))
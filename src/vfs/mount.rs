@@ -0,0 +1,114 @@
+use crate::vfs::model::{User, VfsError, VfsNode};
+use async_trait::async_trait;
+use log::info;
+use std::sync::{Arc, RwLock};
+
+/// 挂载到 VFS 某个目录下的只读文件系统，内容在访问时生成，不保存在 `vfs_nodes` 中
+///
+/// `path` 是相对挂载点的路径，挂载点本身为空字符串；返回的节点名同样相对挂载点，
+/// 由挂载表换算为绝对路径。
+#[async_trait]
+pub trait MountProvider: Send + Sync {
+    /// 获取节点信息
+    async fn get_node(&self, path: &str) -> Result<VfsNode, VfsError>;
+
+    /// 列出目录内容
+    async fn list(&self, path: &str) -> Result<Vec<VfsNode>, VfsError>;
+
+    /// 读取文件内容，内容可以因用户而异（如 `self`）
+    async fn read_file(&self, user: &User, path: &str) -> Result<String, VfsError>;
+}
+
+struct Mount {
+    point: String,
+    provider: Arc<dyn MountProvider>,
+}
+
+/// 挂载表：把挂载点及其下的路径路由到对应的提供者
+pub struct MountTable {
+    mounts: RwLock<Vec<Mount>>,
+}
+
+/// 路径在挂载表中的解析结果
+pub struct Mounted {
+    point: String,
+    path: String,
+    provider: Arc<dyn MountProvider>,
+}
+
+impl MountTable {
+    pub fn new() -> Self {
+        Self {
+            mounts: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 挂载提供者，同一挂载点重复挂载时替换原来的提供者
+    pub fn mount(&self, point: &str, provider: Arc<dyn MountProvider>) {
+        let mut mounts = self.mounts.write().unwrap();
+        mounts.retain(|mount| mount.point != point);
+        mounts.push(Mount {
+            point: point.to_string(),
+            provider,
+        });
+        info!("挂载虚拟文件系统: {}", point);
+    }
+
+    /// 查找规范化路径所在的挂载点，嵌套挂载时取最长的挂载点
+    pub fn resolve(&self, path: &str) -> Option<Mounted> {
+        let mounts = self.mounts.read().unwrap();
+        mounts
+            .iter()
+            .filter_map(|mount| {
+                let rest = path.strip_prefix(&mount.point)?;
+                match rest.strip_prefix('/') {
+                    Some(rest) => Some((mount, rest)),
+                    None if rest.is_empty() => Some((mount, rest)),
+                    None => None,
+                }
+            })
+            .max_by_key(|(mount, _)| mount.point.len())
+            .map(|(mount, rest)| Mounted {
+                point: mount.point.clone(),
+                path: rest.to_string(),
+                provider: mount.provider.clone(),
+            })
+    }
+
+    /// 路径位于挂载点下时返回只读错误，用于拒绝写入、删除等操作
+    pub fn check_writable(&self, path: &str) -> Result<(), VfsError> {
+        match self.resolve(path) {
+            Some(mounted) => Err(VfsError::PermissionError(format!(
+                "{} 位于只读文件系统 {}",
+                path, mounted.point
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Mounted {
+    pub async fn get_node(&self) -> Result<VfsNode, VfsError> {
+        let node = self.provider.get_node(&self.path).await?;
+        Ok(self.absolute(node))
+    }
+
+    pub async fn list(&self) -> Result<Vec<VfsNode>, VfsError> {
+        let nodes = self.provider.list(&self.path).await?;
+        Ok(nodes.into_iter().map(|node| self.absolute(node)).collect())
+    }
+
+    pub async fn read_file(&self, user: &User) -> Result<String, VfsError> {
+        self.provider.read_file(user, &self.path).await
+    }
+
+    // 把提供者返回的相对节点名换算为绝对路径
+    fn absolute(&self, mut node: VfsNode) -> VfsNode {
+        node.name = if node.name.is_empty() {
+            self.point.clone()
+        } else {
+            format!("{}/{}", self.point, node.name)
+        };
+        node
+    }
+}
@@ -1,5 +1,5 @@
 use crate::vfs::{
-    model::{Role, User as VfsUser, VfsError, VfsNode, DEFAULT_DIR_PERM},
+    model::{Role, User as VfsUser, VfsError, VfsNode, DEFAULT_DIR_PERM, DEFAULT_FILE_PERM},
     storage::StorageBackend,
 };
 use async_trait::async_trait;
@@ -35,10 +35,13 @@ impl StorageBackend for PostgresBackend {
             None => return Err(VfsError::NodeNotFound(format!("目录不存在: {}", path))),
         };
 
-        // 获取所有子节点
+        // 获取所有子节点，大小由数据库计算，不读取文件内容
         let nodes = sqlx::query!(
             r#"
-            SELECT * FROM vfs_nodes WHERE parent_id = $1
+            SELECT id, parent_id, name, is_dir, owner_id, permissions, created_at, updated_at,
+                   COALESCE(octet_length(content), 0) AS "size!"
+            FROM vfs_nodes
+            WHERE parent_id = $1
             "#,
             parent_id
         )
@@ -55,6 +58,7 @@ impl StorageBackend for PostgresBackend {
                 is_dir: n.is_dir,
                 owner_id: n.owner_id,
                 permissions: n.permissions,
+                size: n.size as i64,
                 created_at: n.created_at,
                 updated_at: n.updated_at,
             })
@@ -146,11 +150,138 @@ impl StorageBackend for PostgresBackend {
             is_dir: node.is_dir,
             owner_id: node.owner_id,
             permissions: node.permissions,
+            size: 0,
             created_at: node.created_at,
             updated_at: node.updated_at,
         })
     }
 
+    async fn read_file(&self, path: &str) -> Result<String, VfsError> {
+        let node = sqlx::query!(
+            r#"
+            SELECT is_dir, content FROM vfs_nodes WHERE name = $1
+            "#,
+            path
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("读取文件失败: {}", e)))?;
+
+        match node {
+            Some(n) if n.is_dir => Err(VfsError::InvalidOperation(format!("是一个目录: {}", path))),
+            Some(n) => Ok(n.content.unwrap_or_default()),
+            None => Err(VfsError::NodeNotFound(format!("文件不存在: {}", path))),
+        }
+    }
+
+    async fn write_file(
+        &self,
+        path: &str,
+        content: &str,
+        user_id: String,
+    ) -> Result<VfsNode, VfsError> {
+        // 文件已存在时直接更新内容
+        let existing = sqlx::query!(
+            r#"
+            SELECT id, is_dir FROM vfs_nodes WHERE name = $1
+            "#,
+            path
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("查询文件失败: {}", e)))?;
+
+        let node = match existing {
+            Some(n) if n.is_dir => {
+                return Err(VfsError::InvalidOperation(format!("是一个目录: {}", path)))
+            }
+            Some(n) => sqlx::query!(
+                r#"
+                UPDATE vfs_nodes SET content = $1, updated_at = NOW()
+                WHERE id = $2
+                RETURNING id, parent_id, name, is_dir, owner_id, permissions, created_at, updated_at
+                "#,
+                content,
+                n.id
+            )
+            .fetch_one(&self.pool)
+            .await
+            .map(|n| VfsNode {
+                id: n.id,
+                parent_id: n.parent_id,
+                name: n.name,
+                is_dir: n.is_dir,
+                owner_id: n.owner_id,
+                permissions: n.permissions,
+                size: content.len() as i64,
+                created_at: n.created_at,
+                updated_at: n.updated_at,
+            })
+            .map_err(|e| VfsError::StorageError(format!("写入文件失败: {}", e)))?,
+            None => {
+                // 获取父节点
+                let parent_path = match path.trim_end_matches('/').rsplit_once('/') {
+                    Some(("", _)) | None => "/",
+                    Some((parent, _)) => parent,
+                };
+                let parent = sqlx::query!(
+                    r#"
+                    SELECT id, is_dir FROM vfs_nodes WHERE name = $1
+                    "#,
+                    parent_path
+                )
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| VfsError::StorageError(format!("查询父节点失败: {}", e)))?;
+
+                let parent_id = match parent {
+                    Some(p) if p.is_dir => p.id,
+                    Some(_) => {
+                        return Err(VfsError::InvalidOperation(format!(
+                            "不是目录: {}",
+                            parent_path
+                        )))
+                    }
+                    None => {
+                        return Err(VfsError::NodeNotFound(format!(
+                            "父目录不存在: {}",
+                            parent_path
+                        )))
+                    }
+                };
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO vfs_nodes (parent_id, name, is_dir, owner_id, permissions, content)
+                    VALUES ($1, $2, false, $3, $4, $5)
+                    RETURNING id, parent_id, name, is_dir, owner_id, permissions, created_at, updated_at
+                    "#,
+                    parent_id,
+                    path,
+                    user_id,
+                    DEFAULT_FILE_PERM,
+                    content
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map(|n| VfsNode {
+                    id: n.id,
+                    parent_id: n.parent_id,
+                    name: n.name,
+                    is_dir: n.is_dir,
+                    owner_id: n.owner_id,
+                    permissions: n.permissions,
+                    size: content.len() as i64,
+                    created_at: n.created_at,
+                    updated_at: n.updated_at,
+                })
+                .map_err(|e| VfsError::StorageError(format!("创建文件失败: {}", e)))?
+            }
+        };
+
+        Ok(node)
+    }
+
     async fn delete(&self, path: &str) -> Result<(), VfsError> {
         let result = sqlx::query!(
             r#"
@@ -170,14 +301,50 @@ impl StorageBackend for PostgresBackend {
     }
 
     async fn rename(&self, old_path: &str, new_path: &str) -> Result<(), VfsError> {
+        if old_path == "/" || new_path.starts_with(&format!("{}/", old_path)) {
+            return Err(VfsError::InvalidOperation(format!(
+                "不能把 {} 移动到 {}",
+                old_path, new_path
+            )));
+        }
+        let parent_path = match new_path.rsplit_once('/') {
+            Some(("", _)) | None => "/",
+            Some((parent, _)) => parent,
+        };
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| VfsError::StorageError(format!("开始事务失败: {}", e)))?;
+
+        let parent = sqlx::query!(
+            "SELECT id FROM vfs_nodes WHERE name = $1 AND is_dir",
+            parent_path
+        )
+        .fetch_optional(&mut tx)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("查询父节点失败: {}", e)))?
+        .ok_or_else(|| VfsError::NodeNotFound(format!("父目录不存在: {}", parent_path)))?;
+
+        let exists = sqlx::query!("SELECT id FROM vfs_nodes WHERE name = $1", new_path)
+            .fetch_optional(&mut tx)
+            .await
+            .map_err(|e| VfsError::StorageError(format!("查询节点失败: {}", e)))?;
+        if exists.is_some() {
+            return Err(VfsError::NodeExists(new_path.to_string()));
+        }
+
+        // 节点名称保存完整路径，子孙节点的路径前缀一并替换
         let result = sqlx::query!(
             r#"
-            UPDATE vfs_nodes SET name = $1 WHERE name = $2
+            UPDATE vfs_nodes SET name = $1 || substr(name, length($2) + 1)
+            WHERE name = $2 OR left(name, length($2) + 1) = $2 || '/'
             "#,
             new_path,
             old_path
         )
-        .execute(&self.pool)
+        .execute(&mut tx)
         .await
         .map_err(|e| VfsError::StorageError(format!("重命名节点失败: {}", e)))?;
 
@@ -185,13 +352,27 @@ impl StorageBackend for PostgresBackend {
             return Err(VfsError::NodeNotFound(format!("节点不存在: {}", old_path)));
         }
 
+        sqlx::query!(
+            "UPDATE vfs_nodes SET parent_id = $1, updated_at = NOW() WHERE name = $2",
+            parent.id,
+            new_path
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(|e| VfsError::StorageError(format!("更新父节点失败: {}", e)))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| VfsError::StorageError(format!("提交事务失败: {}", e)))?;
+
         Ok(())
     }
 
     async fn get_node(&self, path: &str) -> Result<VfsNode, VfsError> {
         let node = sqlx::query!(
             r#"
-            SELECT id, parent_id, name, is_dir, owner_id, permissions, created_at, updated_at
+            SELECT id, parent_id, name, is_dir, owner_id, permissions, created_at, updated_at,
+                   COALESCE(octet_length(content), 0) AS "size!"
             FROM vfs_nodes
             WHERE name = $1
             "#,
@@ -209,6 +390,7 @@ impl StorageBackend for PostgresBackend {
                 is_dir: n.is_dir,
                 owner_id: n.owner_id,
                 permissions: n.permissions,
+                size: n.size as i64,
                 created_at: n.created_at,
                 updated_at: n.updated_at,
             }),
@@ -9,10 +9,21 @@ pub trait StorageBackend: Send + Sync {
     /// 创建目录
     async fn create_dir(&self, path: &str, user_id: String) -> Result<VfsNode, VfsError>;
 
+    /// 读取文件内容
+    async fn read_file(&self, path: &str) -> Result<String, VfsError>;
+
+    /// 写入文件内容，文件不存在时创建
+    async fn write_file(
+        &self,
+        path: &str,
+        content: &str,
+        user_id: String,
+    ) -> Result<VfsNode, VfsError>;
+
     /// 删除节点
     async fn delete(&self, path: &str) -> Result<(), VfsError>;
 
-    /// 重命名节点
+    /// 重命名或移动节点，子孙节点随之移动
     async fn rename(&self, old_path: &str, new_path: &str) -> Result<(), VfsError>;
 
     /// 获取节点信息
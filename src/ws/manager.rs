@@ -0,0 +1,120 @@
+use log::{debug, info};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::types::{Event, ServerMessage};
+use crate::auth::validate_token;
+
+// 一个 WebSocket 连接
+struct Connection {
+    token: String,
+    // token 对应的用户 ID，访客为 None
+    user_id: Option<String>,
+    sender: UnboundedSender<ServerMessage>,
+}
+
+// WebSocket 连接管理器，用于向连接推送异步事件
+pub struct ConnectionManager {
+    connections: Mutex<HashMap<u64, Connection>>,
+    next_id: AtomicU64,
+}
+
+impl ConnectionManager {
+    pub fn new() -> Self {
+        info!("初始化 WebSocket 连接管理器");
+        Self {
+            connections: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// 登记新连接，返回连接编号
+    pub fn register(&self, token: &str, sender: UnboundedSender<ServerMessage>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut connections = self.connections.lock().unwrap();
+        connections.insert(
+            id,
+            Connection {
+                token: token.to_string(),
+                user_id: user_id(token),
+                sender,
+            },
+        );
+        info!(
+            "WebSocket 连接 {} 已建立，当前连接数: {}",
+            id,
+            connections.len()
+        );
+        id
+    }
+
+    /// 连接登录、登出后更新其 token
+    pub fn set_token(&self, id: u64, token: &str) {
+        if let Some(connection) = self.connections.lock().unwrap().get_mut(&id) {
+            connection.token = token.to_string();
+            connection.user_id = user_id(token);
+        }
+    }
+
+    pub fn unregister(&self, id: u64) {
+        let mut connections = self.connections.lock().unwrap();
+        connections.remove(&id);
+        info!(
+            "WebSocket 连接 {} 已关闭，当前连接数: {}",
+            id,
+            connections.len()
+        );
+    }
+
+    /// 向使用该 token 的所有连接推送事件，返回送达的连接数
+    pub fn send_to_token(&self, token: &str, event: Event) -> usize {
+        if token.is_empty() {
+            return 0;
+        }
+        let connections = self.connections.lock().unwrap();
+        let delivered = connections
+            .values()
+            .filter(|c| c.token == token)
+            .filter(|c| c.sender.send(ServerMessage::Event(event.clone())).is_ok())
+            .count();
+        debug!("推送事件 {:?}，送达 {} 个连接", event, delivered);
+        delivered
+    }
+
+    /// 向该用户的所有连接推送事件，返回送达的连接数
+    pub fn send_to_user(&self, user_id: &str, event: Event) -> usize {
+        let connections = self.connections.lock().unwrap();
+        let delivered = connections
+            .values()
+            .filter(|c| c.user_id.as_deref() == Some(user_id))
+            .filter(|c| c.sender.send(ServerMessage::Event(event.clone())).is_ok())
+            .count();
+        debug!("向用户 {} 推送事件，送达 {} 个连接", user_id, delivered);
+        delivered
+    }
+
+    /// 向所有已登录的连接推送事件，返回收到事件的用户 ID（去重）
+    pub fn broadcast(&self, event: Event) -> Vec<String> {
+        let connections = self.connections.lock().unwrap();
+        let mut users: Vec<String> = connections
+            .values()
+            .filter(|c| c.user_id.is_some())
+            .filter(|c| c.sender.send(ServerMessage::Event(event.clone())).is_ok())
+            .filter_map(|c| c.user_id.clone())
+            .collect();
+        users.sort();
+        users.dedup();
+        debug!("广播事件 {:?}，送达 {} 个用户", event, users.len());
+        users
+    }
+}
+
+// 从 token 中取出用户 ID，无效的 token 视为访客
+fn user_id(token: &str) -> Option<String> {
+    if token.is_empty() {
+        return None;
+    }
+    validate_token(token).ok().map(|claims| claims.sub)
+}
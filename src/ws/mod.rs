@@ -0,0 +1,291 @@
+mod manager;
+mod types;
+
+pub use manager::ConnectionManager;
+pub use types::{ClientMessage, Event, ServerMessage};
+
+use actix_web::http::header;
+use actix_web::{rt, web, HttpRequest, HttpResponse};
+use actix_ws::AggregatedMessage;
+use log::{debug, info, warn};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use crate::auth::{is_token_current, validate_token};
+use crate::command::{self, style, ClientInfo, SideEffect};
+
+// 单条消息的最大长度
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+// 未设置 FRONTEND_ORIGIN 时允许的前端来源，即 Vite 开发服务器
+const DEFAULT_FRONTEND_ORIGIN: &str = "http://localhost:5173";
+
+// 检查发起连接的页面来源是否在 `allowed`（逗号分隔）中
+//
+// 浏览器总会为 WebSocket 握手带上 Origin，且会自动附带查询参数中的 token，
+// 其他网站的页面不能借此以用户身份连接；不发送 Origin 的非浏览器客户端不受限制。
+fn origin_allowed(origin: Option<&str>, allowed: &str) -> bool {
+    match origin {
+        Some(origin) => allowed
+            .split(',')
+            .map(|allowed| allowed.trim().trim_end_matches('/'))
+            .any(|allowed| allowed == origin),
+        None => true,
+    }
+}
+
+#[derive(Deserialize)]
+pub struct WsQuery {
+    token: Option<String>,
+}
+
+// 校验 token，返回用户名；无效、已登出或已失效的 token 返回 None
+async fn username(state: &crate::AppState, token: &str) -> Option<String> {
+    let claims = validate_token(token).ok()?;
+    if state.auth_manager.is_token_blacklisted(token) || !is_token_current(&state.db, &claims).await
+    {
+        return None;
+    }
+    Some(claims.username)
+}
+
+/// WebSocket 终端：`GET /api/ws?token=...`
+///
+/// 浏览器无法为 WebSocket 设置请求头，token 可以放在查询参数中，也可以用
+/// `Authorization` 头或连接后发送 `auth` 消息；浏览器发起的连接只接受 `FRONTEND_ORIGIN`
+/// 中的页面来源。命令结束时发送 `result`，脚本执行过程中每条命令的 stdout 先以 `output`
+/// 消息流式发送，其他命令的 stdout 随 `result` 一次返回；输出中的样式为 ANSI 转义，
+/// 后台任务结束等事件以 `event` 消息推送。
+pub async fn handle_ws(
+    req: HttpRequest,
+    body: web::Payload,
+    query: web::Query<WsQuery>,
+    data: web::Data<crate::AppState>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let origin = req
+        .headers()
+        .get(header::ORIGIN)
+        .map(|v| v.to_str().unwrap_or_default());
+    let allowed =
+        std::env::var("FRONTEND_ORIGIN").unwrap_or_else(|_| DEFAULT_FRONTEND_ORIGIN.to_string());
+    if !origin_allowed(origin, &allowed) {
+        warn!("拒绝来自 {:?} 的 WebSocket 连接", origin);
+        return Ok(HttpResponse::Forbidden().finish());
+    }
+
+    let token = query
+        .token
+        .clone()
+        .or_else(|| {
+            req.headers()
+                .get("Authorization")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.strip_prefix("Bearer "))
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_default();
+
+    let client = ClientInfo::from_request("websocket", &req);
+    let (response, session, stream) = actix_ws::handle(&req, body)?;
+    info!("收到 WebSocket 连接请求");
+    rt::spawn(run(data.into_inner(), session, stream, token, client));
+    Ok(response)
+}
+
+// 处理一个连接直到关闭
+//
+// 读循环只负责接收消息和回复 ping，认证和命令交给连接的命令任务按收到的顺序依次执行，
+// 执行耗时的命令时连接仍能及时响应控制帧。
+async fn run(
+    state: Arc<crate::AppState>,
+    mut session: actix_ws::Session,
+    stream: actix_ws::MessageStream,
+    mut token: String,
+    client: ClientInfo,
+) {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<ServerMessage>();
+
+    // 写任务：把待发送的消息依次写入连接
+    let mut writer = session.clone();
+    rt::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            let text = match serde_json::to_string(&message) {
+                Ok(text) => text,
+                Err(e) => {
+                    warn!("消息序列化失败: {}", e);
+                    continue;
+                }
+            };
+            if writer.text(text).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let user = username(&state, &token).await;
+    if user.is_none() {
+        token.clear();
+    }
+    let id = state.connection_manager.register(&token, sender.clone());
+    let _ = sender.send(ServerMessage::Ready { user });
+
+    // 命令任务：连接关闭后执行完已收到的命令再退出
+    let (requests, mut pending) = mpsc::unbounded_channel::<ClientMessage>();
+    {
+        let state = state.clone();
+        let sender = sender.clone();
+        rt::spawn(async move {
+            while let Some(message) = pending.recv().await {
+                handle_message(&state, id, &sender, &mut token, &client, message).await;
+            }
+        });
+    }
+
+    let mut stream = stream
+        .aggregate_continuations()
+        .max_continuation_size(MAX_MESSAGE_SIZE);
+    while let Some(message) = stream.recv().await {
+        match message {
+            Ok(AggregatedMessage::Text(text)) => {
+                // 不是 JSON 的文本按一行命令处理
+                let message = serde_json::from_str::<ClientMessage>(&text).unwrap_or_else(|_| {
+                    ClientMessage::Command {
+                        id: 0,
+                        command: text.to_string(),
+                        session_id: String::new(),
+                    }
+                });
+                match message {
+                    ClientMessage::Ping => {
+                        let _ = sender.send(ServerMessage::Pong);
+                    }
+                    message => {
+                        if requests.send(message).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+            Ok(AggregatedMessage::Ping(bytes)) => {
+                if session.pong(&bytes).await.is_err() {
+                    break;
+                }
+            }
+            Ok(AggregatedMessage::Close(reason)) => {
+                debug!("客户端关闭连接: {:?}", reason);
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                warn!("WebSocket 协议错误: {}", e);
+                break;
+            }
+        }
+    }
+
+    state.connection_manager.unregister(id);
+    let _ = session.close(None).await;
+}
+
+async fn handle_message(
+    state: &Arc<crate::AppState>,
+    connection: u64,
+    sender: &UnboundedSender<ServerMessage>,
+    token: &mut String,
+    client: &ClientInfo,
+    message: ClientMessage,
+) {
+    match message {
+        ClientMessage::Auth { token: new_token } => match username(state, &new_token).await {
+            Some(user) => {
+                *token = new_token;
+                state.connection_manager.set_token(connection, token);
+                let _ = sender.send(ServerMessage::Ready { user: Some(user) });
+            }
+            None => {
+                warn!("WebSocket 认证失败");
+                let _ = sender.send(ServerMessage::Error {
+                    message: "Token 无效".to_string(),
+                });
+            }
+        },
+        ClientMessage::Command {
+            id,
+            command,
+            session_id,
+        } => {
+            info!(
+                "收到 WebSocket 命令: {} (id: {})",
+                state.command_registry.redact(&command),
+                id
+            );
+
+            // 转发执行过程中的流式输出
+            let (output, mut chunks) = mpsc::unbounded_channel::<String>();
+            let forward = sender.clone();
+            let forwarder = rt::spawn(async move {
+                let mut streamed = false;
+                while let Some(text) = chunks.recv().await {
+                    streamed = true;
+                    let _ = forward.send(ServerMessage::Output {
+                        id,
+                        text: style::to_ansi(&text),
+                    });
+                }
+                streamed
+            });
+            let result =
+                command::execute(state, &command, token, &session_id, Some(&output), client).await;
+            drop(output);
+            let streamed = forwarder.await.unwrap_or(false);
+
+            // 连接上登录或登出后，后续命令使用新的 token
+            for effect in &result.effects {
+                match effect {
+                    SideEffect::SetToken(new_token) => *token = new_token.clone(),
+                    SideEffect::ClearToken => token.clear(),
+                    _ => continue,
+                }
+                state.connection_manager.set_token(connection, token);
+            }
+
+            let _ = sender.send(ServerMessage::Result {
+                id,
+                success: result.success(),
+                exit_code: result.exit_code,
+                stdout: if streamed {
+                    String::new()
+                } else {
+                    style::to_ansi(&result.stdout)
+                },
+                stderr: style::to_ansi(&result.stderr),
+                error: result.error,
+                data: result.data,
+                effects: result.effects,
+            });
+        }
+        // 读循环直接回复，不进入命令队列
+        ClientMessage::Ping => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origin_allowed_accepts_only_configured_frontends() {
+        let allowed = "https://blog.example.com, http://localhost:5173/";
+        assert!(origin_allowed(Some("https://blog.example.com"), allowed));
+        assert!(origin_allowed(Some("http://localhost:5173"), allowed));
+        assert!(origin_allowed(None, allowed));
+        assert!(!origin_allowed(Some("https://evil.example.com"), allowed));
+        assert!(!origin_allowed(
+            Some("https://blog.example.com.evil.com"),
+            allowed
+        ));
+        assert!(!origin_allowed(Some(""), allowed));
+        assert!(!origin_allowed(Some("null"), allowed));
+    }
+}
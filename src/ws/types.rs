@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::command::{ErrorKind, SideEffect};
+use crate::job::JobStatus;
+
+/// 客户端发送的消息
+///
+/// 不是 JSON 的文本帧按一行命令处理，便于用 `websocat` 等工具直接调试。
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// 连接建立后登录或切换 token
+    Auth {
+        token: String,
+    },
+    /// 执行一行命令，`id` 由客户端指定，用于对应输出和结果
+    Command {
+        #[serde(default)]
+        id: u64,
+        command: String,
+        #[serde(default)]
+        session_id: String,
+    },
+    Ping,
+}
+
+/// 服务端推送的异步事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// 后台任务结束
+    JobFinished {
+        job: u64,
+        command: String,
+        status: JobStatus,
+        exit_code: i32,
+    },
+    /// 其他用户用 `write` 发来的消息，`broadcast` 为真时是管理员用 `wall` 发布的公告
+    Message {
+        from: String,
+        text: String,
+        broadcast: bool,
+        sent_at: DateTime<Utc>,
+    },
+}
+
+/// 服务端发送的消息
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// 连接或认证完成，`user` 为当前用户名，访客为空
+    Ready {
+        user: Option<String>,
+    },
+    /// 命令执行过程中的一段 stdout
+    Output {
+        id: u64,
+        text: String,
+    },
+    /// 命令执行结束；已通过 `output` 发送过的 stdout 不再重复
+    Result {
+        id: u64,
+        success: bool,
+        exit_code: i32,
+        stdout: String,
+        stderr: String,
+        error: Option<ErrorKind>,
+        data: Option<Value>,
+        effects: Vec<SideEffect>,
+    },
+    Event(Event),
+    Error {
+        message: String,
+    },
+    Pong,
+}